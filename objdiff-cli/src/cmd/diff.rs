@@ -9,7 +9,7 @@ use std::{
         Arc,
     },
     task::{Wake, Waker},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Context, Result};
@@ -21,30 +21,40 @@ use crossterm::{
         disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
     },
 };
+use globset::{Glob, GlobSetBuilder};
 use objdiff_core::{
-    bindings::diff::DiffResult,
+    bindings::diff::{DiffResult, UnitDiffResult},
     build::{
         watcher::{create_watcher, Watcher},
         BuildConfig,
     },
-    config::{build_globset, default_watch_patterns, ProjectConfig, ProjectObject},
+    config::{build_globset, default_watch_patterns, ProjectConfig, ProjectObject, TargetRegion},
     diff,
     diff::ObjDiff,
+    index::ProjectIndex,
     jobs::{
         objdiff::{start_build, ObjDiffConfig},
+        symbol_index::{start_symbol_index, SymbolIndexConfig},
         Job, JobQueue, JobResult,
     },
     obj,
-    obj::ObjInfo,
+    obj::{map::LinkerMap, ObjInfo},
 };
 use ratatui::prelude::*;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use tracing::{info, warn};
 
 use crate::{
     util::{
-        output::{write_output, OutputFormat},
+        output::{write_output, write_output_stream, OutputFormat},
         term::crossterm_panic_handler,
     },
-    views::{function_diff::FunctionDiffUi, EventControlFlow, EventResult, UiView},
+    views::{
+        data_diff::{find_data_symbol, DataDiffUi},
+        function_diff::FunctionDiffUi,
+        symbol_search::SymbolSearchUi,
+        EventControlFlow, EventResult, UiView,
+    },
 };
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -66,25 +76,47 @@ pub struct Args {
     #[argp(switch, short = 'x')]
     /// Relax relocation diffs
     relax_reloc_diffs: bool,
+    #[argp(switch, short = 'n')]
+    /// Tolerate register renaming
+    register_renaming: bool,
+    #[argp(option, from_str_fn(parse_algorithm))]
+    /// Instruction alignment algorithm (patience, myers, operand) (default: patience)
+    algorithm: Option<diff::DiffAlgorithm>,
+    #[argp(option)]
+    /// Time budget in milliseconds for aligning each function's instructions
+    time_budget: Option<u32>,
     #[argp(option, short = 'o')]
     /// Output file (one-shot mode) ("-" for stdout)
     output: Option<PathBuf>,
     #[argp(option)]
     /// Output format (json, json-pretty, proto) (default: json)
     format: Option<String>,
+    #[argp(option, short = 'b')]
+    /// Unit name or glob pattern to diff in batch mode (repeatable)
+    batch: Vec<String>,
+    #[argp(switch)]
+    /// Batch mode: write all units to a single output stream instead of a directory
+    combined: bool,
     #[argp(positional)]
     /// Function symbol to diff
     symbol: Option<String>,
 }
 
+fn parse_algorithm(value: &str) -> Result<diff::DiffAlgorithm, String> {
+    diff::DiffAlgorithm::from_str(value).map_err(|_| "Invalid diff algorithm".to_string())
+}
+
 pub fn run(args: Args) -> Result<()> {
-    let (target_path, base_path, project_config) = match (
+    if !args.batch.is_empty() {
+        return run_batch(&args);
+    }
+    let (target_path, base_path, target_region, linker_map, project_config) = match (
         &args.target,
         &args.base,
         &args.project,
         &args.unit,
     ) {
-        (Some(t), Some(b), None, None) => (Some(t.clone()), Some(b.clone()), None),
+        (Some(t), Some(b), None, None) => (Some(t.clone()), Some(b.clone()), None, None, None),
         (None, None, p, u) => {
             let project = match p {
                 Some(project) => project.clone(),
@@ -98,14 +130,14 @@ pub fn run(args: Args) -> Result<()> {
             let mut project_config = project_config.with_context(|| {
                 format!("Reading project config {}", project_config_info.path.display())
             })?;
+            let linker_map = load_linker_map(&mut project_config, &project)?;
+            // Resolve all units up front, the symbol search indexes the whole project
+            let target_dir = project_config.target_dir.clone();
+            let base_dir = project_config.base_dir.clone();
+            for obj in project_config.units_mut() {
+                obj.resolve_paths(&project, target_dir.as_deref(), base_dir.as_deref());
+            }
             let object = {
-                let resolve_paths = |o: &mut ProjectObject| {
-                    o.resolve_paths(
-                        &project,
-                        project_config.target_dir.as_deref(),
-                        project_config.base_dir.as_deref(),
-                    )
-                };
                 if let Some(u) = u {
                     let unit_path =
                         PathBuf::from_str(u).ok().and_then(|p| fs::canonicalize(p).ok());
@@ -117,14 +149,11 @@ pub fn run(args: Args) -> Result<()> {
                         .iter_mut()
                         .find_map(|obj| {
                             if obj.name.as_deref() == Some(u) {
-                                resolve_paths(obj);
                                 return Some(obj);
                             }
 
                             let up = unit_path.as_deref()?;
 
-                            resolve_paths(obj);
-
                             if [&obj.base_path, &obj.target_path]
                                 .into_iter()
                                 .filter_map(|p| p.as_ref().and_then(|p| p.canonicalize().ok()))
@@ -150,12 +179,14 @@ pub fn run(args: Args) -> Result<()> {
                         .iter_mut()
                         .enumerate()
                     {
-                        resolve_paths(obj);
-
-                        if obj
-                            .target_path
+                        // Linked executables are shared between units, so search the base instead
+                        let path = match &obj.target_region {
+                            Some(_) => &obj.base_path,
+                            None => &obj.target_path,
+                        };
+                        if path
                             .as_deref()
-                            .map(|o| obj::read::has_function(o, symbol_name))
+                            .map(|o| obj::read::has_symbol(o, symbol_name))
                             .transpose()?
                             .unwrap_or(false)
                         {
@@ -181,35 +212,73 @@ pub fn run(args: Args) -> Result<()> {
             };
             let target_path = object.target_path.clone();
             let base_path = object.base_path.clone();
-            (target_path, base_path, Some(project_config))
+            let target_region = object.target_region.clone();
+            (target_path, base_path, target_region, linker_map, Some(project_config))
         }
         _ => bail!("Either target and base or project and unit must be specified"),
     };
 
     if let Some(output) = &args.output {
-        run_oneshot(&args, output, target_path.as_deref(), base_path.as_deref())
+        let score_weights =
+            project_config.as_ref().and_then(|p| p.score_weights).unwrap_or_default();
+        run_oneshot(
+            &args,
+            output,
+            target_path.as_deref(),
+            base_path.as_deref(),
+            target_region.as_ref(),
+            linker_map.as_ref(),
+            score_weights,
+        )
     } else {
-        run_interactive(args, target_path, base_path, project_config)
+        run_interactive(args, target_path, base_path, target_region, linker_map, project_config)
     }
 }
 
+/// Reads the project's linker map, if configured, and adds the units discovered from it.
+fn load_linker_map(
+    project_config: &mut ProjectConfig,
+    project: &Path,
+) -> Result<Option<LinkerMap>> {
+    let linker_map = obj::map::read_project_map(project_config, project)?;
+    if let Some(linker_map) = &linker_map {
+        let units = obj::map::discover_units(linker_map, project_config);
+        project_config.units_mut().extend(units);
+    }
+    Ok(linker_map)
+}
+
 fn run_oneshot(
     args: &Args,
     output: &Path,
     target_path: Option<&Path>,
     base_path: Option<&Path>,
+    target_region: Option<&TargetRegion>,
+    linker_map: Option<&LinkerMap>,
+    score_weights: diff::DiffScoreWeights,
 ) -> Result<()> {
     let output_format = OutputFormat::from_option(args.format.as_deref())?;
     let config = diff::DiffObjConfig {
         relax_reloc_diffs: args.relax_reloc_diffs,
+        register_renaming: args.register_renaming,
+        score_weights,
+        diff_algorithm: args.algorithm.unwrap_or_default(),
+        diff_time_budget_ms: args.time_budget,
         ..Default::default() // TODO
     };
-    let target = target_path
-        .map(|p| obj::read::read(p, &config).with_context(|| format!("Loading {}", p.display())))
+    let mut target = target_path
+        .map(|p| {
+            obj::read::read_target(p, target_region, linker_map, &config)
+                .with_context(|| format!("Loading {}", p.display()))
+        })
         .transpose()?;
-    let base = base_path
+    let mut base = base_path
         .map(|p| obj::read::read(p, &config).with_context(|| format!("Loading {}", p.display())))
         .transpose()?;
+    if let Some(linker_map) = linker_map {
+        let unit = target_region.and_then(|r| r.unit.as_deref());
+        linker_map.apply(target.as_mut(), base.as_mut(), unit);
+    }
     let result = diff::diff_objs(&config, target.as_ref(), base.as_ref(), None)?;
     let left = target.as_ref().and_then(|o| result.left.as_ref().map(|d| (o, d)));
     let right = base.as_ref().and_then(|o| result.right.as_ref().map(|d| (o, d)));
@@ -217,6 +286,138 @@ fn run_oneshot(
     Ok(())
 }
 
+fn run_batch(args: &Args) -> Result<()> {
+    let output_format = OutputFormat::from_option(args.format.as_deref())?;
+    let Some(output) = args.output.as_deref() else {
+        bail!("Batch mode requires an output path (-o)");
+    };
+    if args.target.is_some() || args.base.is_some() || args.unit.is_some() {
+        bail!("Batch mode can't be combined with target, base or unit");
+    }
+    let project = match &args.project {
+        Some(project) => project.clone(),
+        _ => std::env::current_dir().context("Failed to get the current directory")?,
+    };
+    let Some((project_config, project_config_info)) =
+        objdiff_core::config::try_project_config(&project)
+    else {
+        bail!("Project config not found in {}", &project.display())
+    };
+    let mut project_config = project_config.with_context(|| {
+        format!("Reading project config {}", project_config_info.path.display())
+    })?;
+    let linker_map = load_linker_map(&mut project_config, &project)?;
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in &args.batch {
+        builder
+            .add(Glob::new(pattern).with_context(|| format!("Invalid unit pattern: {}", pattern))?);
+    }
+    let globset = builder.build()?;
+    let target_dir = project_config.target_dir.clone();
+    let base_dir = project_config.base_dir.clone();
+    let units = project_config
+        .units
+        .as_deref_mut()
+        .unwrap_or_default()
+        .iter_mut()
+        .filter(|o| globset.is_match(o.name()))
+        .collect::<Vec<_>>();
+    if units.is_empty() {
+        bail!("No units matched: {}", args.batch.join(", "));
+    }
+    info!("Diffing {} units (using {} threads)", units.len(), rayon::current_num_threads());
+
+    let config = diff::DiffObjConfig {
+        relax_reloc_diffs: args.relax_reloc_diffs,
+        register_renaming: args.register_renaming,
+        score_weights: project_config.score_weights.unwrap_or_default(),
+        diff_algorithm: args.algorithm.unwrap_or_default(),
+        diff_time_budget_ms: args.time_budget,
+        ..Default::default() // TODO
+    };
+    let combined = args.combined || output == Path::new("-");
+    let start = Instant::now();
+    let results = units
+        .into_par_iter()
+        .map(|object| -> Result<Option<UnitDiffResult>> {
+            object.resolve_paths(&project, target_dir.as_deref(), base_dir.as_deref());
+            let Some(result) = diff_unit(object, &config, linker_map.as_ref())? else {
+                return Ok(None);
+            };
+            if combined {
+                return Ok(Some(result));
+            }
+            let path = output.join(format!("{}.{}", result.unit_name, output_format.extension()));
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+            }
+            write_output(&result, Some(&path), output_format)?;
+            Ok(None)
+        })
+        .collect::<Result<Vec<Option<UnitDiffResult>>>>()?;
+    if combined {
+        write_output_stream(results.iter().flatten(), Some(output), output_format)?;
+    }
+    let duration = start.elapsed();
+    info!("Diffed units in {}.{:03}s", duration.as_secs(), duration.subsec_millis());
+    Ok(())
+}
+
+fn diff_unit(
+    object: &ProjectObject,
+    config: &diff::DiffObjConfig,
+    linker_map: Option<&LinkerMap>,
+) -> Result<Option<UnitDiffResult>> {
+    match (&object.target_path, &object.base_path) {
+        (None, Some(_)) if !object.complete().unwrap_or(false) => {
+            warn!("Skipping object without target: {}", object.name());
+            return Ok(None);
+        }
+        (None, None) => {
+            warn!("Skipping object without target or base: {}", object.name());
+            return Ok(None);
+        }
+        _ => {}
+    }
+    // Skip objects that haven't been built yet
+    let missing = [&object.target_path, &object.base_path]
+        .into_iter()
+        .flatten()
+        .find(|p| !obj::read::split_member_path(p).0.exists());
+    if let Some(path) = missing {
+        warn!("Skipping object {}: {} not found", object.name(), path.display());
+        return Ok(None);
+    }
+    let mut config = config.clone();
+    config.symbol_mappings.mappings = object.symbol_mappings.clone().unwrap_or_default();
+    let mut target = object
+        .target_path
+        .as_deref()
+        .map(|p| {
+            obj::read::read_target(p, object.target_region.as_ref(), linker_map, &config)
+                .with_context(|| format!("Loading {}", p.display()))
+        })
+        .transpose()?;
+    let mut base = object
+        .base_path
+        .as_deref()
+        .map(|p| obj::read::read(p, &config).with_context(|| format!("Loading {}", p.display())))
+        .transpose()?;
+    if let Some(linker_map) = linker_map {
+        let unit = object.target_region.as_ref().and_then(|r| r.unit.as_deref());
+        linker_map.apply(target.as_mut(), base.as_mut(), unit);
+    }
+    let result = diff::diff_objs(&config, target.as_ref(), base.as_ref(), None)?;
+    let left = target.as_ref().and_then(|o| result.left.as_ref().map(|d| (o, d)));
+    let right = base.as_ref().and_then(|o| result.right.as_ref().map(|d| (o, d)));
+    Ok(Some(UnitDiffResult {
+        unit_name: object.name().to_string(),
+        result: Some(DiffResult::new(left, right)),
+    }))
+}
+
 pub struct AppState {
     pub jobs: JobQueue,
     pub waker: Arc<TermWaker>,
@@ -224,14 +425,21 @@ pub struct AppState {
     pub project_config: Option<ProjectConfig>,
     pub target_path: Option<PathBuf>,
     pub base_path: Option<PathBuf>,
+    pub target_region: Option<TargetRegion>,
+    pub linker_map: Option<Arc<LinkerMap>>,
     pub left_obj: Option<(ObjInfo, ObjDiff)>,
     pub right_obj: Option<(ObjInfo, ObjDiff)>,
     pub prev_obj: Option<(ObjInfo, ObjDiff)>,
     pub reload_time: Option<time::OffsetDateTime>,
     pub time_format: Vec<time::format_description::FormatItem<'static>>,
     pub relax_reloc_diffs: bool,
+    pub register_renaming: bool,
+    pub diff_algorithm: diff::DiffAlgorithm,
+    pub diff_time_budget_ms: Option<u32>,
     pub watcher: Option<Watcher>,
     pub modified: Arc<AtomicBool>,
+    /// Symbols of all project units, for the symbol search
+    pub index: Option<Box<ProjectIndex>>,
 }
 
 fn create_objdiff_config(state: &AppState) -> ObjDiffConfig {
@@ -257,13 +465,25 @@ fn create_objdiff_config(state: &AppState) -> ObjDiffConfig {
             .is_some_and(|p| p.build_target.unwrap_or(false)),
         target_path: state.target_path.clone(),
         base_path: state.base_path.clone(),
+        target_region: state.target_region.clone(),
+        linker_map: state.linker_map.clone(),
         diff_obj_config: diff::DiffObjConfig {
             relax_reloc_diffs: state.relax_reloc_diffs,
+            register_renaming: state.register_renaming,
+            score_weights: state
+                .project_config
+                .as_ref()
+                .and_then(|p| p.score_weights)
+                .unwrap_or_default(),
+            diff_algorithm: state.diff_algorithm,
+            diff_time_budget_ms: state.diff_time_budget_ms,
             ..Default::default() // TODO
         },
         symbol_mappings: Default::default(),
         selecting_left: None,
         selecting_right: None,
+        last_build: None,
+        compare_base: None,
     }
 }
 
@@ -274,6 +494,51 @@ impl AppState {
         Ok(())
     }
 
+    fn start_symbol_index(&mut self) {
+        let Some(project_config) = &self.project_config else {
+            return;
+        };
+        let config = SymbolIndexConfig {
+            units: project_config.units().to_vec(),
+            linker_map: self.linker_map.clone(),
+            diff_obj_config: create_objdiff_config(self).diff_obj_config,
+        };
+        self.jobs.push_once(Job::SymbolIndex, || {
+            start_symbol_index(Waker::from(self.waker.clone()), config)
+        });
+    }
+
+    /// Path to the selected unit's source file, if the project config specifies it.
+    pub fn source_path(&self) -> Option<PathBuf> {
+        let object = self
+            .project_config
+            .as_ref()?
+            .units()
+            .iter()
+            .find(|o| o.target_path == self.target_path && o.base_path == self.base_path)?;
+        let source_path = object.source_path()?;
+        Some(match &self.project_dir {
+            Some(project_dir) => project_dir.join(source_path),
+            None => PathBuf::from(source_path),
+        })
+    }
+
+    /// Switches to another project unit. Returns false if the unit doesn't exist.
+    fn select_unit(&mut self, name: &str) -> bool {
+        let Some(object) =
+            self.project_config.as_ref().and_then(|c| c.units().iter().find(|o| o.name() == name))
+        else {
+            return false;
+        };
+        self.target_path = object.target_path.clone();
+        self.base_path = object.base_path.clone();
+        self.target_region = object.target_region.clone();
+        self.left_obj = None;
+        self.right_obj = None;
+        self.prev_obj = None;
+        true
+    }
+
     fn check_jobs(&mut self) -> Result<bool> {
         let mut redraw = false;
         self.jobs.collect_results();
@@ -290,6 +555,10 @@ impl AppState {
                 JobResult::CheckUpdate(_) => todo!("CheckUpdate"),
                 JobResult::Update(_) => todo!("Update"),
                 JobResult::CreateScratch(_) => todo!("CreateScratch"),
+                JobResult::SymbolIndex(result) => {
+                    self.index = result;
+                    redraw = true;
+                }
             }
         }
         Ok(redraw)
@@ -309,9 +578,13 @@ fn run_interactive(
     args: Args,
     target_path: Option<PathBuf>,
     base_path: Option<PathBuf>,
+    target_region: Option<TargetRegion>,
+    linker_map: Option<LinkerMap>,
     project_config: Option<ProjectConfig>,
 ) -> Result<()> {
-    let Some(symbol_name) = &args.symbol else { bail!("Interactive mode requires a symbol name") };
+    let Some(mut symbol_name) = args.symbol.clone() else {
+        bail!("Interactive mode requires a symbol name")
+    };
     let time_format = time::format_description::parse_borrowed::<2>("[hour]:[minute]:[second]")
         .context("Failed to parse time format")?;
     let mut state = AppState {
@@ -321,14 +594,20 @@ fn run_interactive(
         project_config,
         target_path,
         base_path,
+        target_region,
+        linker_map: linker_map.map(Arc::new),
         left_obj: None,
         right_obj: None,
         prev_obj: None,
         reload_time: None,
         time_format,
         relax_reloc_diffs: args.relax_reloc_diffs,
+        register_renaming: args.register_renaming,
+        diff_algorithm: args.algorithm.unwrap_or_default(),
+        diff_time_budget_ms: args.time_budget,
         watcher: None,
         modified: Default::default(),
+        index: None,
     };
     if let Some(project_dir) = &state.project_dir {
         let watch_patterns = state
@@ -346,6 +625,8 @@ fn run_interactive(
     }
     let mut view: Box<dyn UiView> =
         Box::new(FunctionDiffUi { symbol_name: symbol_name.clone(), ..Default::default() });
+    // Shown over the current view while open
+    let mut search: Option<SymbolSearchUi> = None;
     state.reload()?;
 
     crossterm_panic_handler();
@@ -360,11 +641,15 @@ fn run_interactive(
     let mut terminal = Terminal::new(backend)?;
 
     let mut result = EventResult { redraw: true, ..Default::default() };
+    let mut view_selected = false;
     'outer: loop {
         if result.redraw {
             terminal.draw(|f| loop {
                 result.redraw = false;
-                view.draw(&state, f, &mut result);
+                match &mut search {
+                    Some(search) => search.draw(&state, f, &mut result),
+                    None => view.draw(&state, f, &mut result),
+                }
                 result.click_xy = None;
                 if !result.redraw {
                     break;
@@ -375,13 +660,47 @@ fn run_interactive(
         }
         loop {
             if event::poll(Duration::from_millis(100))? {
-                match view.handle_event(&mut state, event::read()?) {
+                let event = event::read()?;
+                let flow = match &mut search {
+                    Some(search) => search.handle_event(&mut state, event),
+                    None => view.handle_event(&mut state, event),
+                };
+                match flow {
                     EventControlFlow::Break => break 'outer,
                     EventControlFlow::Continue(r) => result = r,
                     EventControlFlow::Reload => {
                         state.reload()?;
                         result.redraw = true;
                     }
+                    EventControlFlow::OpenSearch => {
+                        if state.project_config.is_some() {
+                            // Rebuild the index to pick up the current match percentages
+                            state.start_symbol_index();
+                            search = Some(SymbolSearchUi::default());
+                        }
+                        result.redraw = true;
+                    }
+                    EventControlFlow::CloseSearch => {
+                        search = None;
+                        result.redraw = true;
+                    }
+                    EventControlFlow::Navigate(symbol) => {
+                        if state.select_unit(&symbol.unit) {
+                            search = None;
+                            symbol_name = symbol.name().to_string();
+                            view = Box::new(FunctionDiffUi {
+                                symbol_name: symbol_name.clone(),
+                                ..Default::default()
+                            });
+                            view_selected = false;
+                            crossterm::execute!(
+                                stdout(),
+                                SetTitle(format!("{} - objdiff", symbol_name))
+                            )?;
+                            state.reload()?;
+                        }
+                        result.redraw = true;
+                    }
                 }
                 break;
             } else if state.waker.0.swap(false, Ordering::Relaxed) {
@@ -394,7 +713,24 @@ fn run_interactive(
         }
         if state.check_jobs()? {
             result.redraw = true;
-            view.reload(&state)?;
+            // Objects are cleared while switching units, wait for them to be loaded
+            if view_selected || state.left_obj.is_some() || state.right_obj.is_some() {
+                // The symbol kind is only known once the objects have been loaded
+                if !view_selected {
+                    view_selected = true;
+                    let is_data = [&state.left_obj, &state.right_obj]
+                        .into_iter()
+                        .flatten()
+                        .any(|(obj, _)| find_data_symbol(obj, &symbol_name).is_some());
+                    if is_data {
+                        view = Box::new(DataDiffUi {
+                            symbol_name: symbol_name.clone(),
+                            ..Default::default()
+                        });
+                    }
+                }
+                view.reload(&state)?;
+            }
         }
     }
 
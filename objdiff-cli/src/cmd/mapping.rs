@@ -0,0 +1,239 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use argp::FromArgs;
+use objdiff_core::{
+    config::{
+        save_unit_symbol_mappings, try_project_config, ProjectConfig, ProjectConfigInfo,
+        ProjectObject, SymbolMappings,
+    },
+    diff,
+    diff::{pairing::MIN_PAIRING_CONFIDENCE, SymbolPairingMode},
+    obj,
+};
+use tracing::{info, warn};
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Edit the symbol mappings of a project unit.
+#[argp(subcommand, name = "mapping")]
+pub struct Args {
+    #[argp(subcommand)]
+    command: SubCommand,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argp(subcommand)]
+enum SubCommand {
+    Add(AddArgs),
+    Remove(RemoveArgs),
+    List(ListArgs),
+    Suggest(SuggestArgs),
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Map a target symbol to a base symbol.
+#[argp(subcommand, name = "add")]
+struct AddArgs {
+    #[argp(option, short = 'p')]
+    /// Project directory
+    project: Option<PathBuf>,
+    #[argp(option, short = 'u')]
+    /// Unit name within project
+    unit: String,
+    #[argp(positional)]
+    /// Target symbol name
+    target: String,
+    #[argp(positional)]
+    /// Base symbol name
+    base: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Remove the mapping of a target or base symbol.
+#[argp(subcommand, name = "remove")]
+struct RemoveArgs {
+    #[argp(option, short = 'p')]
+    /// Project directory
+    project: Option<PathBuf>,
+    #[argp(option, short = 'u')]
+    /// Unit name within project
+    unit: String,
+    #[argp(positional)]
+    /// Target or base symbol name
+    symbol: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// List the symbol mappings of a unit.
+#[argp(subcommand, name = "list")]
+struct ListArgs {
+    #[argp(option, short = 'p')]
+    /// Project directory
+    project: Option<PathBuf>,
+    #[argp(option, short = 'u')]
+    /// Unit name within project
+    unit: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Suggest mappings for symbols that couldn't be matched by name.
+#[argp(subcommand, name = "suggest")]
+struct SuggestArgs {
+    #[argp(option, short = 'p')]
+    /// Project directory
+    project: Option<PathBuf>,
+    #[argp(option, short = 'u')]
+    /// Unit name within project
+    unit: String,
+    #[argp(option, short = 'm')]
+    /// Minimum confidence to list, from 0.5 to 1.0 (default: 0.5)
+    min_confidence: Option<f32>,
+    #[argp(switch)]
+    /// Add the listed suggestions to the unit's mappings
+    apply: bool,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    match args.command {
+        SubCommand::Add(args) => add(args),
+        SubCommand::Remove(args) => remove(args),
+        SubCommand::List(args) => list(args),
+        SubCommand::Suggest(args) => suggest(args),
+    }
+}
+
+struct LoadedUnit {
+    project_dir: PathBuf,
+    config: ProjectConfig,
+    info: ProjectConfigInfo,
+    index: usize,
+}
+
+impl LoadedUnit {
+    fn object(&self) -> &ProjectObject { &self.config.units()[self.index] }
+
+    fn mappings(&self) -> SymbolMappings {
+        self.object().symbol_mappings.clone().unwrap_or_default()
+    }
+
+    fn save(&self, mappings: &SymbolMappings) -> Result<()> {
+        save_unit_symbol_mappings(&self.info, self.object().name(), mappings)
+            .with_context(|| format!("Failed to save {}", self.info.path.display()))?;
+        Ok(())
+    }
+}
+
+fn load_unit(project: Option<&Path>, unit: &str) -> Result<LoadedUnit> {
+    let project_dir = match project {
+        Some(project) => project.to_path_buf(),
+        None => std::env::current_dir().context("Failed to get the current directory")?,
+    };
+    let Some((config, info)) = try_project_config(&project_dir) else {
+        bail!("Project config not found in {}", project_dir.display())
+    };
+    let config =
+        config.with_context(|| format!("Reading project config {}", info.path.display()))?;
+    let index = config
+        .units()
+        .iter()
+        .position(|u| u.name() == unit)
+        .ok_or_else(|| anyhow!("Unit not found: {}", unit))?;
+    Ok(LoadedUnit { project_dir, config, info, index })
+}
+
+fn add(args: AddArgs) -> Result<()> {
+    let unit = load_unit(args.project.as_deref(), &args.unit)?;
+    let mut mappings = unit.mappings();
+    if let Some(base) = mappings.get_by_left(&args.target) {
+        info!("Replacing mapping {} -> {}", args.target, base);
+    }
+    if let Some(target) = mappings.get_by_right(&args.base) {
+        info!("Replacing mapping {} -> {}", target, args.base);
+    }
+    mappings.insert(args.target.clone(), args.base.clone());
+    unit.save(&mappings)?;
+    info!("Mapped {} -> {}", args.target, args.base);
+    Ok(())
+}
+
+fn remove(args: RemoveArgs) -> Result<()> {
+    let unit = load_unit(args.project.as_deref(), &args.unit)?;
+    let mut mappings = unit.mappings();
+    let removed = mappings
+        .remove_by_left(&args.symbol)
+        .or_else(|| mappings.remove_by_right(&args.symbol))
+        .ok_or_else(|| anyhow!("No mapping for symbol {}", args.symbol))?;
+    unit.save(&mappings)?;
+    info!("Removed mapping {} -> {}", removed.0, removed.1);
+    Ok(())
+}
+
+fn list(args: ListArgs) -> Result<()> {
+    let unit = load_unit(args.project.as_deref(), &args.unit)?;
+    for (target, base) in unit.mappings().iter() {
+        println!("{target} -> {base}");
+    }
+    Ok(())
+}
+
+fn suggest(args: SuggestArgs) -> Result<()> {
+    let mut min_confidence = args.min_confidence.unwrap_or(MIN_PAIRING_CONFIDENCE);
+    if min_confidence < MIN_PAIRING_CONFIDENCE {
+        // Less confident pairings aren't suggested by the diff
+        warn!("Minimum confidence is {MIN_PAIRING_CONFIDENCE}, ignoring {min_confidence}");
+        min_confidence = MIN_PAIRING_CONFIDENCE;
+    }
+    let unit = load_unit(args.project.as_deref(), &args.unit)?;
+    let mut object = unit.object().clone();
+    object.resolve_paths(
+        &unit.project_dir,
+        unit.config.target_dir.as_deref(),
+        unit.config.base_dir.as_deref(),
+    );
+    let (Some(target_path), Some(base_path)) = (&object.target_path, &object.base_path) else {
+        bail!("Unit {} needs both a target and a base object", object.name());
+    };
+
+    let mappings = unit.mappings();
+    let config = diff::DiffObjConfig {
+        relax_reloc_diffs: true,
+        symbol_mappings: diff::MappingConfig { mappings: mappings.clone(), ..Default::default() },
+        symbol_pairing: SymbolPairingMode::Suggest,
+        ..Default::default()
+    };
+    let linker_map = obj::map::read_project_map(&unit.config, &unit.project_dir)?;
+    let mut target = obj::read::read_target(
+        target_path,
+        object.target_region.as_ref(),
+        linker_map.as_ref(),
+        &config,
+    )
+    .with_context(|| format!("Loading {}", target_path.display()))?;
+    let mut base = obj::read::read(base_path, &config)
+        .with_context(|| format!("Loading {}", base_path.display()))?;
+    if let Some(linker_map) = &linker_map {
+        let map_unit = object.target_region.as_ref().and_then(|r| r.unit.as_deref());
+        linker_map.apply(Some(&mut target), Some(&mut base), map_unit);
+    }
+    let result = diff::diff_objs(&config, Some(&target), Some(&base), None)?;
+
+    let mut new_mappings = mappings;
+    let mut added = 0;
+    for pairing in result.pairings.iter().filter(|p| p.confidence >= min_confidence) {
+        let (_, target_symbol) = target.section_symbol(pairing.left);
+        let (_, base_symbol) = base.section_symbol(pairing.right);
+        println!(
+            "{} -> {} ({:.0}%)",
+            target_symbol.name,
+            base_symbol.name,
+            pairing.confidence * 100.0
+        );
+        new_mappings.insert(target_symbol.name.clone(), base_symbol.name.clone());
+        added += 1;
+    }
+    if args.apply {
+        unit.save(&new_mappings)?;
+        info!("Added {} mappings", added);
+    }
+    Ok(())
+}
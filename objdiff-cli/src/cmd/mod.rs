@@ -1,2 +1,4 @@
 pub mod diff;
+pub mod mapping;
 pub mod report;
+pub mod similar;
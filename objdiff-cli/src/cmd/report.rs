@@ -1,8 +1,10 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
+    fs,
     fs::File,
     io::Read,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
     time::Instant,
 };
 
@@ -16,14 +18,18 @@ use objdiff_core::{
     },
     config::ProjectObject,
     diff, obj,
-    obj::{ObjSectionKind, ObjSymbolFlags},
+    obj::{map::LinkerMap, ObjSectionKind, ObjSymbolFlags},
 };
 use prost::Message;
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 use tracing::{info, warn};
+use xxhash_rust::xxh3::{xxh3_128, Xxh3};
 
 use crate::util::output::{write_output, OutputFormat};
 
+mod history;
+mod html;
+
 #[derive(FromArgs, PartialEq, Debug)]
 /// Generate a progress report for a project.
 #[argp(subcommand, name = "report")]
@@ -37,6 +43,8 @@ pub struct Args {
 pub enum SubCommand {
     Generate(GenerateArgs),
     Changes(ChangesArgs),
+    Html(HtmlArgs),
+    History(history::HistoryArgs),
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -55,6 +63,9 @@ pub struct GenerateArgs {
     #[argp(option, short = 'f')]
     /// Output format (json, json-pretty, proto) (default: json)
     format: Option<String>,
+    #[argp(option, short = 'c')]
+    /// Cache directory for per-unit results (reused while objects and config are unchanged)
+    cache: Option<PathBuf>,
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -75,10 +86,27 @@ pub struct ChangesArgs {
     format: Option<String>,
 }
 
+#[derive(FromArgs, PartialEq, Debug)]
+/// Render a report as a static HTML site.
+#[argp(subcommand, name = "html")]
+pub struct HtmlArgs {
+    #[argp(positional)]
+    /// Report file ("-" for stdin)
+    report: PathBuf,
+    #[argp(option, short = 'o')]
+    /// Output directory
+    output: PathBuf,
+    #[argp(option, short = 't')]
+    /// Page title (default: Progress)
+    title: Option<String>,
+}
+
 pub fn run(args: Args) -> Result<()> {
     match args.command {
         SubCommand::Generate(args) => generate(args),
         SubCommand::Changes(args) => changes(args),
+        SubCommand::Html(args) => html(args),
+        SubCommand::History(args) => history::run(args),
     }
 }
 
@@ -92,12 +120,36 @@ fn generate(args: GenerateArgs) -> Result<()> {
         Some((Err(err), _)) => bail!("Failed to load project configuration: {}", err),
         None => bail!("No project configuration found"),
     };
+    let linker_map = obj::map::read_project_map(&project, project_dir)?;
+    if let Some(linker_map) = &linker_map {
+        let discovered = obj::map::discover_units(linker_map, &project);
+        project.units_mut().extend(discovered);
+    }
+    let linker_map = linker_map.as_ref();
     info!(
         "Generating report for {} units (using {} threads)",
         project.units().len(),
         if args.deduplicate { 1 } else { rayon::current_num_threads() }
     );
 
+    let cache = match args.cache.as_deref() {
+        Some(_) if args.deduplicate => {
+            warn!("Caching is not supported when deduplicating, ignoring cache directory");
+            None
+        }
+        Some(cache) => {
+            fs::create_dir_all(cache)
+                .with_context(|| format!("Failed to create cache directory {}", cache.display()))?;
+            Some(UnitCache { dir: cache, file_digests: Default::default() })
+        }
+        None => None,
+    };
+
+    let config = diff::DiffObjConfig {
+        relax_reloc_diffs: true,
+        score_weights: project.score_weights.unwrap_or_default(),
+        ..Default::default()
+    };
     let start = Instant::now();
     let mut units = vec![];
     let mut existing_functions: HashSet<String> = HashSet::new();
@@ -110,6 +162,9 @@ fn generate(args: GenerateArgs) -> Result<()> {
                 project.target_dir.as_deref(),
                 project.base_dir.as_deref(),
                 Some(&mut existing_functions),
+                None,
+                linker_map,
+                &config,
             )? {
                 units.push(unit);
             }
@@ -127,6 +182,9 @@ fn generate(args: GenerateArgs) -> Result<()> {
                     project.target_dir.as_deref(),
                     project.base_dir.as_deref(),
                     None,
+                    cache.as_ref(),
+                    linker_map,
+                    &config,
                 )
             })
             .collect::<Result<Vec<Option<ReportUnit>>>>()?;
@@ -150,12 +208,16 @@ fn generate(args: GenerateArgs) -> Result<()> {
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn report_object(
     object: &mut ProjectObject,
     project_dir: &Path,
     target_dir: Option<&Path>,
     base_dir: Option<&Path>,
     mut existing_functions: Option<&mut HashSet<String>>,
+    cache: Option<&UnitCache>,
+    linker_map: Option<&LinkerMap>,
+    config: &diff::DiffObjConfig,
 ) -> Result<Option<ReportUnit>> {
     object.resolve_paths(project_dir, target_dir, base_dir);
     match (&object.target_path, &object.base_path) {
@@ -169,22 +231,39 @@ fn report_object(
         }
         _ => {}
     }
-    let config = diff::DiffObjConfig { relax_reloc_diffs: true, ..Default::default() };
-    let target = object
+    let cache_path = match cache {
+        Some(cache) => {
+            let path = cache.dir.join(format!(
+                "{}.binpb",
+                cache.key(object, config, linker_map.and_then(|m| m.path.as_deref()))?
+            ));
+            if let Some(unit) = read_cached_unit(&path) {
+                return Ok(Some(unit));
+            }
+            Some(path)
+        }
+        None => None,
+    };
+    let mut target = object
         .target_path
         .as_ref()
         .map(|p| {
-            obj::read::read(p, &config).with_context(|| format!("Failed to open {}", p.display()))
+            obj::read::read_target(p, object.target_region.as_ref(), linker_map, config)
+                .with_context(|| format!("Failed to open {}", p.display()))
         })
         .transpose()?;
-    let base = object
+    let mut base = object
         .base_path
         .as_ref()
         .map(|p| {
-            obj::read::read(p, &config).with_context(|| format!("Failed to open {}", p.display()))
+            obj::read::read(p, config).with_context(|| format!("Failed to open {}", p.display()))
         })
         .transpose()?;
-    let result = diff::diff_objs(&config, target.as_ref(), base.as_ref(), None)?;
+    if let Some(linker_map) = linker_map {
+        let unit = object.target_region.as_ref().and_then(|r| r.unit.as_deref());
+        linker_map.apply(target.as_mut(), base.as_mut(), unit);
+    }
+    let result = diff::diff_objs(config, target.as_ref(), base.as_ref(), None)?;
 
     let metadata = ReportUnitMetadata {
         complete: object.complete(),
@@ -204,6 +283,7 @@ fn report_object(
     let mut measures = Measures { total_units: 1, ..Default::default() };
     let mut sections = vec![];
     let mut functions = vec![];
+    let mut data = vec![];
 
     let obj = target.as_ref().or(base.as_ref()).unwrap();
     let obj_diff = result.left.as_ref().or(result.right.as_ref()).unwrap();
@@ -233,7 +313,6 @@ fn report_object(
                 if section_match_percent == 100.0 {
                     measures.matched_data += section.size;
                 }
-                continue;
             }
             ObjSectionKind::Code => (),
         }
@@ -259,12 +338,7 @@ fn report_object(
                     0.0
                 }
             });
-            measures.fuzzy_match_percent += match_percent * symbol.size as f32;
-            measures.total_code += symbol.size;
-            if match_percent == 100.0 {
-                measures.matched_code += symbol.size;
-            }
-            functions.push(ReportItem {
+            let item = ReportItem {
                 name: symbol.name.clone(),
                 size: symbol.size,
                 fuzzy_match_percent: match_percent,
@@ -272,7 +346,21 @@ fn report_object(
                     demangled_name: symbol.demangled_name.clone(),
                     virtual_address: symbol.virtual_address,
                 }),
-            });
+            };
+            if section.kind != ObjSectionKind::Code {
+                data.push(item);
+                if match_percent == 100.0 {
+                    measures.matched_data_symbols += 1;
+                }
+                measures.total_data_symbols += 1;
+                continue;
+            }
+            measures.fuzzy_match_percent += match_percent * symbol.size as f32;
+            measures.total_code += symbol.size;
+            if match_percent == 100.0 {
+                measures.matched_code += symbol.size;
+            }
+            functions.push(item);
             if match_percent == 100.0 {
                 measures.matched_functions += 1;
             }
@@ -286,13 +374,100 @@ fn report_object(
     }
     measures.calc_fuzzy_match_percent();
     measures.calc_matched_percent();
-    Ok(Some(ReportUnit {
+    let unit = ReportUnit {
         name: object.name().to_string(),
         measures: Some(measures),
         sections,
         functions,
         metadata: Some(metadata),
-    }))
+        data,
+    };
+    if let Some(path) = cache_path {
+        write_cached_unit(&path, &unit)?;
+    }
+    Ok(Some(unit))
+}
+
+/// Cache of report units, keyed by a hash of their inputs.
+struct UnitCache<'a> {
+    dir: &'a Path,
+    /// Digests of the files hashed so far, so that inputs shared between units (such as a linked
+    /// executable, its symbol map or the project linker map) are only read once per run
+    file_digests: Mutex<HashMap<PathBuf, Arc<Mutex<Option<u128>>>>>,
+}
+
+impl UnitCache<'_> {
+    /// Hashes all inputs of a unit's report: the object files, the target symbol map, the project
+    /// linker map, the diff configuration and the unit's project configuration.
+    fn key(
+        &self,
+        object: &ProjectObject,
+        config: &diff::DiffObjConfig,
+        map_path: Option<&Path>,
+    ) -> Result<String> {
+        let mut hasher = Xxh3::new();
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update(&REPORT_VERSION.to_le_bytes());
+        hasher.update(&serde_json::to_vec(config)?);
+        hasher.update(&serde_json::to_vec(object)?);
+        let symbols_path = object.target_region.as_ref().and_then(|r| r.symbols.as_ref());
+        for path in [
+            object.target_path.as_deref(),
+            object.base_path.as_deref(),
+            symbols_path.map(PathBuf::as_path),
+            map_path,
+        ] {
+            match path {
+                // Archive members are identified by the object config, so hash the whole archive
+                Some(path) => {
+                    let digest = self.file_digest(obj::read::split_member_path(path).0)?;
+                    hasher.update(&[1]);
+                    hasher.update(&digest.to_le_bytes());
+                }
+                None => hasher.update(&[0]),
+            }
+        }
+        Ok(format!("{:032x}", hasher.digest128()))
+    }
+
+    fn file_digest(&self, path: &Path) -> Result<u128> {
+        // Hold the entry's lock while hashing, so other units sharing the file wait for the
+        // digest instead of reading the file again
+        let entry =
+            self.file_digests.lock().unwrap().entry(path.to_path_buf()).or_default().clone();
+        let mut digest = entry.lock().unwrap();
+        if let Some(digest) = *digest {
+            return Ok(digest);
+        }
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let data = unsafe { memmap2::Mmap::map(&file) }
+            .with_context(|| format!("Failed to map {}", path.display()))?;
+        let value = xxh3_128(&data);
+        *digest = Some(value);
+        Ok(value)
+    }
+}
+
+fn read_cached_unit(path: &Path) -> Option<ReportUnit> {
+    let data = fs::read(path).ok()?;
+    match ReportUnit::decode(data.as_slice()) {
+        Ok(unit) => Some(unit),
+        Err(e) => {
+            warn!("Ignoring invalid cache entry {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn write_cached_unit(path: &Path, unit: &ReportUnit) -> Result<()> {
+    // Write to a temporary file first, so an interrupted run can't leave a partial entry
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, unit.encode_to_vec())
+        .with_context(|| format!("Failed to write cache entry {}", temp_path.display()))?;
+    fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to write cache entry {}", path.display()))?;
+    Ok(())
 }
 
 fn changes(args: ChangesArgs) -> Result<()> {
@@ -313,10 +488,11 @@ fn changes(args: ChangesArgs) -> Result<()> {
         let curr_unit = current.units.iter().find(|u| u.name == prev_unit.name);
         let sections = process_items(prev_unit, curr_unit, |u| &u.sections);
         let functions = process_items(prev_unit, curr_unit, |u| &u.functions);
+        let data = process_items(prev_unit, curr_unit, |u| &u.data);
 
         let prev_measures = prev_unit.measures;
         let curr_measures = curr_unit.and_then(|u| u.measures);
-        if !functions.is_empty() || prev_measures != curr_measures {
+        if !functions.is_empty() || !data.is_empty() || prev_measures != curr_measures {
             changes.units.push(ChangeUnit {
                 name: prev_unit.name.clone(),
                 from: prev_measures,
@@ -327,6 +503,7 @@ fn changes(args: ChangesArgs) -> Result<()> {
                     .as_ref()
                     .and_then(|u| u.metadata.clone())
                     .or_else(|| prev_unit.metadata.clone()),
+                data,
             });
         }
     }
@@ -339,6 +516,7 @@ fn changes(args: ChangesArgs) -> Result<()> {
                 sections: process_new_items(&curr_unit.sections),
                 functions: process_new_items(&curr_unit.functions),
                 metadata: curr_unit.metadata.clone(),
+                data: process_new_items(&curr_unit.data),
             });
         }
     }
@@ -346,6 +524,12 @@ fn changes(args: ChangesArgs) -> Result<()> {
     Ok(())
 }
 
+fn html(args: HtmlArgs) -> Result<()> {
+    let mut report = read_report(&args.report)?;
+    report.migrate()?;
+    html::write_site(&report, &args.output, args.title.as_deref().unwrap_or("Progress"))
+}
+
 fn process_items<F: Fn(&ReportUnit) -> &Vec<ReportItem>>(
     prev_unit: &ReportUnit,
     curr_unit: Option<&ReportUnit>,
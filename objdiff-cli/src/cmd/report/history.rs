@@ -0,0 +1,401 @@
+use std::{
+    collections::HashMap,
+    fs,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use argp::FromArgs;
+use objdiff_core::bindings::report::{Measures, ReportHistoryEntry};
+use prost::Message;
+use tracing::info;
+
+use super::read_report;
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Record reports in a history log and query trends.
+#[argp(subcommand, name = "history")]
+pub struct HistoryArgs {
+    #[argp(subcommand)]
+    command: HistorySubCommand,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argp(subcommand)]
+enum HistorySubCommand {
+    Add(AddArgs),
+    Trend(TrendArgs),
+    Regressions(RegressionsArgs),
+    FirstMatch(FirstMatchArgs),
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Append a report to a history log.
+#[argp(subcommand, name = "add")]
+struct AddArgs {
+    #[argp(positional)]
+    /// History log file (created if missing)
+    log: PathBuf,
+    #[argp(positional)]
+    /// Report file ("-" for stdin)
+    report: PathBuf,
+    #[argp(option, short = 'c')]
+    /// Commit ID the report was generated from
+    commit: String,
+    #[argp(option)]
+    /// Unix timestamp of the entry (default: now)
+    timestamp: Option<u64>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Show progress over time, overall or for a category or unit.
+#[argp(subcommand, name = "trend")]
+struct TrendArgs {
+    #[argp(positional)]
+    /// History log file
+    log: PathBuf,
+    #[argp(option)]
+    /// Progress category ID
+    category: Option<String>,
+    #[argp(option, short = 'u')]
+    /// Unit name
+    unit: Option<String>,
+    #[argp(option, short = 'o')]
+    /// Output file
+    output: Option<PathBuf>,
+    #[argp(option, short = 'f')]
+    /// Output format (json, json-pretty, csv) (default: json)
+    format: Option<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// List functions whose match decreased within the last entries.
+#[argp(subcommand, name = "regressions")]
+struct RegressionsArgs {
+    #[argp(positional)]
+    /// History log file
+    log: PathBuf,
+    #[argp(option, short = 'n')]
+    /// Number of most recent entries to compare (default: 2)
+    entries: Option<usize>,
+    #[argp(option, short = 'o')]
+    /// Output file
+    output: Option<PathBuf>,
+    #[argp(option, short = 'f')]
+    /// Output format (json, json-pretty, csv) (default: json)
+    format: Option<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Find the first entry where a function reached 100%.
+#[argp(subcommand, name = "first-match")]
+struct FirstMatchArgs {
+    #[argp(positional)]
+    /// History log file
+    log: PathBuf,
+    #[argp(positional)]
+    /// Function name (mangled or demangled)
+    function: String,
+    #[argp(option, short = 'u')]
+    /// Unit name
+    unit: Option<String>,
+    #[argp(option, short = 'o')]
+    /// Output file
+    output: Option<PathBuf>,
+    #[argp(option, short = 'f')]
+    /// Output format (json, json-pretty, csv) (default: json)
+    format: Option<String>,
+}
+
+pub fn run(args: HistoryArgs) -> Result<()> {
+    match args.command {
+        HistorySubCommand::Add(args) => add(args),
+        HistorySubCommand::Trend(args) => trend(args),
+        HistorySubCommand::Regressions(args) => regressions(args),
+        HistorySubCommand::FirstMatch(args) => first_match(args),
+    }
+}
+
+fn add(args: AddArgs) -> Result<()> {
+    let mut report = read_report(&args.report)?;
+    report.migrate()?;
+    let timestamp = match args.timestamp {
+        Some(timestamp) => timestamp,
+        None => time::OffsetDateTime::now_utc().unix_timestamp() as u64,
+    };
+    let entry = ReportHistoryEntry { commit: args.commit, timestamp, report: Some(report) };
+    let mut file = File::options()
+        .create(true)
+        .append(true)
+        .open(&args.log)
+        .with_context(|| format!("Failed to open {}", args.log.display()))?;
+    file.write_all(&entry.encode_length_delimited_to_vec())
+        .with_context(|| format!("Failed to write {}", args.log.display()))?;
+    info!("Added entry for {} to {}", entry.commit, args.log.display());
+    Ok(())
+}
+
+fn read_history(path: &Path) -> Result<Vec<ReportHistoryEntry>> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut buf = data.as_slice();
+    let mut entries = vec![];
+    while !buf.is_empty() {
+        let entry = ReportHistoryEntry::decode_length_delimited(&mut buf).with_context(|| {
+            format!("Failed to decode entry {} in {}", entries.len(), path.display())
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn trend(args: TrendArgs) -> Result<()> {
+    let format = TableFormat::from_option(args.format.as_deref())?;
+    let entries = read_history(&args.log)?;
+    let mut rows = vec![];
+    for entry in &entries {
+        let Some(report) = &entry.report else {
+            continue;
+        };
+        let measures = if let Some(unit) = &args.unit {
+            report.units.iter().find(|u| &u.name == unit).and_then(|u| u.measures)
+        } else if let Some(category) = &args.category {
+            report.categories.iter().find(|c| &c.id == category).and_then(|c| c.measures)
+        } else {
+            report.measures
+        };
+        if let Some(measures) = measures {
+            rows.push(TrendRow::new(entry, &measures));
+        }
+    }
+    write_table(&rows, args.output.as_deref(), format)
+}
+
+fn regressions(args: RegressionsArgs) -> Result<()> {
+    let format = TableFormat::from_option(args.format.as_deref())?;
+    let entries = read_history(&args.log)?;
+    let count = args.entries.unwrap_or(2).max(2);
+    let entries = &entries[entries.len().saturating_sub(count)..];
+    let mut rows = vec![];
+    for pair in entries.windows(2) {
+        let (Some(prev), Some(curr)) = (&pair[0].report, &pair[1].report) else {
+            continue;
+        };
+        let prev_functions = prev
+            .units
+            .iter()
+            .flat_map(|u| u.functions.iter().map(move |f| ((&u.name, &f.name), f)))
+            .collect::<HashMap<_, _>>();
+        for unit in &curr.units {
+            for function in &unit.functions {
+                let Some(prev_function) = prev_functions.get(&(&unit.name, &function.name)) else {
+                    continue;
+                };
+                if function.fuzzy_match_percent < prev_function.fuzzy_match_percent {
+                    rows.push(RegressionRow {
+                        commit: pair[1].commit.clone(),
+                        timestamp: pair[1].timestamp,
+                        unit: unit.name.clone(),
+                        function: function.name.clone(),
+                        from: prev_function.fuzzy_match_percent,
+                        to: function.fuzzy_match_percent,
+                    });
+                }
+            }
+        }
+    }
+    write_table(&rows, args.output.as_deref(), format)
+}
+
+fn first_match(args: FirstMatchArgs) -> Result<()> {
+    let format = TableFormat::from_option(args.format.as_deref())?;
+    let entries = read_history(&args.log)?;
+    let mut rows = vec![];
+    'entries: for entry in &entries {
+        let Some(report) = &entry.report else {
+            continue;
+        };
+        for unit in &report.units {
+            if args.unit.as_ref().is_some_and(|u| u != &unit.name) {
+                continue;
+            }
+            let found = unit.functions.iter().find(|f| {
+                f.name == args.function
+                    || f.metadata
+                        .as_ref()
+                        .and_then(|m| m.demangled_name.as_ref())
+                        .is_some_and(|n| n == &args.function)
+            });
+            if let Some(function) = found.filter(|f| f.fuzzy_match_percent >= 100.0) {
+                rows.push(FirstMatchRow {
+                    commit: entry.commit.clone(),
+                    timestamp: entry.timestamp,
+                    unit: unit.name.clone(),
+                    function: function.name.clone(),
+                });
+                break 'entries;
+            }
+        }
+    }
+    write_table(&rows, args.output.as_deref(), format)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+enum TableFormat {
+    #[default]
+    Json,
+    JsonPretty,
+    Csv,
+}
+
+impl TableFormat {
+    fn from_option(s: Option<&str>) -> Result<Self> {
+        match s.map(|s| s.to_ascii_lowercase()).as_deref() {
+            None | Some("json") => Ok(Self::Json),
+            Some("json-pretty" | "json_pretty") => Ok(Self::JsonPretty),
+            Some("csv") => Ok(Self::Csv),
+            Some(s) => bail!("Invalid output format: {}", s),
+        }
+    }
+}
+
+/// A query result row that can be written as JSON or CSV.
+trait TableRow: serde::Serialize {
+    const COLUMNS: &'static [&'static str];
+
+    fn values(&self) -> Vec<String>;
+}
+
+#[derive(serde::Serialize)]
+struct TrendRow {
+    commit: String,
+    timestamp: u64,
+    fuzzy_match_percent: f32,
+    matched_code_percent: f32,
+    matched_data_percent: f32,
+    matched_functions: u32,
+    total_functions: u32,
+    complete_code_percent: f32,
+}
+
+impl TrendRow {
+    fn new(entry: &ReportHistoryEntry, measures: &Measures) -> Self {
+        Self {
+            commit: entry.commit.clone(),
+            timestamp: entry.timestamp,
+            fuzzy_match_percent: measures.fuzzy_match_percent,
+            matched_code_percent: measures.matched_code_percent,
+            matched_data_percent: measures.matched_data_percent,
+            matched_functions: measures.matched_functions,
+            total_functions: measures.total_functions,
+            complete_code_percent: measures.complete_code_percent,
+        }
+    }
+}
+
+impl TableRow for TrendRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "commit",
+        "timestamp",
+        "fuzzy_match_percent",
+        "matched_code_percent",
+        "matched_data_percent",
+        "matched_functions",
+        "total_functions",
+        "complete_code_percent",
+    ];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.commit.clone(),
+            self.timestamp.to_string(),
+            self.fuzzy_match_percent.to_string(),
+            self.matched_code_percent.to_string(),
+            self.matched_data_percent.to_string(),
+            self.matched_functions.to_string(),
+            self.total_functions.to_string(),
+            self.complete_code_percent.to_string(),
+        ]
+    }
+}
+
+#[derive(serde::Serialize)]
+struct RegressionRow {
+    commit: String,
+    timestamp: u64,
+    unit: String,
+    function: String,
+    from: f32,
+    to: f32,
+}
+
+impl TableRow for RegressionRow {
+    const COLUMNS: &'static [&'static str] =
+        &["commit", "timestamp", "unit", "function", "from", "to"];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.commit.clone(),
+            self.timestamp.to_string(),
+            self.unit.clone(),
+            self.function.clone(),
+            self.from.to_string(),
+            self.to.to_string(),
+        ]
+    }
+}
+
+#[derive(serde::Serialize)]
+struct FirstMatchRow {
+    commit: String,
+    timestamp: u64,
+    unit: String,
+    function: String,
+}
+
+impl TableRow for FirstMatchRow {
+    const COLUMNS: &'static [&'static str] = &["commit", "timestamp", "unit", "function"];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.commit.clone(),
+            self.timestamp.to_string(),
+            self.unit.clone(),
+            self.function.clone(),
+        ]
+    }
+}
+
+fn write_table<T: TableRow>(rows: &[T], output: Option<&Path>, format: TableFormat) -> Result<()> {
+    let mut writer: Box<dyn Write> = match output {
+        Some(output) if output != Path::new("-") => {
+            info!("Writing to {}", output.display());
+            let file = File::create(output)
+                .with_context(|| format!("Failed to create file {}", output.display()))?;
+            Box::new(BufWriter::new(file))
+        }
+        _ => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+    match format {
+        TableFormat::Json => serde_json::to_writer(&mut writer, rows)?,
+        TableFormat::JsonPretty => serde_json::to_writer_pretty(&mut writer, rows)?,
+        TableFormat::Csv => {
+            writeln!(writer, "{}", T::COLUMNS.join(","))?;
+            for row in rows {
+                let values = row.values().iter().map(|v| csv_escape(v)).collect::<Vec<_>>();
+                writeln!(writer, "{}", values.join(","))?;
+            }
+        }
+    }
+    writer.flush().context("Failed to flush output")?;
+    Ok(())
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
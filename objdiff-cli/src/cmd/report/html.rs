@@ -0,0 +1,313 @@
+use std::{collections::HashSet, fmt::Write as _, fs, path::Path};
+
+use anyhow::{Context, Result};
+use objdiff_core::bindings::report::{Measures, Report, ReportItem, ReportUnit};
+use tracing::info;
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em auto; max-width: 1200px; padding: 0 1em;
+  background: #1b1b1f; color: #ddd; }
+a { color: #8cb4ff; }
+h1, h2 { font-weight: normal; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }
+th, td { padding: 0.25em 0.5em; text-align: left; border-bottom: 1px solid #333; }
+th.sortable { cursor: pointer; user-select: none; }
+th.sortable:after { content: " \2195"; color: #666; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+code { font-size: 0.9em; }
+.bar { background: #333; height: 0.8em; min-width: 100px; position: relative; }
+.bar > div { background: #4caf50; height: 100%; }
+.bar > div.partial { background: #d8a13a; }
+.summary { display: flex; flex-wrap: wrap; gap: 1em; margin-bottom: 2em; }
+.summary > div { background: #26262c; padding: 0.75em 1em; min-width: 180px; }
+.summary .value { font-size: 1.5em; }
+.summary .label { color: #999; font-size: 0.9em; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll("th.sortable").forEach(function (th) {
+  th.addEventListener("click", function () {
+    var table = th.closest("table");
+    var body = table.tBodies[0];
+    var index = Array.prototype.indexOf.call(th.parentNode.children, th);
+    var asc = th.dataset.order !== "asc";
+    th.dataset.order = asc ? "asc" : "desc";
+    var rows = Array.prototype.slice.call(body.rows);
+    rows.sort(function (a, b) {
+      var x = a.cells[index].dataset.value, y = b.cells[index].dataset.value;
+      var r = isNaN(x) || isNaN(y) ? x.localeCompare(y) : x - y;
+      return asc ? r : -r;
+    });
+    rows.forEach(function (row) { body.appendChild(row); });
+  });
+});
+"#;
+
+/// Writes a self-contained static HTML site for a report: an index page with overall
+/// progress, categories and units, and a page per unit.
+pub fn write_site(report: &Report, output: &Path, title: &str) -> Result<()> {
+    let units_dir = output.join("units");
+    fs::create_dir_all(&units_dir)
+        .with_context(|| format!("Failed to create directory {}", units_dir.display()))?;
+
+    let mut used_names = HashSet::new();
+    let mut unit_files = Vec::with_capacity(report.units.len());
+    for unit in &report.units {
+        let mut file_name = sanitize_file_name(&unit.name);
+        if !used_names.insert(file_name.clone()) {
+            file_name = format!("{}-{}", file_name, unit_files.len());
+            used_names.insert(file_name.clone());
+        }
+        unit_files.push(format!("{}.html", file_name));
+    }
+
+    write_page(&output.join("index.html"), &render_index(report, title, &unit_files))?;
+    for (unit, file_name) in report.units.iter().zip(&unit_files) {
+        write_page(&units_dir.join(file_name), &render_unit(unit, title))?;
+    }
+    info!("Wrote {} pages to {}", report.units.len() + 1, output.display());
+    Ok(())
+}
+
+fn write_page(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn render_index(report: &Report, title: &str, unit_files: &[String]) -> String {
+    let measures = report.measures.unwrap_or_default();
+    let mut out = String::new();
+    page_header(&mut out, title);
+    writeln!(out, "<h1>{}</h1>", escape(title)).unwrap();
+    measures_summary(&mut out, &measures);
+
+    if !report.categories.is_empty() {
+        out.push_str("<h2>Categories</h2>\n<table>\n<thead><tr>");
+        sortable_headers(&mut out, &[
+            "Category",
+            "Code",
+            "Fuzzy match",
+            "Matched code",
+            "Matched data",
+            "Functions",
+            "Complete units",
+        ]);
+        out.push_str("</tr></thead>\n<tbody>\n");
+        for category in &report.categories {
+            let m = category.measures.unwrap_or_default();
+            let name = if category.name.is_empty() { &category.id } else { &category.name };
+            out.push_str("<tr>");
+            text_cell(&mut out, name);
+            size_cell(&mut out, m.total_code);
+            bar_cell(&mut out, m.fuzzy_match_percent);
+            percent_cell(&mut out, m.matched_code_percent);
+            percent_cell(&mut out, m.matched_data_percent);
+            ratio_cell(&mut out, m.matched_functions, m.total_functions);
+            ratio_cell(&mut out, m.complete_units, m.total_units);
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</tbody>\n</table>\n");
+    }
+
+    out.push_str("<h2>Units</h2>\n<table>\n<thead><tr>");
+    sortable_headers(&mut out, &[
+        "Unit",
+        "Code",
+        "Fuzzy match",
+        "Matched code",
+        "Matched data",
+        "Functions",
+        "Data symbols",
+        "Complete",
+    ]);
+    out.push_str("</tr></thead>\n<tbody>\n");
+    for (unit, file_name) in report.units.iter().zip(unit_files) {
+        let m = unit.measures.unwrap_or_default();
+        let complete = unit.metadata.as_ref().and_then(|m| m.complete).unwrap_or(false);
+        write!(
+            out,
+            "<tr><td data-value=\"{0}\"><a href=\"units/{1}\">{0}</a></td>",
+            escape(&unit.name),
+            escape(file_name)
+        )
+        .unwrap();
+        size_cell(&mut out, m.total_code);
+        bar_cell(&mut out, m.fuzzy_match_percent);
+        percent_cell(&mut out, m.matched_code_percent);
+        percent_cell(&mut out, m.matched_data_percent);
+        ratio_cell(&mut out, m.matched_functions, m.total_functions);
+        ratio_cell(&mut out, m.matched_data_symbols, m.total_data_symbols);
+        writeln!(
+            out,
+            "<td data-value=\"{}\">{}</td></tr>",
+            complete as u8,
+            if complete { "✓" } else { "" }
+        )
+        .unwrap();
+    }
+    out.push_str("</tbody>\n</table>\n");
+    page_footer(&mut out);
+    out
+}
+
+fn render_unit(unit: &ReportUnit, title: &str) -> String {
+    let measures = unit.measures.unwrap_or_default();
+    let mut out = String::new();
+    page_header(&mut out, &format!("{} - {}", unit.name, title));
+    writeln!(
+        out,
+        "<p><a href=\"../index.html\">← {}</a></p>\n<h1>{}</h1>",
+        escape(title),
+        escape(&unit.name)
+    )
+    .unwrap();
+    if let Some(source_path) = unit.metadata.as_ref().and_then(|m| m.source_path.as_deref()) {
+        writeln!(out, "<p><code>{}</code></p>", escape(source_path)).unwrap();
+    }
+    measures_summary(&mut out, &measures);
+    items_table(&mut out, "Functions", &unit.functions);
+    items_table(&mut out, "Data", &unit.data);
+    items_table(&mut out, "Sections", &unit.sections);
+    page_footer(&mut out);
+    out
+}
+
+fn items_table(out: &mut String, heading: &str, items: &[ReportItem]) {
+    if items.is_empty() {
+        return;
+    }
+    writeln!(out, "<h2>{}</h2>\n<table>\n<thead><tr>", escape(heading)).unwrap();
+    sortable_headers(out, &["Name", "Address", "Size", "Match"]);
+    out.push_str("</tr></thead>\n<tbody>\n");
+    for item in items {
+        let metadata = item.metadata.as_ref();
+        let name = metadata.and_then(|m| m.demangled_name.as_deref()).unwrap_or(&item.name);
+        write!(
+            out,
+            "<tr><td data-value=\"{0}\" title=\"{1}\"><code>{0}</code></td>",
+            escape(name),
+            escape(&item.name)
+        )
+        .unwrap();
+        match metadata.and_then(|m| m.virtual_address) {
+            Some(address) => {
+                write!(out, "<td data-value=\"{0}\"><code>{0:#x}</code></td>", address).unwrap()
+            }
+            None => out.push_str("<td data-value=\"0\"></td>"),
+        }
+        size_cell(out, item.size);
+        bar_cell(out, item.fuzzy_match_percent);
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</tbody>\n</table>\n");
+}
+
+fn measures_summary(out: &mut String, measures: &Measures) {
+    out.push_str("<div class=\"summary\">\n");
+    let mut stat = |value: String, label: &str| {
+        writeln!(
+            out,
+            "<div><div class=\"value\">{}</div><div class=\"label\">{}</div></div>",
+            value, label
+        )
+        .unwrap();
+    };
+    stat(format!("{:.2}%", measures.fuzzy_match_percent), "Fuzzy match");
+    stat(
+        format!("{:.2}%", measures.matched_code_percent),
+        &format!("Matched code ({} / {})", measures.matched_code, measures.total_code),
+    );
+    stat(
+        format!("{:.2}%", measures.matched_data_percent),
+        &format!("Matched data ({} / {})", measures.matched_data, measures.total_data),
+    );
+    stat(
+        format!("{:.2}%", measures.matched_functions_percent),
+        &format!("Functions ({} / {})", measures.matched_functions, measures.total_functions),
+    );
+    stat(
+        format!("{:.2}%", measures.matched_data_symbols_percent),
+        &format!(
+            "Data symbols ({} / {})",
+            measures.matched_data_symbols, measures.total_data_symbols
+        ),
+    );
+    stat(format!("{:.2}%", measures.complete_code_percent), "Complete code");
+    if measures.total_units > 1 {
+        stat(format!("{} / {}", measures.complete_units, measures.total_units), "Complete units");
+    }
+    out.push_str("</div>\n");
+}
+
+fn page_header(out: &mut String, title: &str) {
+    writeln!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>",
+        escape(title),
+        STYLE
+    )
+    .unwrap();
+}
+
+fn page_footer(out: &mut String) {
+    writeln!(out, "<script>{}</script>\n</body>\n</html>", SCRIPT).unwrap();
+}
+
+fn sortable_headers(out: &mut String, headers: &[&str]) {
+    for header in headers {
+        write!(out, "<th class=\"sortable\">{}</th>", escape(header)).unwrap();
+    }
+}
+
+fn text_cell(out: &mut String, text: &str) {
+    write!(out, "<td data-value=\"{0}\">{0}</td>", escape(text)).unwrap();
+}
+
+fn size_cell(out: &mut String, size: u64) {
+    write!(out, "<td class=\"num\" data-value=\"{0}\">{0}</td>", size).unwrap();
+}
+
+fn percent_cell(out: &mut String, percent: f32) {
+    write!(out, "<td class=\"num\" data-value=\"{0}\">{0:.2}%</td>", percent).unwrap();
+}
+
+fn ratio_cell(out: &mut String, matched: u32, total: u32) {
+    let percent = if total == 0 { 100.0 } else { matched as f32 / total as f32 * 100.0 };
+    write!(out, "<td class=\"num\" data-value=\"{}\">{} / {}</td>", percent, matched, total)
+        .unwrap();
+}
+
+fn bar_cell(out: &mut String, percent: f32) {
+    let class = if percent >= 100.0 { "" } else { " class=\"partial\"" };
+    write!(
+        out,
+        "<td data-value=\"{0}\" title=\"{0:.2}%\"><div class=\"bar\"><div{1} style=\"width: \
+         {2:.2}%\"></div></div></td>",
+        percent,
+        class,
+        percent.clamp(0.0, 100.0)
+    )
+    .unwrap();
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect()
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,112 @@
+use std::{path::PathBuf, time::Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use argp::FromArgs;
+use objdiff_core::{
+    config::try_project_config,
+    diff,
+    diff::fingerprint::MIN_SIMILARITY,
+    index::{index_unit, ProjectIndex},
+    obj,
+};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use tracing::{info, warn};
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Find functions in the project similar to a function.
+#[argp(subcommand, name = "similar")]
+pub struct Args {
+    #[argp(option, short = 'p')]
+    /// Project directory
+    project: Option<PathBuf>,
+    #[argp(option, short = 'u')]
+    /// Unit name within project
+    unit: String,
+    #[argp(option, short = 'm')]
+    /// Minimum similarity to list, from 0.0 to 1.0 (default: 0.75)
+    min_similarity: Option<f32>,
+    #[argp(option, short = 'n')]
+    /// Maximum number of functions to list (default: 20)
+    limit: Option<usize>,
+    #[argp(positional)]
+    /// Function symbol name
+    symbol: String,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let project_dir = match args.project {
+        Some(project) => project,
+        None => std::env::current_dir().context("Failed to get the current directory")?,
+    };
+    let Some((config, info)) = try_project_config(&project_dir) else {
+        bail!("Project config not found in {}", project_dir.display())
+    };
+    let mut config =
+        config.with_context(|| format!("Reading project config {}", info.path.display()))?;
+    let linker_map = obj::map::read_project_map(&config, &project_dir)?;
+    if let Some(linker_map) = &linker_map {
+        let discovered = obj::map::discover_units(linker_map, &config);
+        config.units_mut().extend(discovered);
+    }
+    if !config.units().iter().any(|u| u.name() == args.unit) {
+        bail!("Unit not found: {}", args.unit);
+    }
+
+    info!(
+        "Indexing {} units (using {} threads)",
+        config.units().len(),
+        rayon::current_num_threads()
+    );
+    let diff_config = diff::DiffObjConfig {
+        relax_reloc_diffs: true,
+        score_weights: config.score_weights.unwrap_or_default(),
+        ..Default::default()
+    };
+    let start = Instant::now();
+    let target_dir = config.target_dir.clone();
+    let base_dir = config.base_dir.clone();
+    let indexes = config
+        .units_mut()
+        .par_iter_mut()
+        .map(|object| {
+            object.resolve_paths(&project_dir, target_dir.as_deref(), base_dir.as_deref());
+            let mut index = ProjectIndex::default();
+            if let Err(e) = index_unit(&mut index, object, linker_map.as_ref(), &diff_config) {
+                warn!("Failed to index {}: {:#}", object.name(), e);
+            }
+            index
+        })
+        .collect::<Vec<_>>();
+    let mut index = ProjectIndex::default();
+    for unit_index in indexes {
+        index.symbols.extend(unit_index.symbols);
+    }
+    let duration = start.elapsed();
+    info!("Indexed units in {}.{:03}s", duration.as_secs(), duration.subsec_millis());
+
+    let symbol = index
+        .find_symbol(&args.unit, &args.symbol)
+        .ok_or_else(|| anyhow!("Symbol not found: {}", args.symbol))?;
+    let Some(fingerprint) = &symbol.fingerprint else {
+        bail!("Symbol {} is not a function", args.symbol);
+    };
+    let similar = index
+        .find_similar(fingerprint, args.min_similarity.unwrap_or(MIN_SIMILARITY))
+        .into_iter()
+        .filter(|s| !std::ptr::eq(s.symbol, symbol))
+        .take(args.limit.unwrap_or(20));
+    for result in similar {
+        let status = match result.symbol.match_percent {
+            Some(percent) => format!("{:.2}% matched", percent),
+            None => "unpaired".to_string(),
+        };
+        println!(
+            "{:>3.0}% {} {} ({})",
+            result.similarity * 100.0,
+            result.symbol.unit,
+            result.symbol.name(),
+            status
+        );
+    }
+    Ok(())
+}
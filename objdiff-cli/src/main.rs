@@ -84,7 +84,9 @@ struct TopLevel {
 #[argp(subcommand)]
 enum SubCommand {
     Diff(cmd::diff::Args),
+    Mapping(cmd::mapping::Args),
     Report(cmd::report::Args),
+    Similar(cmd::similar::Args),
 }
 
 // Duplicated from supports-color so we can check early.
@@ -139,7 +141,9 @@ fn main() {
     }
     result = result.and_then(|_| match args.command {
         SubCommand::Diff(c_args) => cmd::diff::run(c_args),
+        SubCommand::Mapping(c_args) => cmd::mapping::run(c_args),
         SubCommand::Report(c_args) => cmd::report::run(c_args),
+        SubCommand::Similar(c_args) => cmd::similar::run(c_args),
     });
     if let Err(e) = result {
         eprintln!("Failed: {e:?}");
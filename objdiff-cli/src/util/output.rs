@@ -32,6 +32,13 @@ impl OutputFormat {
             None => Ok(Self::default()),
         }
     }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json | Self::JsonPretty => "json",
+            Self::Proto => "binpb",
+        }
+    }
 }
 
 pub fn write_output<T>(input: &T, output: Option<&Path>, format: OutputFormat) -> Result<()>
@@ -82,3 +89,46 @@ where T: serde::Serialize + prost::Message {
     }
     Ok(())
 }
+
+/// Writes multiple messages to a single stream. Compact JSON values are separated by newlines,
+/// pretty JSON is written as a single array and protobuf messages are length-delimited.
+pub fn write_output_stream<'a, T, I>(
+    inputs: I,
+    output: Option<&Path>,
+    format: OutputFormat,
+) -> Result<()>
+where
+    T: serde::Serialize + prost::Message + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut writer: Box<dyn Write> = match output {
+        Some(output) if output != Path::new("-") => {
+            info!("Writing to {}", output.display());
+            let file = File::create(output)
+                .with_context(|| format!("Failed to create file {}", output.display()))?;
+            Box::new(BufWriter::new(file))
+        }
+        _ => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+    match format {
+        OutputFormat::Json => {
+            for input in inputs {
+                serde_json::to_writer(&mut writer, input).context("Failed to write output")?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        OutputFormat::JsonPretty => {
+            let mut serializer = serde_json::Serializer::pretty(&mut writer);
+            serde::Serializer::collect_seq(&mut serializer, inputs)
+                .context("Failed to write output")?;
+            writer.write_all(b"\n")?;
+        }
+        OutputFormat::Proto => {
+            for input in inputs {
+                writer.write_all(&input.encode_length_delimited_to_vec())?;
+            }
+        }
+    }
+    writer.flush().context("Failed to flush output")?;
+    Ok(())
+}
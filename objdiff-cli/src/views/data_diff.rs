@@ -0,0 +1,438 @@
+use anyhow::{bail, Result};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers, MouseEventKind};
+use objdiff_core::{
+    diff::{
+        display::display_data_reloc, ObjDataDiffKind, ObjDataFieldDiff, ObjDiff, ObjSymbolDiff,
+    },
+    obj::{ObjInfo, ObjSectionKind, ObjSymbol, SymbolRef},
+};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+
+use super::{function_diff::match_percent_color, EventControlFlow, EventResult, UiView};
+use crate::cmd::diff::AppState;
+
+const BYTES_PER_ROW: usize = 16;
+
+/// Field-by-field diff of a data symbol. Symbols without type
+/// information fall back to a hex dump.
+#[derive(Default)]
+pub struct DataDiffUi {
+    pub symbol_name: String,
+    pub scroll_x: usize,
+    pub scroll_state_x: ScrollbarState,
+    pub scroll_y: usize,
+    pub scroll_state_y: ScrollbarState,
+    pub per_page: usize,
+    pub num_rows: usize,
+    pub left_sym: Option<SymbolRef>,
+    pub right_sym: Option<SymbolRef>,
+}
+
+impl UiView for DataDiffUi {
+    fn draw(&mut self, state: &AppState, f: &mut Frame, _result: &mut EventResult) {
+        let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).split(f.area());
+        let header_chunks = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .split(chunks[0]);
+        let content_chunks = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .split(chunks[1]);
+
+        self.per_page = chunks[1].height.saturating_sub(2) as usize;
+        let max_scroll_y = self.num_rows.saturating_sub(self.per_page);
+        if self.scroll_y > max_scroll_y {
+            self.scroll_y = max_scroll_y;
+        }
+        self.scroll_state_y =
+            self.scroll_state_y.content_length(max_scroll_y).position(self.scroll_y);
+
+        let mut line_l = Line::default();
+        line_l
+            .spans
+            .push(Span::styled(self.symbol_name.clone(), Style::new().fg(Color::White).bold()));
+        f.render_widget(line_l, header_chunks[0]);
+
+        let mut line_r = Line::default();
+        if let Some(percent) =
+            get_symbol(state.right_obj.as_ref(), self.right_sym).and_then(|(_, d)| d.match_percent)
+        {
+            line_r.spans.push(Span::styled(
+                format!("{:.2}% ", percent),
+                Style::new().fg(match_percent_color(percent)),
+            ));
+        }
+        let reload_time = state
+            .reload_time
+            .as_ref()
+            .and_then(|t| t.format(&state.time_format).ok())
+            .unwrap_or_else(|| "N/A".to_string());
+        line_r.spans.push(Span::styled(
+            format!("Last reload: {}", reload_time),
+            Style::new().fg(Color::White),
+        ));
+        f.render_widget(line_r, header_chunks[2]);
+
+        let left = get_symbol(state.left_obj.as_ref(), self.left_sym);
+        let right = get_symbol(state.right_obj.as_ref(), self.right_sym);
+        let left_bytes = left.map(|(s, _)| s.bytes.as_slice()).unwrap_or_default();
+        let right_bytes = right.map(|(s, _)| s.bytes.as_slice()).unwrap_or_default();
+
+        let mut max_width = 0;
+        let mut left_text = None;
+        if let Some((symbol, symbol_diff)) = left {
+            let rect = content_chunks[0].inner(Margin::new(0, 1));
+            let relocs = symbol_relocs(state.left_obj.as_ref(), self.left_sym);
+            let text = self.print_sym(symbol, symbol_diff, right_bytes, &relocs, rect);
+            max_width = max_width.max(text.width());
+            left_text = Some(text);
+        }
+        let mut right_text = None;
+        let mut margin_text = None;
+        if let Some((symbol, symbol_diff)) = right {
+            let rect = content_chunks[2].inner(Margin::new(0, 1));
+            let relocs = symbol_relocs(state.right_obj.as_ref(), self.right_sym);
+            let text = self.print_sym(symbol, symbol_diff, left_bytes, &relocs, rect);
+            max_width = max_width.max(text.width());
+            right_text = Some(text);
+
+            let rect = content_chunks[1].inner(Margin::new(1, 1));
+            margin_text = Some(self.print_margin(&symbol_diff.data_fields, rect));
+        }
+
+        let max_scroll_x =
+            max_width.saturating_sub(content_chunks[0].width.min(content_chunks[2].width) as usize);
+        if self.scroll_x > max_scroll_x {
+            self.scroll_x = max_scroll_x;
+        }
+        self.scroll_state_x =
+            self.scroll_state_x.content_length(max_scroll_x).position(self.scroll_x);
+
+        for (text, title, chunk) in
+            [(left_text, "TARGET", content_chunks[0]), (right_text, "CURRENT", content_chunks[2])]
+        {
+            let Some(text) = text else {
+                continue;
+            };
+            f.render_widget(
+                Paragraph::new(text)
+                    .block(
+                        Block::new()
+                            .borders(Borders::TOP)
+                            .border_style(Style::new().fg(Color::Gray))
+                            .title_style(Style::new().bold())
+                            .title(title),
+                    )
+                    .scroll((0, self.scroll_x as u16)),
+                chunk,
+            );
+        }
+        if let Some(text) = margin_text {
+            f.render_widget(text, content_chunks[1].inner(Margin::new(1, 1)));
+        }
+
+        // Render scrollbars
+        f.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None),
+            chunks[1].inner(Margin::new(0, 1)),
+            &mut self.scroll_state_y,
+        );
+        for chunk in [content_chunks[0], content_chunks[2]] {
+            f.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::HorizontalBottom).thumb_symbol("■"),
+                chunk,
+                &mut self.scroll_state_x,
+            );
+        }
+    }
+
+    fn handle_event(&mut self, state: &mut AppState, event: Event) -> EventControlFlow {
+        let mut result = EventResult::default();
+        match event {
+            Event::Key(event)
+                if matches!(event.kind, KeyEventKind::Press | KeyEventKind::Repeat) =>
+            {
+                match event.code {
+                    // Quit
+                    KeyCode::Esc | KeyCode::Char('q') => return EventControlFlow::Break,
+                    // Page up
+                    KeyCode::PageUp => {
+                        self.scroll_y = self.scroll_y.saturating_sub(self.per_page);
+                        result.redraw = true;
+                    }
+                    // Page down
+                    KeyCode::Char(' ') | KeyCode::PageDown => {
+                        self.scroll_y += self.per_page;
+                        result.redraw = true;
+                    }
+                    // Half page down (ctrl + d)
+                    KeyCode::Char('d') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.scroll_y += self.per_page / 2;
+                        result.redraw = true;
+                    }
+                    // Half page up (ctrl + u)
+                    KeyCode::Char('u') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.scroll_y = self.scroll_y.saturating_sub(self.per_page / 2);
+                        result.redraw = true;
+                    }
+                    // Scroll down
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.scroll_y += 1;
+                        result.redraw = true;
+                    }
+                    // Scroll up
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.scroll_y = self.scroll_y.saturating_sub(1);
+                        result.redraw = true;
+                    }
+                    // Scroll to start
+                    KeyCode::Char('g') => {
+                        self.scroll_y = 0;
+                        result.redraw = true;
+                    }
+                    // Scroll to end
+                    KeyCode::Char('G') => {
+                        self.scroll_y = self.num_rows;
+                        result.redraw = true;
+                    }
+                    // Reload
+                    KeyCode::Char('r') => {
+                        return EventControlFlow::Reload;
+                    }
+                    // Scroll right
+                    KeyCode::Right | KeyCode::Char('l') => {
+                        self.scroll_x += 1;
+                        result.redraw = true;
+                    }
+                    // Scroll left
+                    KeyCode::Left | KeyCode::Char('h') => {
+                        self.scroll_x = self.scroll_x.saturating_sub(1);
+                        result.redraw = true;
+                    }
+                    // Search symbols in the project
+                    KeyCode::Char('/') => return EventControlFlow::OpenSearch,
+                    // Toggle relax relocation diffs
+                    KeyCode::Char('x') => {
+                        state.relax_reloc_diffs = !state.relax_reloc_diffs;
+                        return EventControlFlow::Reload;
+                    }
+                    _ => {}
+                }
+            }
+            Event::Mouse(event) => match event.kind {
+                MouseEventKind::ScrollDown => {
+                    self.scroll_y += 3;
+                    result.redraw = true;
+                }
+                MouseEventKind::ScrollUp => {
+                    self.scroll_y = self.scroll_y.saturating_sub(3);
+                    result.redraw = true;
+                }
+                MouseEventKind::ScrollRight => {
+                    self.scroll_x += 3;
+                    result.redraw = true;
+                }
+                MouseEventKind::ScrollLeft => {
+                    self.scroll_x = self.scroll_x.saturating_sub(3);
+                    result.redraw = true;
+                }
+                _ => {}
+            },
+            Event::Resize(_, _) => {
+                result.redraw = true;
+            }
+            _ => {}
+        }
+        EventControlFlow::Continue(result)
+    }
+
+    fn reload(&mut self, state: &AppState) -> Result<()> {
+        let left_sym =
+            state.left_obj.as_ref().and_then(|(o, _)| find_data_symbol(o, &self.symbol_name));
+        let right_sym =
+            state.right_obj.as_ref().and_then(|(o, _)| find_data_symbol(o, &self.symbol_name));
+        let num_rows = |(symbol, diff): (&ObjSymbol, &ObjSymbolDiff)| {
+            if diff.data_fields.is_empty() {
+                symbol.bytes.len().div_ceil(BYTES_PER_ROW)
+            } else {
+                diff.data_fields.len()
+            }
+        };
+        self.num_rows = match (
+            get_symbol(state.left_obj.as_ref(), left_sym),
+            get_symbol(state.right_obj.as_ref(), right_sym),
+        ) {
+            (Some(l), Some(r)) => num_rows(l).max(num_rows(r)),
+            (Some(l), None) => num_rows(l),
+            (None, Some(r)) => num_rows(r),
+            (None, None) => bail!("Symbol not found: {}", self.symbol_name),
+        };
+        self.left_sym = left_sym;
+        self.right_sym = right_sym;
+        Ok(())
+    }
+}
+
+impl DataDiffUi {
+    fn print_sym(
+        &self,
+        symbol: &ObjSymbol,
+        symbol_diff: &ObjSymbolDiff,
+        other_bytes: &[u8],
+        relocs: &[DataReloc],
+        rect: Rect,
+    ) -> Text<'static> {
+        let mut out = Text::default();
+        let rows = self.scroll_y..self.scroll_y + rect.height as usize;
+        if symbol_diff.data_fields.is_empty() {
+            // No type information, compare bytes at the same offsets
+            for row in rows {
+                let start = row * BYTES_PER_ROW;
+                if start >= symbol.bytes.len() {
+                    break;
+                }
+                let end = (start + BYTES_PER_ROW).min(symbol.bytes.len());
+                let mut line = Line::default();
+                line.spans.push(Span::raw(format!("{start:04x}: ")));
+                for (i, byte) in symbol.bytes[start..end].iter().enumerate() {
+                    let reloc = relocs.iter().find(|r| r.contains(start + i));
+                    let color = match (reloc, other_bytes.get(start + i)) {
+                        (Some(reloc), _) => data_kind_color(reloc.kind),
+                        (None, Some(other)) if other == byte => Color::Gray,
+                        (None, Some(_)) => Color::Cyan,
+                        (None, None) => Color::Green,
+                    };
+                    line.spans.push(Span::styled(format!("{byte:02x} "), Style::new().fg(color)));
+                }
+                for reloc in relocs.iter().filter(|r| (start..end).contains(&r.offset)) {
+                    line.spans.push(Span::styled(
+                        format!(" {}", reloc.target),
+                        Style::new().fg(data_kind_color(reloc.kind)),
+                    ));
+                }
+                out.lines.push(line);
+            }
+            return out;
+        }
+        for diff in symbol_diff.data_fields.iter().skip(rows.start).take(rows.len()) {
+            let Some(field) = &diff.field else {
+                out.lines.push(Line::default());
+                continue;
+            };
+            let color = data_kind_color(diff.kind);
+            let mut line = Line::default();
+            line.spans.push(Span::raw(format!("{:04x}: ", field.offset)));
+            line.spans.push(Span::styled(
+                format!("{}{}", symbol.name, field.path),
+                Style::new().fg(color),
+            ));
+            if let Some(value) = &field.value {
+                line.spans.push(Span::raw(": "));
+                line.spans.push(Span::styled(value.clone(), Style::new().fg(color)));
+            }
+            out.lines.push(line);
+        }
+        out
+    }
+
+    fn print_margin(&self, data_fields: &[ObjDataFieldDiff], rect: Rect) -> Text<'static> {
+        let mut out = Text::default();
+        for diff in data_fields.iter().skip(self.scroll_y).take(rect.height as usize) {
+            out.lines.push(Line::raw(match diff.kind {
+                ObjDataDiffKind::None => " ",
+                ObjDataDiffKind::Delete => "<",
+                ObjDataDiffKind::Insert => ">",
+                ObjDataDiffKind::Replace => "|",
+            }));
+        }
+        out
+    }
+}
+
+fn data_kind_color(kind: ObjDataDiffKind) -> Color {
+    match kind {
+        ObjDataDiffKind::None => Color::Gray,
+        ObjDataDiffKind::Replace => Color::Cyan,
+        ObjDataDiffKind::Delete => Color::Red,
+        ObjDataDiffKind::Insert => Color::Green,
+    }
+}
+
+#[inline]
+fn get_symbol(
+    obj: Option<&(ObjInfo, ObjDiff)>,
+    sym: Option<SymbolRef>,
+) -> Option<(&ObjSymbol, &ObjSymbolDiff)> {
+    let (obj, diff) = obj?;
+    let sym = sym?;
+    Some((obj.section_symbol(sym).1, diff.symbol_diff(sym)))
+}
+
+/// A relocation within a data symbol, as diffed in its section.
+struct DataReloc {
+    /// Offset into the symbol
+    offset: usize,
+    len: usize,
+    kind: ObjDataDiffKind,
+    target: String,
+}
+
+impl DataReloc {
+    fn contains(&self, offset: usize) -> bool {
+        offset >= self.offset && offset < self.offset + self.len
+    }
+}
+
+/// Collects the relocations within a data symbol from its section's data diff.
+fn symbol_relocs(obj: Option<&(ObjInfo, ObjDiff)>, sym: Option<SymbolRef>) -> Vec<DataReloc> {
+    let (Some((obj, diff)), Some(sym)) = (obj, sym) else {
+        return vec![];
+    };
+    let (_, symbol) = obj.section_symbol(sym);
+    let range = symbol.section_address..symbol.section_address + symbol.size;
+    let mut address = 0u64;
+    let mut relocs = vec![];
+    for data_diff in &diff.sections[sym.section_idx].data_diff {
+        // Diffs without data are gaps for bytes only present in the other object
+        if data_diff.data.is_empty() {
+            continue;
+        }
+        if let Some(reloc) = &data_diff.reloc {
+            if range.contains(&address) {
+                relocs.push(DataReloc {
+                    offset: (address - range.start) as usize,
+                    len: data_diff.len,
+                    kind: data_diff.kind,
+                    target: display_data_reloc(reloc),
+                });
+            }
+        }
+        address += data_diff.len as u64;
+    }
+    relocs
+}
+
+pub fn find_data_symbol(obj: &ObjInfo, name: &str) -> Option<SymbolRef> {
+    for (section_idx, section) in obj.sections.iter().enumerate() {
+        if section.kind != ObjSectionKind::Data {
+            continue;
+        }
+        for (symbol_idx, symbol) in section.symbols.iter().enumerate() {
+            if symbol.name == name {
+                return Some(SymbolRef { section_idx, symbol_idx });
+            }
+        }
+    }
+    None
+}
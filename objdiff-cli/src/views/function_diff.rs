@@ -1,9 +1,10 @@
 use anyhow::{bail, Result};
 use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
 use objdiff_core::{
+    arch::ObjArch,
     diff::{
-        display::{display_diff, DiffText, HighlightKind},
-        ObjDiff, ObjInsDiffKind, ObjSymbolDiff,
+        display::{display_diff, ins_annotations, source_line_diffs, DiffText, HighlightKind},
+        DiffAlgorithm, ObjDiff, ObjInsDiffKind, ObjSymbolDiff,
     },
     obj::{ObjInfo, ObjSectionKind, ObjSymbol, SymbolRef},
 };
@@ -33,6 +34,11 @@ pub struct FunctionDiffUi {
     pub prev_sym: Option<SymbolRef>,
     pub open_options: bool,
     pub three_way: bool,
+    /// Instruction row selected by clicking it
+    pub selected_row: Option<usize>,
+    pub show_source: bool,
+    /// Lines of the unit's source file, or the error reading it
+    pub source: Option<Result<Vec<String>, String>>,
 }
 
 impl UiView for FunctionDiffUi {
@@ -45,6 +51,13 @@ impl UiView for FunctionDiffUi {
             Constraint::Length(2),
         ])
         .split(chunks[0]);
+        let (diff_area, source_area) = if self.show_source {
+            let chunks =
+                Layout::horizontal([Constraint::Fill(2), Constraint::Fill(1)]).split(chunks[1]);
+            (chunks[0], Some(chunks[1]))
+        } else {
+            (chunks[1], None)
+        };
         let content_chunks = if self.three_way {
             Layout::horizontal([
                 Constraint::Fill(1),
@@ -54,7 +67,7 @@ impl UiView for FunctionDiffUi {
                 Constraint::Fill(1),
                 Constraint::Length(2),
             ])
-            .split(chunks[1])
+            .split(diff_area)
         } else {
             Layout::horizontal([
                 Constraint::Fill(1),
@@ -62,7 +75,7 @@ impl UiView for FunctionDiffUi {
                 Constraint::Fill(1),
                 Constraint::Length(2),
             ])
-            .split(chunks[1])
+            .split(diff_area)
         };
 
         self.per_page = chunks[1].height.saturating_sub(2) as usize;
@@ -73,6 +86,17 @@ impl UiView for FunctionDiffUi {
         self.scroll_state_y =
             self.scroll_state_y.content_length(max_scroll_y).position(self.scroll_y);
 
+        // Select the clicked instruction row
+        let rows = diff_area.inner(Margin::new(0, 1));
+        if let Some((cx, cy)) = result.click_xy {
+            if !self.open_options && rows.contains(Position::new(cx, cy)) {
+                let row = self.scroll_y + (cy - rows.y) as usize;
+                if row < self.num_rows {
+                    self.selected_row = Some(row);
+                }
+            }
+        }
+
         let mut line_l = Line::default();
         line_l
             .spans
@@ -80,14 +104,21 @@ impl UiView for FunctionDiffUi {
         f.render_widget(line_l, header_chunks[0]);
 
         let mut line_r = Line::default();
-        if let Some(percent) =
-            get_symbol(state.right_obj.as_ref(), self.right_sym).and_then(|(_, d)| d.match_percent)
-        {
+        let right_diff = get_symbol(state.right_obj.as_ref(), self.right_sym).map(|(_, d)| d);
+        if let Some(percent) = right_diff.and_then(|d| d.match_percent) {
             line_r.spans.push(Span::styled(
                 format!("{:.2}% ", percent),
                 Style::new().fg(match_percent_color(percent)),
             ));
         }
+        if let Some(score) = right_diff.and_then(|d| d.score) {
+            line_r
+                .spans
+                .push(Span::styled(format!("(score {score}) "), Style::new().fg(Color::Gray)));
+        }
+        if right_diff.is_some_and(|d| d.approximate) {
+            line_r.spans.push(Span::styled("(approximate) ", Style::new().fg(Color::Yellow)));
+        }
         let reload_time = state
             .reload_time
             .as_ref()
@@ -106,11 +137,14 @@ impl UiView for FunctionDiffUi {
         let mut left_text = None;
         let mut left_highlight = None;
         let mut max_width = 0;
-        if let Some((symbol, symbol_diff)) = get_symbol(state.left_obj.as_ref(), self.left_sym) {
+        if let (Some((obj, _)), Some((symbol, symbol_diff))) =
+            (state.left_obj.as_ref(), get_symbol(state.left_obj.as_ref(), self.left_sym))
+        {
             let mut text = Text::default();
             let rect = content_chunks[0].inner(Margin::new(0, 1));
             left_highlight = self.print_sym(
                 &mut text,
+                obj.arch.as_ref(),
                 symbol,
                 symbol_diff,
                 rect,
@@ -125,11 +159,14 @@ impl UiView for FunctionDiffUi {
         let mut right_text = None;
         let mut right_highlight = None;
         let mut margin_text = None;
-        if let Some((symbol, symbol_diff)) = get_symbol(state.right_obj.as_ref(), self.right_sym) {
+        if let (Some((obj, _)), Some((symbol, symbol_diff))) =
+            (state.right_obj.as_ref(), get_symbol(state.right_obj.as_ref(), self.right_sym))
+        {
             let mut text = Text::default();
             let rect = content_chunks[2].inner(Margin::new(0, 1));
             right_highlight = self.print_sym(
                 &mut text,
+                obj.arch.as_ref(),
                 symbol,
                 symbol_diff,
                 rect,
@@ -150,12 +187,14 @@ impl UiView for FunctionDiffUi {
         let mut prev_text = None;
         let mut prev_margin_text = None;
         if self.three_way {
-            if let Some((symbol, symbol_diff)) = get_symbol(state.prev_obj.as_ref(), self.prev_sym)
+            if let (Some((obj, _)), Some((symbol, symbol_diff))) =
+                (state.prev_obj.as_ref(), get_symbol(state.prev_obj.as_ref(), self.prev_sym))
             {
                 let mut text = Text::default();
                 let rect = content_chunks[4].inner(Margin::new(0, 1));
                 self.print_sym(
                     &mut text,
+                    obj.arch.as_ref(),
                     symbol,
                     symbol_diff,
                     rect,
@@ -234,10 +273,25 @@ impl UiView for FunctionDiffUi {
             }
         }
 
+        if let Some(source_area) = source_area {
+            let mut text = Text::default();
+            self.print_source(&mut text, state, source_area.inner(Margin::new(0, 1)));
+            f.render_widget(
+                Paragraph::new(text).block(
+                    Block::new()
+                        .borders(Borders::TOP | Borders::LEFT)
+                        .border_style(Style::new().fg(Color::Gray))
+                        .title_style(Style::new().bold())
+                        .title("SOURCE"),
+                ),
+                source_area,
+            );
+        }
+
         // Render scrollbars
         f.render_stateful_widget(
             Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None),
-            chunks[1].inner(Margin::new(0, 1)),
+            diff_area.inner(Margin::new(0, 1)),
             &mut self.scroll_state_y,
         );
         f.render_stateful_widget(
@@ -368,12 +422,34 @@ impl UiView for FunctionDiffUi {
                         self.scroll_x = self.scroll_x.saturating_sub(1);
                         result.redraw = true;
                     }
+                    // Search symbols in the project
+                    KeyCode::Char('/') => return EventControlFlow::OpenSearch,
                     // Toggle relax relocation diffs
                     KeyCode::Char('x') => {
                         state.relax_reloc_diffs = !state.relax_reloc_diffs;
                         result.redraw = true;
                         return EventControlFlow::Reload;
                     }
+                    // Toggle register renaming tolerance
+                    KeyCode::Char('n') => {
+                        state.register_renaming = !state.register_renaming;
+                        return EventControlFlow::Reload;
+                    }
+                    // Cycle instruction alignment algorithms
+                    KeyCode::Char('a') => {
+                        state.diff_algorithm = match state.diff_algorithm {
+                            DiffAlgorithm::Patience => DiffAlgorithm::Myers,
+                            DiffAlgorithm::Myers => DiffAlgorithm::Operand,
+                            DiffAlgorithm::Operand => DiffAlgorithm::Patience,
+                        };
+                        return EventControlFlow::Reload;
+                    }
+                    // Toggle source view
+                    KeyCode::Char('s') => {
+                        self.show_source = !self.show_source;
+                        self.source = self.show_source.then(|| read_source(state));
+                        result.redraw = true;
+                    }
                     // Toggle three-way diff
                     KeyCode::Char('3') => {
                         self.three_way = !self.three_way;
@@ -437,6 +513,10 @@ impl UiView for FunctionDiffUi {
         self.left_sym = left_sym;
         self.right_sym = right_sym;
         self.prev_sym = prev_sym;
+        if self.show_source {
+            // The source file may have changed since the last build
+            self.source = Some(read_source(state));
+        }
         Ok(())
     }
 }
@@ -478,6 +558,7 @@ impl FunctionDiffUi {
     fn print_sym(
         &self,
         out: &mut Text<'static>,
+        arch: &dyn ObjArch,
         symbol: &ObjSymbol,
         symbol_diff: &ObjSymbolDiff,
         rect: Rect,
@@ -487,6 +568,8 @@ impl FunctionDiffUi {
     ) -> Option<HighlightKind> {
         let base_addr = symbol.address;
         let mut new_highlight = None;
+        let has_inlined =
+            symbol.function_info.as_ref().is_some_and(|info| !info.inlined.is_empty());
         for (y, ins_diff) in symbol_diff
             .instructions
             .iter()
@@ -501,12 +584,27 @@ impl FunctionDiffUi {
             let mut sx = rect.x;
             let sy = rect.y + y as u16;
             let mut line = Line::default();
+            if has_inlined {
+                // Mark instructions belonging to inlined functions
+                let depth = match (&symbol.function_info, &ins_diff.ins) {
+                    (Some(info), Some(ins)) => info.inlined_at(ins.address).count(),
+                    _ => 0,
+                };
+                if depth > 0 {
+                    let color = COLOR_ROTATION[(depth - 1) % COLOR_ROTATION.len()];
+                    line.spans.push(Span::styled("│", Style::new().fg(color)));
+                } else {
+                    line.spans.push(Span::raw(" "));
+                }
+                sx += 1;
+            }
             display_diff(ins_diff, base_addr, |text| -> Result<()> {
                 let label_text;
                 let mut base_color = match ins_diff.kind {
                     ObjInsDiffKind::None
                     | ObjInsDiffKind::OpMismatch
-                    | ObjInsDiffKind::ArgMismatch => Color::Gray,
+                    | ObjInsDiffKind::ArgMismatch
+                    | ObjInsDiffKind::RegisterRename => Color::Gray,
                     ObjInsDiffKind::Replace => Color::Cyan,
                     ObjInsDiffKind::Delete => Color::Red,
                     ObjInsDiffKind::Insert => Color::Green,
@@ -563,6 +661,15 @@ impl FunctionDiffUi {
                         return Ok(());
                     }
                     DiffText::Eol => {
+                        if let Some(ins) = &ins_diff.ins {
+                            let annotations = ins_annotations(arch, symbol, ins);
+                            if !annotations.is_empty() {
+                                line.spans.push(Span::styled(
+                                    format!("  {}", annotations.join(", ")),
+                                    Style::new().fg(Color::DarkGray),
+                                ));
+                            }
+                        }
                         return Ok(());
                     }
                 }
@@ -587,17 +694,79 @@ impl FunctionDiffUi {
                 Ok(())
             })
             .unwrap();
+            if self.selected_row == Some(self.scroll_y + y) {
+                line = line.style(Style::new().bg(Color::DarkGray));
+            }
             out.lines.push(line);
         }
         new_highlight
     }
 
+    /// Prints the source file around the selected instruction's line, or the first visible
+    /// instruction's line if none is selected.
+    fn print_source(&self, out: &mut Text<'static>, state: &AppState, rect: Rect) {
+        let lines = match &self.source {
+            Some(Ok(lines)) => lines,
+            Some(Err(e)) => {
+                out.lines.push(Line::styled(e.clone(), Style::new().fg(Color::Red)));
+                return;
+            }
+            None => return,
+        };
+        // Prefer the base object's line info
+        let symbol_diffs = [
+            get_symbol(state.right_obj.as_ref(), self.right_sym),
+            get_symbol(state.left_obj.as_ref(), self.left_sym),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|(_, symbol_diff)| symbol_diff)
+        .collect::<Vec<_>>();
+        let line_diffs = symbol_diffs
+            .iter()
+            .map(|symbol_diff| source_line_diffs(symbol_diff))
+            .find(|line_diffs| !line_diffs.is_empty())
+            .unwrap_or_default();
+        let line_at = |row: usize| {
+            symbol_diffs
+                .iter()
+                .find_map(|symbol_diff| symbol_diff.instructions.get(row)?.ins.as_ref()?.line)
+        };
+        let selected_line = self.selected_row.and_then(line_at);
+        let Some(center_line) = selected_line
+            .or_else(|| (self.scroll_y..self.scroll_y + self.per_page).find_map(line_at))
+        else {
+            out.lines.push(Line::styled("No line info", Style::new().fg(Color::DarkGray)));
+            return;
+        };
+        let height = rect.height as usize;
+        let start = (center_line as usize)
+            .saturating_sub(1 + height / 2)
+            .min(lines.len().saturating_sub(height));
+        for (idx, text) in lines.iter().enumerate().skip(start).take(height) {
+            let line_number = idx as u32 + 1;
+            let mismatched = line_diffs.get(&line_number).copied().unwrap_or_default();
+            let mut line = Line::default();
+            line.spans
+                .push(Span::styled(format!("{line_number:>5} "), Style::new().fg(Color::DarkGray)));
+            line.spans.push(Span::styled(
+                text.clone(),
+                Style::new().fg(if mismatched { Color::Cyan } else { Color::Gray }),
+            ));
+            if selected_line == Some(line_number) {
+                line = line.style(Style::new().bg(Color::DarkGray));
+            }
+            out.lines.push(line);
+        }
+    }
+
     fn print_margin(&self, out: &mut Text, symbol: &ObjSymbolDiff, rect: Rect) {
         for ins_diff in symbol.instructions.iter().skip(self.scroll_y).take(rect.height as usize) {
             if ins_diff.kind != ObjInsDiffKind::None {
                 out.lines.push(Line::raw(match ins_diff.kind {
                     ObjInsDiffKind::Delete => "<",
                     ObjInsDiffKind::Insert => ">",
+                    ObjInsDiffKind::RegisterRename => "~",
                     _ => "|",
                 }));
             } else {
@@ -607,6 +776,16 @@ impl FunctionDiffUi {
     }
 }
 
+/// Reads the lines of the unit's source file.
+fn read_source(state: &AppState) -> Result<Vec<String>, String> {
+    let Some(path) = state.source_path() else {
+        return Err("Source file metadata missing".to_string());
+    };
+    std::fs::read_to_string(&path)
+        .map(|text| text.lines().map(|line| line.replace('\t', "    ")).collect())
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))
+}
+
 pub const COLOR_ROTATION: [Color; 7] = [
     Color::Magenta,
     Color::Cyan,
@@ -1,10 +1,13 @@
 use anyhow::Result;
 use crossterm::event::Event;
+use objdiff_core::index::IndexedSymbol;
 use ratatui::Frame;
 
 use crate::cmd::diff::AppState;
 
+pub mod data_diff;
 pub mod function_diff;
+pub mod symbol_search;
 
 #[derive(Default)]
 pub struct EventResult {
@@ -16,6 +19,12 @@ pub enum EventControlFlow {
     Break,
     Continue(EventResult),
     Reload,
+    /// Open the project-wide symbol search
+    OpenSearch,
+    /// Close the symbol search and return to the current diff
+    CloseSearch,
+    /// Open the diff of a symbol, switching units if needed
+    Navigate(IndexedSymbol),
 }
 
 pub trait UiView {
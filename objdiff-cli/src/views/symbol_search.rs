@@ -0,0 +1,169 @@
+use anyhow::Result;
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use objdiff_core::{index::IndexedSymbol, jobs::Job};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+
+use super::{function_diff::match_percent_color, EventControlFlow, EventResult, UiView};
+use crate::cmd::diff::AppState;
+
+/// Search for symbols across all units of the project.
+#[derive(Default)]
+pub struct SymbolSearchUi {
+    pub query: String,
+    pub selected: usize,
+    pub scroll_y: usize,
+    pub scroll_state_y: ScrollbarState,
+    pub per_page: usize,
+}
+
+fn search<'a>(state: &'a AppState, query: &str) -> Vec<&'a IndexedSymbol> {
+    state.index.as_ref().map(|index| index.search(query)).unwrap_or_default()
+}
+
+impl UiView for SymbolSearchUi {
+    fn draw(&mut self, state: &AppState, f: &mut Frame, _result: &mut EventResult) {
+        let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).split(f.area());
+        let header_chunks = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .split(chunks[0]);
+
+        let results = search(state, &self.query);
+        self.selected = self.selected.min(results.len().saturating_sub(1));
+        self.per_page = chunks[1].height.saturating_sub(1) as usize;
+        // Keep the selected result visible
+        if self.selected < self.scroll_y {
+            self.scroll_y = self.selected;
+        } else if self.per_page > 0 && self.selected >= self.scroll_y + self.per_page {
+            self.scroll_y = self.selected + 1 - self.per_page;
+        }
+        let max_scroll_y = results.len().saturating_sub(self.per_page);
+        self.scroll_y = self.scroll_y.min(max_scroll_y);
+        self.scroll_state_y =
+            self.scroll_state_y.content_length(max_scroll_y).position(self.scroll_y);
+
+        let mut line_l = Line::default();
+        line_l.spans.push(Span::styled("/", Style::new().fg(Color::Gray)));
+        line_l.spans.push(Span::styled(self.query.clone(), Style::new().fg(Color::White).bold()));
+        line_l.spans.push(Span::styled("_", Style::new().fg(Color::Gray)));
+        f.render_widget(line_l, header_chunks[0]);
+
+        let mut line_r = Line::default();
+        if state.jobs.is_running(Job::SymbolIndex) {
+            line_r.spans.push(Span::styled("Indexing… ", Style::new().fg(Color::LightYellow)));
+        }
+        if state.index.is_some() {
+            line_r.spans.push(Span::styled(
+                format!("{} results", results.len()),
+                Style::new().fg(Color::White),
+            ));
+        }
+        f.render_widget(line_r, header_chunks[2]);
+
+        let unit_width = results.iter().map(|s| s.unit.len()).max().unwrap_or_default();
+        let section_width = results.iter().map(|s| s.section.len()).max().unwrap_or_default();
+        let mut text = Text::default();
+        for (i, symbol) in results.iter().enumerate().skip(self.scroll_y).take(self.per_page) {
+            let mut line = Line::default();
+            match symbol.match_percent {
+                Some(percent) => line.spans.push(Span::styled(
+                    format!("{:>6.2}% ", percent),
+                    Style::new().fg(match_percent_color(percent)),
+                )),
+                None => line.spans.push(Span::raw(" ".repeat(8))),
+            }
+            line.spans.push(Span::styled(
+                format!("{:unit_width$} {:section_width$} ", symbol.unit, symbol.section),
+                Style::new().fg(Color::Gray),
+            ));
+            let name = symbol.demangled_name.as_deref().unwrap_or(symbol.name());
+            let mut style = Style::new().fg(Color::White);
+            if symbol.target_name.is_none() {
+                // Symbol only exists in the base object
+                style = style.fg(Color::DarkGray);
+            }
+            line.spans.push(Span::styled(name.to_string(), style));
+            if i == self.selected {
+                line = line.style(Style::new().bg(Color::DarkGray));
+            }
+            text.lines.push(line);
+        }
+        f.render_widget(
+            Paragraph::new(text).block(
+                Block::new()
+                    .borders(Borders::TOP)
+                    .border_style(Style::new().fg(Color::Gray))
+                    .title_style(Style::new().bold())
+                    .title("SYMBOLS"),
+            ),
+            chunks[1],
+        );
+
+        f.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None),
+            chunks[1].inner(Margin::new(0, 1)),
+            &mut self.scroll_state_y,
+        );
+    }
+
+    fn handle_event(&mut self, state: &mut AppState, event: Event) -> EventControlFlow {
+        let mut result = EventResult::default();
+        match event {
+            Event::Key(event)
+                if matches!(event.kind, KeyEventKind::Press | KeyEventKind::Repeat) =>
+            {
+                match event.code {
+                    // Close search
+                    KeyCode::Esc => return EventControlFlow::CloseSearch,
+                    // Open selected symbol
+                    KeyCode::Enter => {
+                        if let Some(symbol) = search(state, &self.query).get(self.selected) {
+                            return EventControlFlow::Navigate((*symbol).clone());
+                        }
+                    }
+                    KeyCode::Down => {
+                        self.selected += 1;
+                        result.redraw = true;
+                    }
+                    KeyCode::Up => {
+                        self.selected = self.selected.saturating_sub(1);
+                        result.redraw = true;
+                    }
+                    KeyCode::PageDown => {
+                        self.selected += self.per_page;
+                        result.redraw = true;
+                    }
+                    KeyCode::PageUp => {
+                        self.selected = self.selected.saturating_sub(self.per_page);
+                        result.redraw = true;
+                    }
+                    KeyCode::Backspace => {
+                        self.query.pop();
+                        self.selected = 0;
+                        result.redraw = true;
+                    }
+                    KeyCode::Char(c) => {
+                        self.query.push(c);
+                        self.selected = 0;
+                        result.redraw = true;
+                    }
+                    _ => {}
+                }
+            }
+            Event::Resize(_, _) => {
+                result.redraw = true;
+            }
+            _ => {}
+        }
+        EventControlFlow::Continue(result)
+    }
+
+    fn reload(&mut self, _state: &AppState) -> Result<()> { Ok(()) }
+}
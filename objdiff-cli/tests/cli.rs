@@ -0,0 +1,301 @@
+//! End-to-end tests for the non-interactive commands.
+//!
+//! Each test sets up a project in a temporary directory from the `objdiff-core` fixtures (see
+//! `objdiff-core/tests/data/build.sh`), runs the `objdiff-cli` binary on it and summarizes the
+//! output, which is compared against the snapshots in `tests/snapshots`.
+//!
+//! When a change in output is intended, regenerate the snapshots with:
+//! ```sh
+//! cargo insta test -p objdiff-cli --accept
+//! ```
+//! or, without `cargo-insta` installed:
+//! ```sh
+//! INSTA_UPDATE=always cargo test -p objdiff-cli
+//! ```
+
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::SystemTime,
+};
+
+use serde_json::Value;
+use tempfile::TempDir;
+
+fn fixture_path(arch: &str, name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../objdiff-core/tests/data").join(arch).join(name)
+}
+
+/// Creates a project directory with the given config file and fixture objects, copied from
+/// `(arch, name)` to the relative path.
+fn project(config_name: &str, config: &str, objects: &[(&str, (&str, &str))]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(config_name), config).unwrap();
+    for (path, (arch, name)) in objects {
+        copy_fixture(dir.path(), path, arch, name);
+    }
+    dir
+}
+
+fn copy_fixture(dir: &Path, path: &str, arch: &str, name: &str) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::copy(fixture_path(arch, name), path).unwrap();
+}
+
+/// Runs `objdiff-cli` in the given directory, returning its standard output.
+fn objdiff(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_objdiff-cli"))
+        .current_dir(dir)
+        .args(["--no-color", "-L", "warn"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "objdiff-cli {} failed:\n{}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn read_json(path: &Path) -> Value { serde_json::from_slice(&fs::read(path).unwrap()).unwrap() }
+
+fn format_percent(percent: Option<f64>) -> String {
+    match percent {
+        Some(percent) => format!("{percent:.2}%"),
+        None => "-".to_string(),
+    }
+}
+
+/// Renders the functions of one side of a unit's `DiffResult` JSON.
+fn render_diff_side(out: &mut String, side: &str, diff: &Value) {
+    for section in diff["sections"].as_array().into_iter().flatten() {
+        writeln!(out, "  {side} section {}", section["name"].as_str().unwrap()).unwrap();
+        for function in section["functions"].as_array().into_iter().flatten() {
+            writeln!(
+                out,
+                "    {} instructions: {} match: {}",
+                function["symbol"]["name"].as_str().unwrap(),
+                function["instructions"].as_array().map_or(0, Vec::len),
+                format_percent(function["match_percent"].as_f64())
+            )
+            .unwrap();
+        }
+    }
+}
+
+#[test]
+fn batch_diff() {
+    let config = r#"{
+  "units": [
+    { "name": "main/x86", "target_path": "build/x86/target.o", "base_path": "build/x86/base.o" },
+    { "name": "main/ppc", "target_path": "build/ppc/target.o", "base_path": "build/ppc/base.o" },
+    { "name": "main/missing", "target_path": "build/missing.o", "base_path": "build/x86/base.o" },
+    { "name": "other/x86", "target_path": "build/x86/target.o", "base_path": "build/x86/target.o" }
+  ]
+}"#;
+    let dir = project("objdiff.json", config, &[
+        ("build/x86/target.o", ("x86", "target.o")),
+        ("build/x86/base.o", ("x86", "base.o")),
+        ("build/ppc/target.o", ("ppc", "target.o")),
+        ("build/ppc/base.o", ("ppc", "base.o")),
+    ]);
+
+    // Combined output has one JSON document per line, and units that haven't been built are
+    // skipped
+    let stdout = objdiff(dir.path(), &["diff", "-b", "main/*", "--combined", "-o", "-"]);
+    let results =
+        stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect::<Vec<Value>>();
+    let mut out = String::new();
+    for result in &results {
+        writeln!(out, "unit {}", result["unit_name"].as_str().unwrap()).unwrap();
+        render_diff_side(&mut out, "left", &result["result"]["left"]);
+        render_diff_side(&mut out, "right", &result["result"]["right"]);
+    }
+    insta::assert_snapshot!("batch_diff", out);
+
+    // Pretty output is a single array
+    let stdout = objdiff(dir.path(), &[
+        "diff",
+        "-b",
+        "main/*",
+        "--combined",
+        "-o",
+        "-",
+        "--format",
+        "json-pretty",
+    ]);
+    assert_eq!(serde_json::from_str::<Vec<Value>>(&stdout).unwrap(), results);
+
+    // Otherwise each unit is written to its own file, named after the unit
+    objdiff(dir.path(), &["diff", "-b", "main/x86", "-b", "other/*", "-o", "out"]);
+    let x86 = read_json(&dir.path().join("out/main/x86.json"));
+    assert_eq!(x86, results[0]);
+    let other = read_json(&dir.path().join("out/other/x86.json"));
+    assert_eq!(other["unit_name"], "other/x86");
+    assert!(!dir.path().join("out/main/ppc.json").exists());
+    assert!(!dir.path().join("out/main/missing.json").exists());
+}
+
+/// Lists the entries of a report cache directory.
+fn cache_entries(dir: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let mut entries = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap())
+        .map(|e| (e.path(), e.metadata().unwrap().modified().unwrap()))
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+}
+
+fn unit_fuzzy_match(report: &Value, unit: &str) -> f64 {
+    let unit = report["units"].as_array().unwrap().iter().find(|u| u["name"] == unit).unwrap();
+    unit["measures"]["fuzzy_match_percent"].as_f64().unwrap_or(0.0)
+}
+
+#[test]
+fn report_cache() {
+    let config = r#"{
+  "units": [
+    { "name": "x86", "target_path": "x86/target.o", "base_path": "x86/base.o" },
+    { "name": "ppc", "target_path": "ppc/target.o", "base_path": "ppc/base.o" }
+  ]
+}"#;
+    let dir = project("objdiff.json", config, &[
+        ("x86/target.o", ("x86", "target.o")),
+        ("x86/base.o", ("x86", "base.o")),
+        ("ppc/target.o", ("ppc", "target.o")),
+        ("ppc/base.o", ("ppc", "base.o")),
+    ]);
+    let cache = dir.path().join("cache");
+    let generate = || {
+        objdiff(dir.path(), &["report", "generate", "-c", "cache", "-o", "report.json"]);
+        read_json(&dir.path().join("report.json"))
+    };
+    let uncached = {
+        objdiff(dir.path(), &["report", "generate", "-o", "report.json"]);
+        read_json(&dir.path().join("report.json"))
+    };
+
+    // The first run fills the cache, and the second reuses every entry
+    let report = generate();
+    assert_eq!(report, uncached);
+    let entries = cache_entries(&cache);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(generate(), report);
+    assert_eq!(cache_entries(&cache), entries);
+
+    // Changing an object only invalidates its unit
+    copy_fixture(dir.path(), "x86/base.o", "x86", "target.o");
+    let report = generate();
+    assert_eq!(unit_fuzzy_match(&report, "x86"), 100.0);
+    let new_entries = cache_entries(&cache);
+    assert_eq!(new_entries.len(), 3);
+    assert!(entries.iter().all(|e| new_entries.contains(e)));
+
+    // Changing a unit's config only invalidates that unit
+    let config = config.replace(
+        r#""base_path": "ppc/base.o""#,
+        r#""base_path": "ppc/base.o", "metadata": { "complete": true }"#,
+    );
+    fs::write(dir.path().join("objdiff.json"), config).unwrap();
+    let report = generate();
+    assert_eq!(report["measures"]["complete_units"], 1);
+    assert_eq!(cache_entries(&cache).len(), 4);
+}
+
+#[test]
+fn report_history() {
+    let config = r#"{
+  "units": [
+    { "name": "x86", "target_path": "x86/target.o", "base_path": "x86/base.o" },
+    { "name": "ppc", "target_path": "ppc/target.o", "base_path": "ppc/base.o" }
+  ]
+}"#;
+    let dir = project("objdiff.json", config, &[
+        ("x86/target.o", ("x86", "target.o")),
+        ("ppc/target.o", ("ppc", "target.o")),
+        ("ppc/base.o", ("ppc", "base.o")),
+    ]);
+    // The x86 unit's base matches the target in the second entry, then regresses in the third
+    let entries = [
+        ("1111111", "1700000000", "base.o", "json"),
+        ("2222222", "1700086400", "target.o", "proto"),
+        ("3333333", "1700172800", "base.o", "json"),
+        ("4444444", "1700259200", "base.o", "json"),
+    ];
+    for (commit, timestamp, base, format) in entries {
+        copy_fixture(dir.path(), "x86/base.o", "x86", base);
+        objdiff(dir.path(), &["report", "generate", "-o", "report", "-f", format]);
+        objdiff(dir.path(), &[
+            "report",
+            "history",
+            "add",
+            "history.log",
+            "report",
+            "-c",
+            commit,
+            "--timestamp",
+            timestamp,
+        ]);
+    }
+
+    let mut out = String::new();
+    let mut query = |name: &str, command: &str, args: &[&str]| {
+        let args = [&["report", "history", command, "history.log"], args].concat();
+        let csv = objdiff(dir.path(), &[&args[..], &["-f", "csv"]].concat());
+        writeln!(out, "# {name}\n{csv}").unwrap();
+        // JSON output has the same rows
+        let json = objdiff(dir.path(), &[&args[..], &["-f", "json"]].concat());
+        let rows = serde_json::from_str::<Vec<Value>>(&json).unwrap();
+        assert_eq!(rows.len(), csv.lines().count() - 1);
+    };
+    query("trend", "trend", &[]);
+    query("trend of x86", "trend", &["-u", "x86"]);
+    query("regressions", "regressions", &[]);
+    query("regressions in the last 3 entries", "regressions", &["-n", "3"]);
+    query("first match", "first-match", &["_accumulate"]);
+    query("never matched", "first-match", &["_accumulate", "-u", "ppc"]);
+    insta::assert_snapshot!("report_history", out);
+}
+
+#[test]
+fn mapping_edits() {
+    let config = "\
+# Project comment
+units:
+  - name: main/x86 # The unit being edited
+    target_path: x86/target.o
+    base_path: pairing/base.o
+  # Trailing unit
+  - name: main/other
+    target_path: x86/target.o
+";
+    let dir = project("objdiff.yml", config, &[
+        ("x86/target.o", ("x86", "target.o")),
+        ("pairing/base.o", ("pairing", "base.o")),
+    ]);
+    let config_path = dir.path().join("objdiff.yml");
+    let mapping = |args: &[&str]| objdiff(dir.path(), &[&["mapping"], args].concat());
+
+    let mut out = String::new();
+    mapping(&["add", "-u", "main/x86", "_add_one", "_func_00401000"]);
+    writeln!(out, "# suggest\n{}", mapping(&["suggest", "-u", "main/x86"])).unwrap();
+    mapping(&["suggest", "-u", "main/x86", "--apply"]);
+    writeln!(out, "# list\n{}", mapping(&["list", "-u", "main/x86"])).unwrap();
+    // Mappings can be removed by their base symbol
+    mapping(&["remove", "-u", "main/x86", "_Accumulate"]);
+    writeln!(out, "# config\n{}", fs::read_to_string(&config_path).unwrap()).unwrap();
+    insta::assert_snapshot!("mapping_edits", out);
+
+    // Removing every mapping restores the original file
+    for symbol in ["_add_one", "_counter", "_select"] {
+        mapping(&["remove", "-u", "main/x86", symbol]);
+    }
+    assert_eq!(mapping(&["list", "-u", "main/x86"]), "");
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), config);
+}
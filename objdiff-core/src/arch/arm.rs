@@ -276,6 +276,38 @@ impl ObjArch for ObjArchArm {
     fn display_reloc(&self, flags: RelocationFlags) -> Cow<'static, str> {
         Cow::Owned(format!("<{flags:?}>"))
     }
+
+    fn is_unconditional_branch(&self, instruction: &ObjIns) -> bool {
+        match instruction.mnemonic.as_ref() {
+            "b" | "bx" => true,
+            // Writes to pc
+            "pop" | "ldm" | "ldmia" | "ldmfd" => instruction.formatted.contains("pc"),
+            "mov" | "ldr" => instruction.formatted.split_whitespace().nth(1) == Some("pc,"),
+            _ => false,
+        }
+    }
+
+    fn dwarf_register_names(&self, register: u16) -> &'static [&'static str] {
+        match register {
+            0 => &["r0", "a1"],
+            1 => &["r1", "a2"],
+            2 => &["r2", "a3"],
+            3 => &["r3", "a4"],
+            4 => &["r4", "v1"],
+            5 => &["r5", "v2"],
+            6 => &["r6", "v3"],
+            7 => &["r7", "v4"],
+            8 => &["r8", "v5"],
+            9 => &["r9", "v6", "sb", "tr"],
+            10 => &["r10", "v7", "sl"],
+            11 => &["r11", "v8", "fp"],
+            12 => &["r12", "ip"],
+            13 => &["sp", "r13"],
+            14 => &["lr", "r14"],
+            15 => &["pc", "r15"],
+            _ => &[],
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -287,6 +319,8 @@ struct DisasmMode {
 impl DisasmMode {
     fn from_symbol<'a>(sym: &Symbol<'a, '_, &'a [u8]>) -> Option<Self> {
         if let Ok(name) = sym.name() {
+            // Mapping symbols may have a suffix, e.g. `$a.0`
+            let name = name.split_once('.').map_or(name, |(prefix, _)| prefix);
             ParseMode::from_mapping_symbol(name)
                 .map(|mapping| DisasmMode { address: sym.address() as u32, mapping })
         } else {
@@ -1,7 +1,7 @@
 use std::{borrow::Cow, cmp::Ordering, collections::BTreeMap};
 
 use anyhow::{bail, Result};
-use object::{elf, File, Relocation, RelocationFlags};
+use object::{elf, macho, File, Relocation, RelocationFlags};
 use yaxpeax_arch::{Arch, Decoder, Reader, U8Reader};
 use yaxpeax_arm::armv8::a64::{
     ARMv8, DecodeError, InstDecoder, Instruction, Opcode, Operand, SIMDSizeCode, ShiftStyle,
@@ -134,11 +134,33 @@ impl ObjArch for ObjArchArm64 {
     fn implcit_addend(
         &self,
         _file: &File<'_>,
-        _section: &ObjSection,
+        section: &ObjSection,
         address: u64,
         reloc: &Relocation,
     ) -> Result<i64> {
-        bail!("Unsupported ARM64 implicit relocation {:#x}:{:?}", address, reloc.flags())
+        match reloc.flags() {
+            // Instruction fields are zero in Mach-O, addends use a preceding ARM64_RELOC_ADDEND
+            RelocationFlags::MachO {
+                r_type:
+                    macho::ARM64_RELOC_BRANCH26
+                    | macho::ARM64_RELOC_PAGE21
+                    | macho::ARM64_RELOC_PAGEOFF12
+                    | macho::ARM64_RELOC_GOT_LOAD_PAGE21
+                    | macho::ARM64_RELOC_GOT_LOAD_PAGEOFF12
+                    | macho::ARM64_RELOC_TLVP_LOAD_PAGE21
+                    | macho::ARM64_RELOC_TLVP_LOAD_PAGEOFF12,
+                ..
+            } => Ok(0),
+            RelocationFlags::MachO { r_type: macho::ARM64_RELOC_UNSIGNED, r_length, .. } => {
+                let start = address as usize;
+                match r_length {
+                    2 => Ok(i32::from_le_bytes(section.data[start..start + 4].try_into()?) as i64),
+                    3 => Ok(i64::from_le_bytes(section.data[start..start + 8].try_into()?)),
+                    _ => bail!("Unsupported ARM64 relocation length {r_length}"),
+                }
+            }
+            flags => bail!("Unsupported ARM64 implicit relocation {:#x}:{:?}", address, flags),
+        }
     }
 
     fn demangle(&self, name: &str) -> Option<String> {
@@ -147,6 +169,15 @@ impl ObjArch for ObjArchArm64 {
             .and_then(|s| s.demangle(&cpp_demangle::DemangleOptions::default()).ok())
     }
 
+    fn data_reloc_size(&self, flags: RelocationFlags) -> usize {
+        match flags {
+            RelocationFlags::Elf { r_type: elf::R_AARCH64_ABS64 | elf::R_AARCH64_PREL64 } => 8,
+            RelocationFlags::Elf { r_type: elf::R_AARCH64_ABS16 | elf::R_AARCH64_PREL16 } => 2,
+            RelocationFlags::MachO { r_length, .. } => 1 << r_length,
+            _ => 4,
+        }
+    }
+
     fn display_reloc(&self, flags: RelocationFlags) -> Cow<'static, str> {
         match flags {
             RelocationFlags::Elf { r_type: elf::R_AARCH64_ADR_PREL_PG_HI21 } => {
@@ -170,11 +201,50 @@ impl ObjArch for ObjArchArm64 {
             RelocationFlags::Elf { r_type: elf::R_AARCH64_LD64_GOT_LO12_NC } => {
                 Cow::Borrowed("R_AARCH64_LD64_GOT_LO12_NC")
             }
+            RelocationFlags::MachO { r_type, .. } => match r_type {
+                macho::ARM64_RELOC_UNSIGNED => Cow::Borrowed("ARM64_RELOC_UNSIGNED"),
+                macho::ARM64_RELOC_SUBTRACTOR => Cow::Borrowed("ARM64_RELOC_SUBTRACTOR"),
+                macho::ARM64_RELOC_BRANCH26 => Cow::Borrowed("ARM64_RELOC_BRANCH26"),
+                macho::ARM64_RELOC_PAGE21 => Cow::Borrowed("ARM64_RELOC_PAGE21"),
+                macho::ARM64_RELOC_PAGEOFF12 => Cow::Borrowed("ARM64_RELOC_PAGEOFF12"),
+                macho::ARM64_RELOC_GOT_LOAD_PAGE21 => Cow::Borrowed("ARM64_RELOC_GOT_LOAD_PAGE21"),
+                macho::ARM64_RELOC_GOT_LOAD_PAGEOFF12 => {
+                    Cow::Borrowed("ARM64_RELOC_GOT_LOAD_PAGEOFF12")
+                }
+                macho::ARM64_RELOC_POINTER_TO_GOT => Cow::Borrowed("ARM64_RELOC_POINTER_TO_GOT"),
+                macho::ARM64_RELOC_TLVP_LOAD_PAGE21 => {
+                    Cow::Borrowed("ARM64_RELOC_TLVP_LOAD_PAGE21")
+                }
+                macho::ARM64_RELOC_TLVP_LOAD_PAGEOFF12 => {
+                    Cow::Borrowed("ARM64_RELOC_TLVP_LOAD_PAGEOFF12")
+                }
+                _ => Cow::Owned(format!("<{flags:?}>")),
+            },
             _ => Cow::Owned(format!("<{flags:?}>")),
         }
     }
+
+    fn is_unconditional_branch(&self, instruction: &ObjIns) -> bool {
+        matches!(instruction.mnemonic.as_ref(), "b" | "br" | "ret")
+    }
+
+    fn dwarf_register_names(&self, register: u16) -> &'static [&'static str] {
+        match register {
+            29 => &["x29", "fp"],
+            30 => &["x30", "lr"],
+            31 => &["sp"],
+            0..=28 => std::slice::from_ref(&X_REG_NAMES[register as usize]),
+            _ => &[],
+        }
+    }
 }
 
+const X_REG_NAMES: [&str; 29] = [
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28",
+];
+
 struct DisplayCtx<'a> {
     address: u64,
     section_index: usize,
@@ -2074,6 +2144,14 @@ fn is_pc_offset_reloc(reloc: Option<&ObjReloc>) -> Option<&ObjReloc> {
                 | elf::R_AARCH64_JUMP26
                 | elf::R_AARCH64_CALL26
                 | elf::R_AARCH64_ADR_GOT_PAGE,
+        }
+        | RelocationFlags::MachO {
+            r_type:
+                macho::ARM64_RELOC_BRANCH26
+                | macho::ARM64_RELOC_PAGE21
+                | macho::ARM64_RELOC_GOT_LOAD_PAGE21
+                | macho::ARM64_RELOC_TLVP_LOAD_PAGE21,
+            ..
         } = reloc.flags
         {
             return Some(reloc);
@@ -2086,6 +2164,9 @@ fn is_pc_offset_reloc(reloc: Option<&ObjReloc>) -> Option<&ObjReloc> {
 fn is_imm_reloc(reloc: Option<&ObjReloc>) -> bool {
     matches!(reloc, Some(reloc) if matches!(reloc.flags, RelocationFlags::Elf {
         r_type: elf::R_AARCH64_ADD_ABS_LO12_NC,
+    } | RelocationFlags::MachO {
+        r_type: macho::ARM64_RELOC_PAGEOFF12 | macho::ARM64_RELOC_TLVP_LOAD_PAGEOFF12,
+        ..
     }))
 }
 
@@ -2093,6 +2174,9 @@ fn is_imm_reloc(reloc: Option<&ObjReloc>) -> bool {
 fn is_reg_index_reloc(reloc: Option<&ObjReloc>) -> bool {
     matches!(reloc, Some(reloc) if matches!(reloc.flags, RelocationFlags::Elf {
         r_type: elf::R_AARCH64_LDST32_ABS_LO12_NC | elf::R_AARCH64_LD64_GOT_LO12_NC,
+    } | RelocationFlags::MachO {
+        r_type: macho::ARM64_RELOC_PAGEOFF12 | macho::ARM64_RELOC_GOT_LOAD_PAGEOFF12,
+        ..
     }))
 }
 
@@ -36,6 +36,47 @@ const EF_MIPS_MACH_5900: u32 = 0x00920000;
 
 const R_MIPS15_S3: u32 = 119;
 
+const GPR_NAMES: [&[&str]; 32] = [
+    &["zero", "0"],
+    &["at", "1"],
+    &["v0", "2"],
+    &["v1", "3"],
+    &["a0", "4"],
+    &["a1", "5"],
+    &["a2", "6"],
+    &["a3", "7"],
+    &["t0", "8"],
+    &["t1", "9"],
+    &["t2", "10"],
+    &["t3", "11"],
+    &["t4", "12"],
+    &["t5", "13"],
+    &["t6", "14"],
+    &["t7", "15"],
+    &["s0", "16"],
+    &["s1", "17"],
+    &["s2", "18"],
+    &["s3", "19"],
+    &["s4", "20"],
+    &["s5", "21"],
+    &["s6", "22"],
+    &["s7", "23"],
+    &["t8", "24"],
+    &["t9", "25"],
+    &["k0", "26"],
+    &["k1", "27"],
+    &["gp", "28"],
+    &["sp", "29"],
+    &["fp", "30", "s8"],
+    &["ra", "31"],
+];
+
+const FPR_NAMES: [&str; 32] = [
+    "f0", "f1", "f2", "f3", "f4", "f5", "f6", "f7", "f8", "f9", "f10", "f11", "f12", "f13", "f14",
+    "f15", "f16", "f17", "f18", "f19", "f20", "f21", "f22", "f23", "f24", "f25", "f26", "f27",
+    "f28", "f29", "f30", "f31",
+];
+
 impl ObjArchMips {
     pub fn new(object: &File) -> Result<Self> {
         let mut abi = Abi::NUMERIC;
@@ -271,6 +312,20 @@ impl ObjArch for ObjArchMips {
             _ => Cow::Owned(format!("<{flags:?}>")),
         }
     }
+
+    fn is_unconditional_branch(&self, instruction: &ObjIns) -> bool {
+        matches!(instruction.mnemonic.as_ref(), "j" | "jr" | "b" | "eret")
+    }
+
+    fn branch_delay_slots(&self) -> usize { 1 }
+
+    fn dwarf_register_names(&self, register: u16) -> &'static [&'static str] {
+        match register {
+            0..=31 => GPR_NAMES[register as usize],
+            32..=63 => std::slice::from_ref(&FPR_NAMES[register as usize - 32]),
+            _ => &[],
+        }
+    }
 }
 
 fn push_reloc(args: &mut Vec<ObjInsArg>, reloc: &ObjReloc) -> Result<()> {
@@ -148,6 +148,14 @@ pub trait ObjArch: Send + Sync {
 
     fn display_reloc(&self, flags: RelocationFlags) -> Cow<'static, str>;
 
+    /// Number of bytes patched by a relocation in a data section.
+    fn data_reloc_size(&self, flags: RelocationFlags) -> usize {
+        match flags {
+            RelocationFlags::MachO { r_length, .. } => 1 << r_length,
+            _ => 4,
+        }
+    }
+
     fn symbol_address(&self, symbol: &Symbol) -> u64 { symbol.address() }
 
     fn guess_data_type(&self, _instruction: &ObjIns) -> Option<DataType> { None }
@@ -156,11 +164,37 @@ pub trait ObjArch: Send + Sync {
         Some(format!("Bytes: {:#x?}", bytes))
     }
 
+    /// Names the disassembler may use for the given DWARF register number.
+    /// The first entry is the preferred name.
+    fn dwarf_register_names(&self, _register: u16) -> &'static [&'static str] { &[] }
+
+    /// Resolves a register name, as formatted by the disassembler, to its DWARF register number.
+    fn dwarf_register(&self, name: &str) -> Option<u16> {
+        (0..MAX_DWARF_REGISTER).find(|&register| {
+            self.dwarf_register_names(register).iter().any(|n| register_name_eq(name, n))
+        })
+    }
+
+    /// Whether execution never continues to the next instruction, e.g. unconditional
+    /// branches and returns. Used to split functions into basic blocks.
+    fn is_unconditional_branch(&self, _instruction: &ObjIns) -> bool { false }
+
+    /// Number of delay slot instructions executed after a branch.
+    fn branch_delay_slots(&self) -> usize { 0 }
+
     // Downcast methods
     #[cfg(feature = "ppc")]
     fn ppc(&self) -> Option<&ppc::ObjArchPpc> { None }
 }
 
+/// Upper bound for DWARF register numbers searched by [`ObjArch::dwarf_register`].
+const MAX_DWARF_REGISTER: u16 = 128;
+
+/// Compares register names, ignoring case and assembler prefixes like `%` and `$`.
+pub(crate) fn register_name_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches(['%', '$']).eq_ignore_ascii_case(b.trim_start_matches(['%', '$']))
+}
+
 pub struct ProcessCodeResult {
     pub ops: Vec<u16>,
     pub insts: Vec<ObjIns>,
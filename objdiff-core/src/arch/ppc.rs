@@ -30,6 +30,18 @@ fn is_rel_abs_arg(arg: &Argument) -> bool {
 
 fn is_offset_arg(arg: &Argument) -> bool { matches!(arg, Argument::Offset(_)) }
 
+const GPR_NAMES: [&str; 32] = [
+    "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12", "r13", "r14",
+    "r15", "r16", "r17", "r18", "r19", "r20", "r21", "r22", "r23", "r24", "r25", "r26", "r27",
+    "r28", "r29", "r30", "r31",
+];
+
+const FPR_NAMES: [&str; 32] = [
+    "f0", "f1", "f2", "f3", "f4", "f5", "f6", "f7", "f8", "f9", "f10", "f11", "f12", "f13", "f14",
+    "f15", "f16", "f17", "f18", "f19", "f20", "f21", "f22", "f23", "f24", "f25", "f26", "f27",
+    "f28", "f29", "f30", "f31",
+];
+
 pub struct ObjArchPpc {
     /// Exception info
     pub extab: Option<BTreeMap<usize, ExceptionInfo>>,
@@ -193,6 +205,19 @@ impl ObjArch for ObjArchPpc {
         }
     }
 
+    fn is_unconditional_branch(&self, instruction: &ObjIns) -> bool {
+        matches!(instruction.mnemonic.as_ref(), "b" | "ba" | "blr" | "bctr" | "rfi")
+    }
+
+    fn dwarf_register_names(&self, register: u16) -> &'static [&'static str] {
+        match register {
+            1 => &["r1", "sp"],
+            0..=31 => std::slice::from_ref(&GPR_NAMES[register as usize]),
+            32..=63 => std::slice::from_ref(&FPR_NAMES[register as usize - 32]),
+            _ => &[],
+        }
+    }
+
     fn guess_data_type(&self, instruction: &ObjIns) -> Option<super::DataType> {
         if instruction.reloc.as_ref().is_some_and(|r| r.target.name.starts_with("@stringBase")) {
             return Some(DataType::String);
@@ -481,6 +506,8 @@ fn make_fake_pool_reloc(offset: i16, cur_addr: u32, pool_reloc: &ObjReloc) -> Op
         virtual_address: None,
         original_index: None,
         bytes: vec![],
+        data_type: None,
+        function_info: None,
     };
     // The addend is also fake because we don't know yet if the `target_address` here is the exact
     // start of the symbol or if it's in the middle of it.
@@ -1,12 +1,12 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{borrow::Cow, collections::BTreeMap, ops::Range};
 
 use anyhow::{anyhow, bail, ensure, Result};
 use iced_x86::{
     Decoder, DecoderOptions, DecoratorKind, Formatter, FormatterOutput, FormatterTextKind,
-    GasFormatter, Instruction, IntelFormatter, MasmFormatter, NasmFormatter, NumberKind, OpKind,
-    PrefixKind, Register,
+    GasFormatter, Instruction, IntelFormatter, MasmFormatter, Mnemonic, NasmFormatter, NumberKind,
+    OpKind, PrefixKind, Register,
 };
-use object::{pe, Endian, Endianness, File, Object, Relocation, RelocationFlags};
+use object::{elf, macho, pe, Endian, Endianness, File, Object, Relocation, RelocationFlags};
 
 use crate::{
     arch::{ObjArch, ProcessCodeResult},
@@ -30,7 +30,7 @@ impl ObjArch for ObjArchX86 {
         &self,
         address: u64,
         code: &[u8],
-        _section_index: usize,
+        section_index: usize,
         relocations: &[ObjReloc],
         line_info: &BTreeMap<u64, u32>,
         config: &DiffObjConfig,
@@ -45,6 +45,7 @@ impl ObjArch for ObjArchX86 {
         };
         formatter.options_mut().set_space_after_operand_separator(config.space_between_args);
 
+        let code_range = address..address + code.len() as u64;
         let mut output = InstructionFormatterOutput {
             formatted: String::new(),
             ins: ObjIns {
@@ -61,9 +62,56 @@ impl ObjArch for ObjArchX86 {
             },
             error: None,
             ins_operands: vec![],
+            code_range: code_range.clone(),
         };
+        let mut jump_tables = BTreeMap::<u64, JumpTableKind>::new();
+        let mut jump_table: Option<(u64, JumpTableKind)> = None;
+        let mut relative_dispatch = RelativeDispatch::default();
         let mut instruction = Instruction::default();
         while decoder.can_decode() {
+            // Emit jump table entries as data until an entry doesn't resolve to a destination
+            // within this function
+            let ip = decoder.ip();
+            if let Some(&kind) = jump_tables.get(&ip) {
+                jump_table = Some((ip, kind));
+            }
+            if let Some((table_address, kind)) = jump_table {
+                let offset = decoder.position();
+                let entry = jump_table_entry(
+                    kind,
+                    table_address,
+                    ip,
+                    &code[offset..],
+                    relocations,
+                    section_index,
+                    &code_range,
+                );
+                if let Some((reloc, dest)) = entry {
+                    let mnemonic = match config.x86_formatter {
+                        X86Formatter::Gas => ".int",
+                        _ => "dd",
+                    };
+                    let op = Mnemonic::Dd as u16;
+                    result.ops.push(op);
+                    result.insts.push(ObjIns {
+                        address: ip,
+                        size: 4,
+                        op,
+                        mnemonic: Cow::Borrowed(mnemonic),
+                        args: vec![ObjInsArg::BranchDest(dest)],
+                        reloc: reloc.cloned(),
+                        branch_dest: Some(dest),
+                        line: line_info.range(..=ip).last().map(|(_, &b)| b),
+                        formatted: format!("{} {:#x}", mnemonic, dest),
+                        orig: None,
+                    });
+                    decoder.set_position(offset + 4)?;
+                    decoder.set_ip(ip + 4);
+                    continue;
+                }
+                jump_table = None;
+            }
+
             decoder.decode_out(&mut instruction);
 
             let address = instruction.ip();
@@ -116,6 +164,24 @@ impl ObjArch for ObjArchX86 {
                 bail!("Failed to find relocation in instruction");
             }
 
+            // Relocated branches are handled above, so only resolve local branch targets
+            if reloc.is_none() {
+                output.ins.branch_dest = output.ins.args.iter().find_map(|arg| match arg {
+                    ObjInsArg::BranchDest(dest) => Some(*dest),
+                    _ => None,
+                });
+            }
+            if let Some(table_address) =
+                jump_table_ref(&instruction, reloc, section_index, &code_range)
+            {
+                jump_tables.entry(table_address).or_insert(JumpTableKind::Absolute);
+            }
+            if let Some(table_address) =
+                relative_dispatch.update(&instruction, reloc, section_index, &code_range)
+            {
+                jump_tables.entry(table_address).or_insert(JumpTableKind::Relative);
+            }
+
             result.ops.push(op);
             result.insts.push(output.ins.clone());
 
@@ -138,6 +204,14 @@ impl ObjArch for ObjArchX86 {
                 let data = section.data[address as usize..address as usize + 4].try_into()?;
                 Ok(self.endianness.read_i32_bytes(data) as i64)
             }
+            RelocationFlags::MachO { r_length: 2, .. } => {
+                let data = section.data[address as usize..address as usize + 4].try_into()?;
+                Ok(self.endianness.read_i32_bytes(data) as i64)
+            }
+            RelocationFlags::MachO { r_length: 3, .. } => {
+                let data = section.data[address as usize..address as usize + 8].try_into()?;
+                Ok(self.endianness.read_i64_bytes(data))
+            }
             flags => bail!("Unsupported x86 implicit relocation {flags:?}"),
         }
     }
@@ -152,6 +226,19 @@ impl ObjArch for ObjArchX86 {
         }
     }
 
+    fn data_reloc_size(&self, flags: RelocationFlags) -> usize {
+        match flags {
+            RelocationFlags::Elf { r_type: elf::R_X86_64_64 }
+            | RelocationFlags::Coff { typ: pe::IMAGE_REL_AMD64_ADDR64 }
+                if self.bits == 64 =>
+            {
+                8
+            }
+            RelocationFlags::MachO { r_length, .. } => 1 << r_length,
+            _ => 4,
+        }
+    }
+
     fn display_reloc(&self, flags: RelocationFlags) -> Cow<'static, str> {
         match flags {
             RelocationFlags::Coff { typ } => match typ {
@@ -159,9 +246,198 @@ impl ObjArch for ObjArchX86 {
                 pe::IMAGE_REL_I386_REL32 => Cow::Borrowed("IMAGE_REL_I386_REL32"),
                 _ => Cow::Owned(format!("<{flags:?}>")),
             },
+            RelocationFlags::MachO { r_type, .. } if self.bits == 64 => match r_type {
+                macho::X86_64_RELOC_UNSIGNED => Cow::Borrowed("X86_64_RELOC_UNSIGNED"),
+                macho::X86_64_RELOC_SIGNED => Cow::Borrowed("X86_64_RELOC_SIGNED"),
+                macho::X86_64_RELOC_BRANCH => Cow::Borrowed("X86_64_RELOC_BRANCH"),
+                macho::X86_64_RELOC_GOT_LOAD => Cow::Borrowed("X86_64_RELOC_GOT_LOAD"),
+                macho::X86_64_RELOC_GOT => Cow::Borrowed("X86_64_RELOC_GOT"),
+                macho::X86_64_RELOC_SUBTRACTOR => Cow::Borrowed("X86_64_RELOC_SUBTRACTOR"),
+                macho::X86_64_RELOC_SIGNED_1 => Cow::Borrowed("X86_64_RELOC_SIGNED_1"),
+                macho::X86_64_RELOC_SIGNED_2 => Cow::Borrowed("X86_64_RELOC_SIGNED_2"),
+                macho::X86_64_RELOC_SIGNED_4 => Cow::Borrowed("X86_64_RELOC_SIGNED_4"),
+                macho::X86_64_RELOC_TLV => Cow::Borrowed("X86_64_RELOC_TLV"),
+                _ => Cow::Owned(format!("<{flags:?}>")),
+            },
+            RelocationFlags::MachO { r_type: macho::GENERIC_RELOC_VANILLA, .. } => {
+                Cow::Borrowed("GENERIC_RELOC_VANILLA")
+            }
             _ => Cow::Owned(format!("<{flags:?}>")),
         }
     }
+
+    fn is_unconditional_branch(&self, instruction: &ObjIns) -> bool {
+        matches!(
+            instruction.mnemonic.as_ref(),
+            "jmp"
+                | "jmpq"
+                | "ret"
+                | "retl"
+                | "retq"
+                | "retn"
+                | "retf"
+                | "iret"
+                | "iretd"
+                | "iretq"
+                | "ud2"
+        )
+    }
+
+    fn dwarf_register_names(&self, register: u16) -> &'static [&'static str] {
+        // Include sub-registers, since 32-bit values live in the low half of 64-bit registers
+        let names: &'static [&'static [&'static str]] = if self.bits == 64 {
+            &[
+                &["rax", "eax", "ax", "al"],
+                &["rdx", "edx", "dx", "dl"],
+                &["rcx", "ecx", "cx", "cl"],
+                &["rbx", "ebx", "bx", "bl"],
+                &["rsi", "esi", "si", "sil"],
+                &["rdi", "edi", "di", "dil"],
+                &["rbp", "ebp", "bp", "bpl"],
+                &["rsp", "esp", "sp", "spl"],
+                &["r8", "r8d", "r8w", "r8b"],
+                &["r9", "r9d", "r9w", "r9b"],
+                &["r10", "r10d", "r10w", "r10b"],
+                &["r11", "r11d", "r11w", "r11b"],
+                &["r12", "r12d", "r12w", "r12b"],
+                &["r13", "r13d", "r13w", "r13b"],
+                &["r14", "r14d", "r14w", "r14b"],
+                &["r15", "r15d", "r15w", "r15b"],
+            ]
+        } else {
+            &[
+                &["eax", "ax", "al"],
+                &["ecx", "cx", "cl"],
+                &["edx", "dx", "dl"],
+                &["ebx", "bx", "bl"],
+                &["esp", "sp"],
+                &["ebp", "bp"],
+                &["esi", "si"],
+                &["edi", "di"],
+            ]
+        };
+        names.get(register as usize).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum JumpTableKind {
+    /// Entries are absolute addresses (`jmp dword ptr [table + reg*4]`)
+    Absolute,
+    /// Entries are offsets from the start of the table (`lea base, [rip + table]`, followed by
+    /// `movsxd`, `add` and an indirect `jmp`)
+    Relative,
+}
+
+/// Returns the address of an absolute jump table within the function referenced by an indirect
+/// `jmp`.
+fn jump_table_ref(
+    instruction: &Instruction,
+    reloc: Option<&ObjReloc>,
+    section_index: usize,
+    code_range: &Range<u64>,
+) -> Option<u64> {
+    if instruction.mnemonic() != Mnemonic::Jmp
+        || instruction.op0_kind() != OpKind::Memory
+        || instruction.memory_index() == Register::None
+        || instruction.memory_index_scale() != 4
+        || reloc.is_some_and(|r| r.target.orig_section_index != Some(section_index))
+    {
+        return None;
+    }
+    let address = implicit_reloc_address(reloc, instruction.memory_displacement64());
+    code_range.contains(&address).then_some(address)
+}
+
+/// Resolves the value of a field with an implicit relocation to an address in the target's
+/// section. Relocations against a symbol (such as MSVC's `$LN` labels) keep the value as their
+/// addend, so it's an offset from the symbol. Relocations against a section are retargeted to
+/// the nearest symbol with a different addend, and the value is already the section offset.
+fn implicit_reloc_address(reloc: Option<&ObjReloc>, value: u64) -> u64 {
+    match reloc {
+        Some(reloc) if reloc.addend == value as i64 => reloc.target.address.wrapping_add(value),
+        _ => value,
+    }
+}
+
+/// Tracks the instruction sequence that dispatches through a relative jump table:
+/// `lea base, [rip + table]`, `movsxd offset, dword ptr [base + index*4]`, `add offset, base`
+/// and `jmp offset`.
+#[derive(Default)]
+struct RelativeDispatch {
+    /// Register holding the address of the table, and the table's address
+    base: Option<(Register, u64)>,
+    /// Register holding the entry loaded from the table
+    offset: Option<Register>,
+}
+
+impl RelativeDispatch {
+    /// Returns the address of the jump table once an instruction completes the sequence.
+    fn update(
+        &mut self,
+        instruction: &Instruction,
+        reloc: Option<&ObjReloc>,
+        section_index: usize,
+        code_range: &Range<u64>,
+    ) -> Option<u64> {
+        match instruction.mnemonic() {
+            Mnemonic::Lea if instruction.is_ip_rel_memory_operand() => {
+                let address = instruction.ip_rel_memory_address();
+                let local =
+                    reloc.map_or(true, |r| r.target.orig_section_index == Some(section_index));
+                self.base = if local && code_range.contains(&address) {
+                    Some((instruction.op0_register(), address))
+                } else {
+                    None
+                };
+                self.offset = None;
+            }
+            Mnemonic::Movsxd => {
+                let (base, _) = self.base?;
+                let loads_entry = instruction.op1_kind() == OpKind::Memory
+                    && instruction.memory_base() == base
+                    && instruction.memory_index() != Register::None
+                    && instruction.memory_index_scale() == 4;
+                self.offset = loads_entry.then(|| instruction.op0_register());
+            }
+            Mnemonic::Jmp => {
+                let (base, address) = self.base.take()?;
+                let offset = self.offset.take()?;
+                if instruction.op0_kind() != OpKind::Register {
+                    return None;
+                }
+                let target = instruction.op0_register();
+                return (target == offset || target == base).then_some(address);
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Reads a jump table entry, returning its relocation and destination address.
+fn jump_table_entry<'a>(
+    kind: JumpTableKind,
+    table_address: u64,
+    address: u64,
+    data: &[u8],
+    relocations: &'a [ObjReloc],
+    section_index: usize,
+    code_range: &Range<u64>,
+) -> Option<(Option<&'a ObjReloc>, u64)> {
+    let value = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+    let reloc = relocations.iter().find(|r| r.address == address);
+    if reloc.is_some_and(|r| r.target.orig_section_index != Some(section_index)) {
+        return None;
+    }
+    let dest = match kind {
+        JumpTableKind::Absolute => implicit_reloc_address(reloc, value as u64),
+        JumpTableKind::Relative if reloc.is_none() => {
+            table_address.wrapping_add(value as i32 as i64 as u64)
+        }
+        JumpTableKind::Relative => return None,
+    };
+    code_range.contains(&dest).then_some((reloc, dest))
 }
 
 fn replace_arg(
@@ -195,6 +471,8 @@ struct InstructionFormatterOutput {
     ins: ObjIns,
     error: Option<anyhow::Error>,
     ins_operands: Vec<Option<u32>>,
+    /// Addresses of the function being formatted
+    code_range: Range<u64>,
 }
 
 impl InstructionFormatterOutput {
@@ -263,9 +541,12 @@ impl FormatterOutput for InstructionFormatterOutput {
         match kind {
             FormatterTextKind::LabelAddress => {
                 if let Some(reloc) = self.ins.reloc.as_ref() {
-                    if matches!(reloc.flags, RelocationFlags::Coff {
-                        typ: pe::IMAGE_REL_I386_DIR32 | pe::IMAGE_REL_I386_REL32
-                    }) {
+                    if matches!(
+                        reloc.flags,
+                        RelocationFlags::Coff {
+                            typ: pe::IMAGE_REL_I386_DIR32 | pe::IMAGE_REL_I386_REL32
+                        } | RelocationFlags::MachO { r_pcrel: true, .. }
+                    ) {
                         self.ins.args.push(ObjInsArg::Reloc);
                         return;
                     } else if self.error.is_none() {
@@ -276,14 +557,15 @@ impl FormatterOutput for InstructionFormatterOutput {
                     }
                 }
                 self.ins.args.push(ObjInsArg::BranchDest(value));
-                self.ins.branch_dest = Some(value);
                 return;
             }
             FormatterTextKind::FunctionAddress => {
                 if let Some(reloc) = self.ins.reloc.as_ref() {
-                    if matches!(reloc.flags, RelocationFlags::Coff {
-                        typ: pe::IMAGE_REL_I386_REL32
-                    }) {
+                    if matches!(
+                        reloc.flags,
+                        RelocationFlags::Coff { typ: pe::IMAGE_REL_I386_REL32 }
+                            | RelocationFlags::MachO { r_pcrel: true, .. }
+                    ) {
                         self.ins.args.push(ObjInsArg::Reloc);
                         return;
                     } else if self.error.is_none() {
@@ -293,6 +575,11 @@ impl FormatterOutput for InstructionFormatterOutput {
                         ));
                     }
                 }
+                // Calls within the function are compared by destination, others by address
+                if self.code_range.contains(&value) {
+                    self.ins.args.push(ObjInsArg::BranchDest(value));
+                    return;
+                }
             }
             _ => {}
         }
@@ -1,8 +1,9 @@
 #![allow(clippy::needless_lifetimes)] // Generated serde code
 use crate::{
     diff::{
-        ObjDataDiff, ObjDataDiffKind, ObjDiff, ObjInsArgDiff, ObjInsBranchFrom, ObjInsBranchTo,
-        ObjInsDiff, ObjInsDiffKind, ObjSectionDiff, ObjSymbolDiff,
+        ObjBasicBlock, ObjBlockDiffKind, ObjDataDiff, ObjDataDiffKind, ObjDataField,
+        ObjDataFieldDiff, ObjDiff, ObjInsArgDiff, ObjInsBranchFrom, ObjInsBranchTo, ObjInsDiff,
+        ObjInsDiffKind, ObjRegisterMapping, ObjSectionDiff, ObjSymbolDiff,
     },
     obj::{
         ObjInfo, ObjIns, ObjInsArg, ObjInsArgValue, ObjReloc, ObjSectionKind, ObjSymbol,
@@ -81,6 +82,53 @@ impl FunctionDiff {
             // diff_symbol,
             instructions,
             match_percent: symbol_diff.match_percent,
+            data_fields: symbol_diff.data_fields.iter().map(DataFieldDiff::new).collect(),
+            register_mapping: symbol_diff
+                .register_mapping
+                .iter()
+                .map(RegisterMapping::new)
+                .collect(),
+            blocks: symbol_diff.blocks.iter().map(BasicBlock::new).collect(),
+            score: symbol_diff.score,
+            approximate: symbol_diff.approximate,
+        }
+    }
+}
+
+impl RegisterMapping {
+    pub fn new(value: &ObjRegisterMapping) -> Self {
+        Self { left: value.left.clone(), right: value.right.clone() }
+    }
+}
+
+impl BasicBlock {
+    pub fn new(value: &ObjBasicBlock) -> Self {
+        Self {
+            start: value.start as u32,
+            end: value.end as u32,
+            successors: value.successors.iter().map(|&x| x as u32).collect(),
+            target_block: value.target_block.map(|x| x as u32),
+            diff_kind: DiffKind::from(value.kind) as i32,
+        }
+    }
+}
+
+impl DataFieldDiff {
+    pub fn new(value: &ObjDataFieldDiff) -> Self {
+        Self {
+            kind: DiffKind::from(value.kind) as i32,
+            field: value.field.as_ref().map(DataField::new),
+        }
+    }
+}
+
+impl DataField {
+    pub fn new(value: &ObjDataField) -> Self {
+        Self {
+            path: value.path.clone(),
+            offset: value.offset,
+            size: value.size,
+            value: value.value.clone(),
         }
     }
 }
@@ -212,6 +260,7 @@ impl From<ObjInsDiffKind> for DiffKind {
             ObjInsDiffKind::None => DiffKind::DiffNone,
             ObjInsDiffKind::OpMismatch => DiffKind::DiffOpMismatch,
             ObjInsDiffKind::ArgMismatch => DiffKind::DiffArgMismatch,
+            ObjInsDiffKind::RegisterRename => DiffKind::DiffRegisterRename,
             ObjInsDiffKind::Replace => DiffKind::DiffReplace,
             ObjInsDiffKind::Delete => DiffKind::DiffDelete,
             ObjInsDiffKind::Insert => DiffKind::DiffInsert,
@@ -230,6 +279,17 @@ impl From<ObjDataDiffKind> for DiffKind {
     }
 }
 
+impl From<ObjBlockDiffKind> for DiffKind {
+    fn from(value: ObjBlockDiffKind) -> Self {
+        match value {
+            ObjBlockDiffKind::None => DiffKind::DiffNone,
+            ObjBlockDiffKind::Mismatch => DiffKind::DiffReplace,
+            ObjBlockDiffKind::Delete => DiffKind::DiffDelete,
+            ObjBlockDiffKind::Insert => DiffKind::DiffInsert,
+        }
+    }
+}
+
 impl InstructionBranchFrom {
     pub fn new(value: &ObjInsBranchFrom) -> Self {
         Self {
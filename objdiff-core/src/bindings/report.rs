@@ -237,7 +237,7 @@ impl Measures {
         }
     }
 
-    /// Calculate the percentage of matched code, data, and functions.
+    /// Calculate the percentage of matched code, data, functions and data symbols.
     pub fn calc_matched_percent(&mut self) {
         self.matched_code_percent = if self.total_code == 0 {
             100.0
@@ -254,6 +254,11 @@ impl Measures {
         } else {
             self.matched_functions as f32 / self.total_functions as f32 * 100.0
         };
+        self.matched_data_symbols_percent = if self.total_data_symbols == 0 {
+            100.0
+        } else {
+            self.matched_data_symbols as f32 / self.total_data_symbols as f32 * 100.0
+        };
         self.complete_code_percent = if self.total_code == 0 {
             100.0
         } else {
@@ -286,6 +291,8 @@ impl AddAssign for Measures {
         self.complete_data += other.complete_data;
         self.total_units += other.total_units;
         self.complete_units += other.complete_units;
+        self.total_data_symbols += other.total_data_symbols;
+        self.matched_data_symbols += other.matched_data_symbols;
     }
 }
 
@@ -379,6 +386,7 @@ impl From<LegacyReportUnit> for ReportUnit {
             measures: Some(measures),
             sections: value.sections.into_iter().map(ReportItem::from).collect(),
             functions: value.functions.into_iter().map(ReportItem::from).collect(),
+            data: vec![],
             metadata: Some(ReportUnitMetadata {
                 complete: value.complete,
                 module_name: value.module_name.clone(),
@@ -0,0 +1,405 @@
+//! Edits of a unit's symbol mappings in the text of a project config file. Only the
+//! `symbol_mappings` entry of the unit is rewritten, leaving comments and formatting elsewhere in
+//! the file untouched.
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::config::SymbolMappings;
+
+/// A JSON value and its location in the text.
+enum JsonNode {
+    Object { start: usize, end: usize, members: Vec<JsonMember> },
+    Array { end: usize, items: Vec<JsonNode> },
+    String { end: usize, value: String },
+    Other { end: usize },
+}
+
+struct JsonMember {
+    key: String,
+    key_start: usize,
+    value_start: usize,
+    value: JsonNode,
+}
+
+impl JsonNode {
+    fn end(&self) -> usize {
+        match self {
+            JsonNode::Object { end, .. }
+            | JsonNode::Array { end, .. }
+            | JsonNode::String { end, .. }
+            | JsonNode::Other { end } => *end,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&JsonNode> {
+        match self {
+            JsonNode::Object { members, .. } => {
+                members.iter().find(|m| m.key == key).map(|m| &m.value)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parses JSON that is already known to be valid, recording where each value is.
+struct JsonParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn peek(&self) -> Option<u8> { self.text.as_bytes().get(self.pos).copied() }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<()> {
+        self.skip_ws();
+        if self.peek() != Some(c) {
+            bail!("Expected '{}' at offset {}", c as char, self.pos);
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<JsonNode> {
+        self.skip_ws();
+        let start = self.pos;
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.skip_ws();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        self.skip_ws();
+                        let key_start = self.pos;
+                        let key = self.string()?;
+                        self.expect(b':')?;
+                        self.skip_ws();
+                        let value_start = self.pos;
+                        let value = self.value()?;
+                        members.push(JsonMember { key, key_start, value_start, value });
+                        self.skip_ws();
+                        match self.peek() {
+                            Some(b',') => self.pos += 1,
+                            _ => break self.expect(b'}')?,
+                        }
+                    }
+                }
+                Ok(JsonNode::Object { start, end: self.pos, members })
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_ws();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        items.push(self.value()?);
+                        self.skip_ws();
+                        match self.peek() {
+                            Some(b',') => self.pos += 1,
+                            _ => break self.expect(b']')?,
+                        }
+                    }
+                }
+                Ok(JsonNode::Array { end: self.pos, items })
+            }
+            Some(b'"') => {
+                let value = self.string()?;
+                Ok(JsonNode::String { end: self.pos, value })
+            }
+            Some(_) => {
+                while !matches!(
+                    self.peek(),
+                    None | Some(b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r')
+                ) {
+                    self.pos += 1;
+                }
+                Ok(JsonNode::Other { end: self.pos })
+            }
+            None => bail!("Unexpected end of JSON"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        loop {
+            match self.peek() {
+                Some(b'\\') => self.pos += 2,
+                Some(b'"') => break,
+                Some(_) => self.pos += 1,
+                None => bail!("Unterminated string at offset {start}"),
+            }
+        }
+        self.pos += 1;
+        Ok(serde_json::from_str(&self.text[start..self.pos])?)
+    }
+}
+
+/// Whitespace before `pos` on its line, or `None` if there's anything else before it.
+fn line_indent(text: &str, pos: usize) -> Option<&str> {
+    let line_start = text[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &text[line_start..pos];
+    indent.chars().all(|c| c == ' ' || c == '\t').then_some(indent)
+}
+
+/// Replaces the symbol mappings of a unit in a JSON project config.
+pub(super) fn set_json_mappings(
+    text: &str,
+    unit_name: &str,
+    mappings: &SymbolMappings,
+) -> Result<String> {
+    // Report syntax errors with serde_json's messages
+    serde_json::from_str::<serde_json::Value>(text).context("Failed to parse JSON")?;
+    let root = JsonParser { text, pos: 0 }.value()?;
+    let units = match root.get("units") {
+        Some(units @ JsonNode::Array { .. }) => Some(units),
+        _ => root.get("objects"),
+    };
+    let Some(JsonNode::Array { items, .. }) = units else {
+        bail!("Project config has no units");
+    };
+    let (object_start, object_end, members) = items
+        .iter()
+        .find_map(|unit| {
+            let JsonNode::Object { start, end, members } = unit else {
+                return None;
+            };
+            let key = if unit.get("name").is_some() { "name" } else { "path" };
+            match unit.get(key) {
+                Some(JsonNode::String { value, .. }) if value == unit_name => {
+                    Some((*start, *end, members))
+                }
+                _ => None,
+            }
+        })
+        .ok_or_else(|| anyhow!("Unit {unit_name} not found in project config"))?;
+    let Some(last) = members.last() else {
+        bail!("Unit {unit_name} has no name or path");
+    };
+
+    // Format the mappings like the other members of the unit
+    let multiline = text[object_start..object_end].contains('\n');
+    let indent = line_indent(text, members[0].key_start).filter(|_| multiline).unwrap_or("");
+    let value = if multiline {
+        let object_indent = line_indent(text, object_start).unwrap_or("");
+        let step = indent.strip_prefix(object_indent).filter(|s| !s.is_empty()).unwrap_or("  ");
+        let mut buf = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(step.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buf, formatter);
+        serde::Serialize::serialize(mappings, &mut serializer).context("Failed to write JSON")?;
+        String::from_utf8(buf)?.replace('\n', &format!("\n{indent}"))
+    } else {
+        serde_json::to_string(mappings).context("Failed to write JSON")?
+    };
+
+    let mut out = text.to_string();
+    match members.iter().position(|m| m.key == "symbol_mappings") {
+        Some(idx) if !mappings.is_empty() => {
+            let member = &members[idx];
+            out.replace_range(member.value_start..member.value.end(), &value);
+        }
+        Some(idx) => {
+            // Remove the member along with the separator before it, or after it if it's first
+            let range = if idx > 0 {
+                members[idx - 1].value.end()..members[idx].value.end()
+            } else if let Some(next) = members.get(1) {
+                members[0].key_start..next.key_start
+            } else {
+                object_start + 1..object_end - 1
+            };
+            out.replace_range(range, "");
+        }
+        None if !mappings.is_empty() => {
+            let separator = if multiline { format!(",\n{indent}") } else { ", ".to_string() };
+            out.insert_str(last.value.end(), &format!("{separator}\"symbol_mappings\": {value}"));
+        }
+        None => {}
+    }
+    Ok(out)
+}
+
+/// A line of YAML text.
+struct YamlLine<'a> {
+    start: usize,
+    end: usize,
+    indent: usize,
+    /// The line without its indentation and line ending
+    content: &'a str,
+}
+
+impl YamlLine<'_> {
+    fn is_blank(&self) -> bool { self.content.is_empty() || self.content.starts_with('#') }
+
+    fn is_item(&self) -> bool { self.content == "-" || self.content.starts_with("- ") }
+}
+
+fn yaml_lines(text: &str) -> Vec<YamlLine<'_>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let trimmed = content.trim_start_matches(' ');
+        lines.push(YamlLine {
+            start,
+            end: start + line.len(),
+            indent: content.len() - trimmed.len(),
+            content: trimmed.trim_end(),
+        });
+        start += line.len();
+    }
+    lines
+}
+
+/// Finds the index of a unit by name (or path, for units without a name) in a YAML project
+/// config, along with the key of the units list.
+fn find_unit_yaml(text: &str, unit_name: &str) -> Result<(&'static str, usize)> {
+    let value: serde_yaml::Value = serde_yaml::from_str(text).context("Failed to parse YAML")?;
+    let key = if value.get("units").is_some_and(|v| v.is_sequence()) { "units" } else { "objects" };
+    let units = value
+        .get(key)
+        .and_then(|v| v.as_sequence())
+        .ok_or_else(|| anyhow!("Project config has no units"))?;
+    let idx = units
+        .iter()
+        .position(|u| {
+            let key = if u.get("name").is_some() { "name" } else { "path" };
+            u.get(key).and_then(|v| v.as_str()) == Some(unit_name)
+        })
+        .ok_or_else(|| anyhow!("Unit {unit_name} not found in project config"))?;
+    Ok((key, idx))
+}
+
+/// Replaces the symbol mappings of a unit in a YAML project config. The units must be written
+/// as a block sequence of block mappings.
+pub(super) fn set_yaml_mappings(
+    text: &str,
+    unit_name: &str,
+    mappings: &SymbolMappings,
+) -> Result<String> {
+    let (units_key, unit_idx) = find_unit_yaml(text, unit_name)?;
+    let unsupported = || anyhow!("Unsupported formatting of {units_key} in YAML project config");
+    let lines = yaml_lines(text);
+    let key_line = lines
+        .iter()
+        .position(|l| {
+            l.indent == 0
+                && l.content.strip_prefix(units_key).and_then(|s| s.strip_prefix(':')).is_some()
+        })
+        .ok_or_else(unsupported)?;
+    let rest = &lines[key_line].content[units_key.len() + 1..];
+    if !(rest.trim().is_empty() || rest.trim_start().starts_with('#')) {
+        return Err(unsupported());
+    }
+
+    // Find the lines of each item in the sequence
+    let mut items = Vec::<(usize, usize)>::new();
+    let mut item_indent = None;
+    let mut end = lines.len();
+    for (i, line) in lines.iter().enumerate().skip(key_line + 1) {
+        if line.is_blank() {
+            continue;
+        }
+        let indent = *item_indent.get_or_insert(line.indent);
+        if line.indent < indent || (line.indent == indent && !line.is_item()) {
+            end = i;
+            break;
+        }
+        if line.indent == indent {
+            if let Some(last) = items.last_mut() {
+                last.1 = i;
+            }
+            items.push((i, end));
+        }
+    }
+    if let Some(last) = items.last_mut() {
+        last.1 = end;
+    }
+    let (item_start, mut item_end) = *items.get(unit_idx).ok_or_else(unsupported)?;
+    // Comments and blank lines after the item are left in place
+    while item_end > item_start + 1 && lines[item_end - 1].is_blank() {
+        item_end -= 1;
+    }
+
+    // Keys of the unit's mapping, as line indices and text offsets
+    let dash_line = &lines[item_start];
+    let after_dash = dash_line.content[1..].trim_start();
+    let key_indent = if !after_dash.is_empty() {
+        dash_line.indent + dash_line.content.len() - after_dash.len()
+    } else {
+        lines[item_start + 1..item_end]
+            .iter()
+            .find(|l| !l.is_blank())
+            .map(|l| l.indent)
+            .ok_or_else(unsupported)?
+    };
+    let mut keys = Vec::<(usize, usize)>::new();
+    for (i, line) in lines.iter().enumerate().take(item_end).skip(item_start) {
+        if i == item_start {
+            if !after_dash.is_empty() {
+                keys.push((i, line.start + key_indent));
+            }
+        } else if !line.is_blank() && line.indent == key_indent {
+            keys.push((i, line.start + key_indent));
+        }
+    }
+    let is_mappings_key = |&(i, offset): &(usize, usize)| {
+        let key = &text[offset..lines[i].end];
+        key.strip_prefix("symbol_mappings").is_some_and(|s| s.trim_start().starts_with(':'))
+    };
+
+    let indent = " ".repeat(key_indent + 2);
+    let mut value = String::from("symbol_mappings:\n");
+    for line in serde_yaml::to_string(mappings).context("Failed to write YAML")?.lines() {
+        value.push_str(&indent);
+        value.push_str(line);
+        value.push('\n');
+    }
+
+    let mut out = text.to_string();
+    match keys.iter().position(is_mappings_key) {
+        Some(k) => {
+            let (line, offset) = keys[k];
+            // The value ends at the last line before the next key, not counting comments
+            let mut block_end = keys.get(k + 1).map_or(item_end, |&(i, _)| i);
+            while block_end > line + 1 && lines[block_end - 1].is_blank() {
+                block_end -= 1;
+            }
+            let end = lines[block_end - 1].end;
+            if !text[..end].ends_with('\n') {
+                value.pop();
+            }
+            if !mappings.is_empty() {
+                out.replace_range(offset..end, &value);
+            } else if line == item_start {
+                // Move the next key up to the item's first line
+                match keys.get(k + 1) {
+                    Some(&(_, next_offset)) => out.replace_range(offset..next_offset, ""),
+                    None => out.replace_range(offset..end, "{}\n"),
+                }
+            } else {
+                out.replace_range(lines[line].start..end, "");
+            }
+        }
+        None if !mappings.is_empty() => {
+            let end = lines[item_end - 1].end;
+            if text[..end].ends_with('\n') {
+                out.insert_str(end, &format!("{}{value}", " ".repeat(key_indent)));
+            } else {
+                value.pop();
+                out.insert_str(end, &format!("\n{}{value}", " ".repeat(key_indent)));
+            }
+        }
+        None => {}
+    }
+    Ok(out)
+}
@@ -10,6 +10,10 @@ use bimap::BiBTreeMap;
 use filetime::FileTime;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 
+use crate::diff::DiffScoreWeights;
+
+mod edit;
+
 #[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ProjectConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -32,6 +36,11 @@ pub struct ProjectConfig {
     pub units: Option<Vec<ProjectObject>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub progress_categories: Option<Vec<ProjectProgressCategory>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linker_map: Option<ProjectLinkerMap>,
+    /// Penalties used to score function diffs, overriding the defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_weights: Option<DiffScoreWeights>,
 }
 
 impl ProjectConfig {
@@ -65,6 +74,8 @@ pub struct ProjectObject {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub base_path: Option<PathBuf>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_region: Option<TargetRegion>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[deprecated(note = "Use metadata.reverse_fn_order")]
     pub reverse_fn_order: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -80,6 +91,85 @@ pub struct ProjectObject {
 
 pub type SymbolMappings = BiBTreeMap<String, String>;
 
+/// Selects a region of a linked executable to use as the target object, for units without a
+/// split target object. At least one of the address range or the symbol map must be provided.
+#[derive(Debug, Default, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TargetRegion {
+    /// Start address of the region (inclusive)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_address",
+        serialize_with = "serialize_address"
+    )]
+    pub start: Option<u64>,
+    /// End address of the region (exclusive)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_address",
+        serialize_with = "serialize_address"
+    )]
+    pub end: Option<u64>,
+    /// Path to a linker map or symbol map for the executable. If not specified, symbols are
+    /// taken from the executable's symbol table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbols: Option<PathBuf>,
+    /// Unit in the linker map to take symbols from, as written in the map. If not specified,
+    /// all symbols in the map are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// A linker map for the whole project, used to name and locate symbols in the target.
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProjectLinkerMap {
+    /// Path to the linker map from the project root
+    pub path: PathBuf,
+    /// Path to the linked executable from the project root. If specified, a unit is added for
+    /// each object in the map that isn't listed in `units`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_path: Option<PathBuf>,
+}
+
+/// Parses an address written either as a number or as a hexadecimal string (`"0x80003100"`).
+pub fn parse_address(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let result = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse::<u64>(),
+    };
+    result.with_context(|| format!("Invalid address: {s}"))
+}
+
+fn deserialize_address<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
+where D: serde::Deserializer<'de> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Address {
+        Number(u64),
+        String(String),
+    }
+    match <Option<Address> as serde::Deserialize>::deserialize(deserializer)? {
+        Some(Address::Number(address)) => Ok(Some(address)),
+        Some(Address::String(s)) => parse_address(&s).map(Some).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+fn serialize_address<S>(
+    address: &Option<u64>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match address {
+        Some(address) => serializer.serialize_str(&format!("{address:#x}")),
+        None => serializer.serialize_none(),
+    }
+}
+
 #[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ProjectObjectMetadata {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -132,6 +222,9 @@ impl ProjectObject {
         } else if let Some(path) = &self.base_path {
             self.base_path = Some(project_dir.join(path));
         }
+        if let Some(path) = self.target_region.as_mut().and_then(|r| r.symbols.as_mut()) {
+            *path = project_dir.join(&*path);
+        }
     }
 
     pub fn complete(&self) -> Option<bool> {
@@ -215,10 +308,8 @@ pub fn try_project_config(dir: &Path) -> Option<(Result<ProjectConfig>, ProjectC
     None
 }
 
-pub fn save_project_config(
-    config: &ProjectConfig,
-    info: &ProjectConfigInfo,
-) -> Result<ProjectConfigInfo> {
+/// Fails if the config file has changed since it was read.
+fn check_config_unchanged(info: &ProjectConfigInfo) -> Result<()> {
     if let Some(last_ts) = info.timestamp {
         // Check if the file has changed since we last read it
         if let Ok(metadata) = fs::metadata(&info.path) {
@@ -228,6 +319,14 @@ pub fn save_project_config(
             }
         }
     }
+    Ok(())
+}
+
+pub fn save_project_config(
+    config: &ProjectConfig,
+    info: &ProjectConfigInfo,
+) -> Result<ProjectConfigInfo> {
+    check_config_unchanged(info)?;
     let mut writer =
         BufWriter::new(File::create(&info.path).context("Failed to create config file")?);
     let ext = info.path.extension().and_then(|ext| ext.to_str()).unwrap_or("json");
@@ -244,6 +343,30 @@ pub fn save_project_config(
     Ok(ProjectConfigInfo { path: info.path.clone(), timestamp: Some(ts) })
 }
 
+/// Replaces the symbol mappings of a unit in the project config file. Unlike
+/// [`save_project_config`], only the unit's `symbol_mappings` entry is rewritten, so comments and
+/// formatting elsewhere in the file are kept.
+pub fn save_unit_symbol_mappings(
+    info: &ProjectConfigInfo,
+    unit_name: &str,
+    mappings: &SymbolMappings,
+) -> Result<ProjectConfigInfo> {
+    check_config_unchanged(info)?;
+    let text = fs::read_to_string(&info.path)
+        .with_context(|| format!("Failed to read {}", info.path.display()))?;
+    let ext = info.path.extension().and_then(|ext| ext.to_str()).unwrap_or("json");
+    let out = match ext {
+        "json" => edit::set_json_mappings(&text, unit_name, mappings)?,
+        "yml" | "yaml" => edit::set_yaml_mappings(&text, unit_name, mappings)?,
+        _ => return Err(anyhow!("Unknown config file extension: {ext}")),
+    };
+    fs::write(&info.path, out)
+        .with_context(|| format!("Failed to write {}", info.path.display()))?;
+    let metadata = fs::metadata(&info.path).context("Failed to get file metadata")?;
+    let ts = FileTime::from_last_modification_time(&metadata);
+    Ok(ProjectConfigInfo { path: info.path.clone(), timestamp: Some(ts) })
+}
+
 fn validate_min_version(config: &ProjectConfig) -> Result<()> {
     let Some(min_version) = &config.min_version else { return Ok(()) };
     let version = semver::Version::parse(env!("CARGO_PKG_VERSION"))
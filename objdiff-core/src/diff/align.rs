@@ -0,0 +1,211 @@
+use std::{cmp::max, time::Instant};
+
+use similar::{capture_diff_slices_deadline, Algorithm, DiffOp};
+
+use crate::{
+    arch::ProcessCodeResult,
+    diff::{DiffAlgorithm, DiffObjConfig},
+    obj::{ObjIns, ObjInsArg},
+};
+
+/// Maximum number of cells in the operand-aware aligner's table (4 MiB), beyond which it falls
+/// back to patience alignment.
+const MAX_LCS_CELLS: usize = 1 << 20;
+
+/// Aligns the instructions of two functions with the configured algorithm. Also returns whether
+/// the alignment is approximate, because it exceeded the time budget or the function was too
+/// large for the selected algorithm.
+pub fn align_instructions(
+    left: &ProcessCodeResult,
+    right: &ProcessCodeResult,
+    config: &DiffObjConfig,
+) -> (Vec<DiffOp>, bool) {
+    let deadline = deadline(config);
+    match config.diff_algorithm {
+        DiffAlgorithm::Patience => align_ops(Algorithm::Patience, left, right, deadline),
+        DiffAlgorithm::Myers => align_ops(Algorithm::Myers, left, right, deadline),
+        DiffAlgorithm::Operand => align_operands(left, right, deadline),
+    }
+}
+
+/// The time after which alignment is approximate. `Instant::now` panics on wasm32, so the time
+/// budget is ignored there.
+#[cfg(not(target_arch = "wasm32"))]
+fn deadline(config: &DiffObjConfig) -> Option<Instant> {
+    config
+        .diff_time_budget_ms
+        .map(|ms| Instant::now() + std::time::Duration::from_millis(ms as u64))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn deadline(_config: &DiffObjConfig) -> Option<Instant> { None }
+
+fn deadline_passed(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// Aligns instructions by opcode. Once the deadline has passed, the remaining ranges are
+/// compared without searching for the best alignment.
+fn align_ops(
+    algorithm: Algorithm,
+    left: &ProcessCodeResult,
+    right: &ProcessCodeResult,
+    deadline: Option<Instant>,
+) -> (Vec<DiffOp>, bool) {
+    let ops = capture_diff_slices_deadline(algorithm, &left.ops, &right.ops, deadline);
+    (ops, deadline_passed(deadline))
+}
+
+/// Whether two instructions with the same opcode have the same arguments. Branch destinations
+/// are ignored, as they depend on the alignment itself.
+fn args_eq(left: &ObjIns, right: &ObjIns) -> bool {
+    left.iter_args().count() == right.iter_args().count()
+        && left.iter_args().zip(right.iter_args()).all(|(a, b)| match (a, b) {
+            (ObjInsArg::Arg(a), ObjInsArg::Arg(b)) => a.loose_eq(b),
+            (ObjInsArg::Reloc, ObjInsArg::Reloc) => {
+                left.reloc.as_ref().map(|r| &r.target.name)
+                    == right.reloc.as_ref().map(|r| &r.target.name)
+            }
+            (ObjInsArg::BranchDest(_), ObjInsArg::BranchDest(_)) => true,
+            _ => false,
+        })
+}
+
+/// How much aligning two instructions is worth: instructions with the same opcode can be
+/// aligned, preferably those that also have the same arguments.
+fn match_weight(left: &ObjIns, right: &ObjIns) -> u32 {
+    if left.op != right.op {
+        0
+    } else if args_eq(left, right) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Aligns instructions by the longest common subsequence of opcodes, preferring to align
+/// instructions that also have the same arguments.
+fn align_operands(
+    left: &ProcessCodeResult,
+    right: &ProcessCodeResult,
+    deadline: Option<Instant>,
+) -> (Vec<DiffOp>, bool) {
+    let (left_insts, right_insts) = (&left.insts, &right.insts);
+    // Identical instructions at either end are always aligned
+    let identical = |(a, b): &(&ObjIns, &ObjIns)| match_weight(a, b) == 2;
+    let prefix = left_insts.iter().zip(right_insts).take_while(identical).count();
+    let suffix = left_insts[prefix..]
+        .iter()
+        .rev()
+        .zip(right_insts[prefix..].iter().rev())
+        .take_while(identical)
+        .count();
+    let left_mid = &left_insts[prefix..left_insts.len() - suffix];
+    let right_mid = &right_insts[prefix..right_insts.len() - suffix];
+    let (n, m) = (left_mid.len(), right_mid.len());
+    let width = m + 1;
+    if (n + 1).saturating_mul(width) > MAX_LCS_CELLS {
+        let (ops, _) = align_ops(Algorithm::Patience, left, right, deadline);
+        return (ops, true);
+    }
+
+    // Best total weight of aligning each pair of prefixes
+    let mut weights = vec![0u32; (n + 1) * width];
+    for i in 1..=n {
+        if deadline_passed(deadline) {
+            let (ops, _) = align_ops(Algorithm::Patience, left, right, deadline);
+            return (ops, true);
+        }
+        for j in 1..=m {
+            let mut best = max(weights[(i - 1) * width + j], weights[i * width + j - 1]);
+            let weight = match_weight(&left_mid[i - 1], &right_mid[j - 1]);
+            if weight > 0 {
+                best = max(best, weights[(i - 1) * width + j - 1] + weight);
+            }
+            weights[i * width + j] = best;
+        }
+    }
+
+    // Walk back through the table, recording whether each step aligns both instructions or
+    // only takes one from the left (true) or right (false). Unaligned instructions are taken
+    // first, so that earlier instructions are aligned when there are several choices.
+    let mut steps = Vec::<Option<bool>>::with_capacity(n + m);
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        let weight = weights[i * width + j];
+        if i > 0 && weight == weights[(i - 1) * width + j] {
+            steps.push(Some(true));
+            i -= 1;
+        } else if j > 0 && weight == weights[i * width + j - 1] {
+            steps.push(Some(false));
+            j -= 1;
+        } else {
+            steps.push(None);
+            i -= 1;
+            j -= 1;
+        }
+    }
+
+    let mut ops = OpsBuilder::default();
+    ops.equal(prefix);
+    for step in steps.into_iter().rev() {
+        match step {
+            None => ops.equal(1),
+            Some(true) => ops.deleted += 1,
+            Some(false) => ops.inserted += 1,
+        }
+    }
+    ops.equal(suffix);
+    (ops.finish(), false)
+}
+
+/// Builds diff ops from aligned instructions, combining unaligned instructions between aligned
+/// ones into replacements.
+#[derive(Default)]
+struct OpsBuilder {
+    ops: Vec<DiffOp>,
+    old_index: usize,
+    new_index: usize,
+    deleted: usize,
+    inserted: usize,
+}
+
+impl OpsBuilder {
+    fn equal(&mut self, len: usize) {
+        self.flush();
+        if len == 0 {
+            return;
+        }
+        if let Some(DiffOp::Equal { len: last_len, .. }) = self.ops.last_mut() {
+            *last_len += len;
+        } else {
+            self.ops.push(DiffOp::Equal {
+                old_index: self.old_index,
+                new_index: self.new_index,
+                len,
+            });
+        }
+        self.old_index += len;
+        self.new_index += len;
+    }
+
+    fn flush(&mut self) {
+        let (old_index, new_index) = (self.old_index, self.new_index);
+        let op = match (self.deleted, self.inserted) {
+            (0, 0) => return,
+            (old_len, 0) => DiffOp::Delete { old_index, old_len, new_index },
+            (0, new_len) => DiffOp::Insert { old_index, new_index, new_len },
+            (old_len, new_len) => DiffOp::Replace { old_index, old_len, new_index, new_len },
+        };
+        self.ops.push(op);
+        self.old_index += self.deleted;
+        self.new_index += self.inserted;
+        self.deleted = 0;
+        self.inserted = 0;
+    }
+
+    fn finish(mut self) -> Vec<DiffOp> {
+        self.flush();
+        self.ops
+    }
+}
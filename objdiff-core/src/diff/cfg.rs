@@ -0,0 +1,144 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    arch::ObjArch,
+    diff::{ObjBasicBlock, ObjBlockDiffKind, ObjInsDiff, ObjInsDiffKind},
+};
+
+/// Splits a symbol's instruction diff into basic blocks.
+///
+/// Block boundaries are derived from the branches resolved by `resolve_branches`, so only
+/// branches within the function are considered. Padding rows (without an instruction)
+/// belong to the block they appear in.
+pub fn basic_blocks(arch: &dyn ObjArch, instructions: &[ObjInsDiff]) -> Vec<ObjBasicBlock> {
+    if instructions.is_empty() {
+        return vec![];
+    }
+    let delay_slots = arch.branch_delay_slots();
+
+    // Find block leaders
+    let mut leaders = BTreeSet::<usize>::new();
+    leaders.insert(0);
+    let mut remaining_slots: Option<usize> = None;
+    for (i, ins_diff) in instructions.iter().enumerate() {
+        let Some(ins) = &ins_diff.ins else {
+            continue;
+        };
+        if ins_diff.branch_from.is_some() {
+            leaders.insert(i);
+        }
+        if let Some(n) = remaining_slots {
+            remaining_slots = Some(n - 1);
+        } else if ins_diff.branch_to.is_some() || arch.is_unconditional_branch(ins) {
+            remaining_slots = Some(delay_slots);
+        }
+        if remaining_slots == Some(0) {
+            if i + 1 < instructions.len() {
+                leaders.insert(i + 1);
+            }
+            remaining_slots = None;
+        }
+    }
+
+    let mut blocks = Vec::with_capacity(leaders.len());
+    let mut block_of_row = vec![0usize; instructions.len()];
+    let starts = leaders.iter().copied().collect::<Vec<_>>();
+    for (block_idx, &start) in starts.iter().enumerate() {
+        let end = starts.get(block_idx + 1).copied().unwrap_or(instructions.len());
+        block_of_row[start..end].fill(block_idx);
+        blocks.push(ObjBasicBlock { start, end, ..Default::default() });
+    }
+
+    // Resolve successors from the last branch in each block
+    let block_count = blocks.len();
+    for (block_idx, block) in blocks.iter_mut().enumerate() {
+        let branch = instructions[block.start..block.end].iter().rev().find(|i| {
+            i.ins
+                .as_ref()
+                .is_some_and(|ins| i.branch_to.is_some() || arch.is_unconditional_branch(ins))
+        });
+        let mut falls_through = true;
+        if let Some(branch) = branch {
+            if let Some(branch_to) = &branch.branch_to {
+                block.successors.push(block_of_row[branch_to.ins_idx]);
+            }
+            falls_through =
+                !branch.ins.as_ref().is_some_and(|ins| arch.is_unconditional_branch(ins));
+        }
+        if falls_through
+            && block_idx + 1 < block_count
+            && !block.successors.contains(&(block_idx + 1))
+        {
+            block.successors.push(block_idx + 1);
+        }
+    }
+    blocks
+}
+
+/// Pairs up blocks between the left and right symbol diffs and sets their diff kinds.
+///
+/// Blocks are matched when they share the most aligned instructions with each other.
+pub fn match_blocks(
+    left_blocks: &mut [ObjBasicBlock],
+    right_blocks: &mut [ObjBasicBlock],
+    left_diff: &[ObjInsDiff],
+    right_diff: &[ObjInsDiff],
+) {
+    let block_of_row = |blocks: &[ObjBasicBlock]| {
+        let mut rows = vec![0usize; blocks.last().map_or(0, |b| b.end)];
+        for (block_idx, block) in blocks.iter().enumerate() {
+            rows[block.start..block.end].fill(block_idx);
+        }
+        rows
+    };
+    let left_rows = block_of_row(left_blocks);
+    let right_rows = block_of_row(right_blocks);
+
+    // Count aligned instructions shared by each pair of blocks
+    let mut overlap = BTreeMap::<(usize, usize), usize>::new();
+    for (row, (left, right)) in left_diff.iter().zip(right_diff).enumerate() {
+        if left.ins.is_some() && right.ins.is_some() {
+            *overlap.entry((left_rows[row], right_rows[row])).or_default() += 1;
+        }
+    }
+    let mut best_right = vec![None::<(usize, usize)>; left_blocks.len()];
+    let mut best_left = vec![None::<(usize, usize)>; right_blocks.len()];
+    for (&(left_idx, right_idx), &count) in &overlap {
+        if best_right[left_idx].map_or(true, |(_, c)| count > c) {
+            best_right[left_idx] = Some((right_idx, count));
+        }
+        if best_left[right_idx].map_or(true, |(_, c)| count > c) {
+            best_left[right_idx] = Some((left_idx, count));
+        }
+    }
+
+    for block in right_blocks.iter_mut() {
+        block.target_block = None;
+        block.kind = ObjBlockDiffKind::Insert;
+    }
+    for (left_idx, left_block) in left_blocks.iter_mut().enumerate() {
+        let target = best_right[left_idx]
+            .map(|(right_idx, _)| right_idx)
+            .filter(|&right_idx| best_left[right_idx].map(|(l, _)| l) == Some(left_idx));
+        let Some(right_idx) = target else {
+            left_block.target_block = None;
+            left_block.kind = ObjBlockDiffKind::Delete;
+            continue;
+        };
+        let right_block = &mut right_blocks[right_idx];
+        let kind = if block_matches(left_block, left_diff) && block_matches(right_block, right_diff)
+        {
+            ObjBlockDiffKind::None
+        } else {
+            ObjBlockDiffKind::Mismatch
+        };
+        left_block.target_block = Some(right_idx);
+        left_block.kind = kind;
+        right_block.target_block = Some(left_idx);
+        right_block.kind = kind;
+    }
+}
+
+fn block_matches(block: &ObjBasicBlock, diff: &[ObjInsDiff]) -> bool {
+    diff[block.start..block.end].iter().all(|i| i.ins.is_some() && i.kind == ObjInsDiffKind::None)
+}
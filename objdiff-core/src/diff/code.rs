@@ -1,15 +1,22 @@
-use std::{cmp::max, collections::BTreeMap};
+use std::{
+    cmp::max,
+    collections::{BTreeMap, HashMap},
+};
 
 use anyhow::{anyhow, Result};
-use similar::{capture_diff_slices_deadline, Algorithm};
 
 use crate::{
-    arch::ProcessCodeResult,
+    arch::{ObjArch, ProcessCodeResult},
     diff::{
+        align::align_instructions,
+        cfg::{basic_blocks, match_blocks},
         DiffObjConfig, ObjInsArgDiff, ObjInsBranchFrom, ObjInsBranchTo, ObjInsDiff, ObjInsDiffKind,
-        ObjSymbolDiff,
+        ObjRegisterMapping, ObjSymbolDiff,
+    },
+    obj::{
+        ObjInfo, ObjInsArg, ObjInsArgValue, ObjReloc, ObjSection, ObjSymbol, ObjSymbolFlags,
+        SymbolRef,
     },
-    obj::{ObjInfo, ObjInsArg, ObjReloc, ObjSection, ObjSymbol, ObjSymbolFlags, SymbolRef},
 };
 
 pub fn process_code_symbol(
@@ -47,7 +54,11 @@ pub fn process_code_symbol(
     Ok(res)
 }
 
-pub fn no_diff_code(out: &ProcessCodeResult, symbol_ref: SymbolRef) -> Result<ObjSymbolDiff> {
+pub fn no_diff_code(
+    obj: &ObjInfo,
+    out: &ProcessCodeResult,
+    symbol_ref: SymbolRef,
+) -> Result<ObjSymbolDiff> {
     let mut diff = Vec::<ObjInsDiff>::new();
     for i in &out.insts {
         diff.push(ObjInsDiff {
@@ -57,7 +68,18 @@ pub fn no_diff_code(out: &ProcessCodeResult, symbol_ref: SymbolRef) -> Result<Ob
         });
     }
     resolve_branches(&mut diff);
-    Ok(ObjSymbolDiff { symbol_ref, target_symbol: None, instructions: diff, match_percent: None })
+    let blocks = basic_blocks(obj.arch.as_ref(), &diff);
+    Ok(ObjSymbolDiff {
+        symbol_ref,
+        target_symbol: None,
+        instructions: diff,
+        data_fields: vec![],
+        register_mapping: vec![],
+        blocks,
+        match_percent: None,
+        score: None,
+        approximate: false,
+    })
 }
 
 pub fn diff_code(
@@ -71,12 +93,21 @@ pub fn diff_code(
 ) -> Result<(ObjSymbolDiff, ObjSymbolDiff)> {
     let mut left_diff = Vec::<ObjInsDiff>::new();
     let mut right_diff = Vec::<ObjInsDiff>::new();
-    diff_instructions(&mut left_diff, &mut right_diff, left_out, right_out)?;
+    let approximate =
+        diff_instructions(&mut left_diff, &mut right_diff, left_out, right_out, config)?;
 
     resolve_branches(&mut left_diff);
     resolve_branches(&mut right_diff);
 
     let mut diff_state = InsDiffState::default();
+    if config.register_renaming {
+        diff_state.registers = Some(RegisterMapping::new(
+            left_obj.arch.as_ref(),
+            right_obj.arch.as_ref(),
+            &left_diff,
+            &right_diff,
+        ));
+    }
     for (left, right) in left_diff.iter_mut().zip(right_diff.iter_mut()) {
         let result = compare_ins(config, left_obj, right_obj, left, right, &mut diff_state)?;
         left.kind = result.kind;
@@ -85,25 +116,38 @@ pub fn diff_code(
         right.arg_diff = result.right_args_diff;
     }
 
+    let score = config.score_weights.score(left_diff.iter().map(|ins| ins.kind));
     let total = left_out.insts.len().max(right_out.insts.len());
-    let percent = if diff_state.diff_count >= total {
-        0.0
-    } else {
-        ((total - diff_state.diff_count) as f32 / total as f32) * 100.0
-    };
+    let percent = config.score_weights.match_percent(score, total);
+    let register_mapping =
+        diff_state.registers.as_ref().map(|r| r.renamed_registers()).unwrap_or_default();
+
+    let mut left_blocks = basic_blocks(left_obj.arch.as_ref(), &left_diff);
+    let mut right_blocks = basic_blocks(right_obj.arch.as_ref(), &right_diff);
+    match_blocks(&mut left_blocks, &mut right_blocks, &left_diff, &right_diff);
 
     Ok((
         ObjSymbolDiff {
             symbol_ref: left_symbol_ref,
             target_symbol: Some(right_symbol_ref),
             instructions: left_diff,
+            data_fields: vec![],
+            register_mapping: register_mapping.clone(),
+            blocks: left_blocks,
             match_percent: Some(percent),
+            score: Some(score),
+            approximate,
         },
         ObjSymbolDiff {
             symbol_ref: right_symbol_ref,
             target_symbol: Some(left_symbol_ref),
             instructions: right_diff,
+            data_fields: vec![],
+            register_mapping,
+            blocks: right_blocks,
             match_percent: Some(percent),
+            score: Some(score),
+            approximate,
         },
     ))
 }
@@ -113,9 +157,9 @@ fn diff_instructions(
     right_diff: &mut Vec<ObjInsDiff>,
     left_code: &ProcessCodeResult,
     right_code: &ProcessCodeResult,
-) -> Result<()> {
-    let ops =
-        capture_diff_slices_deadline(Algorithm::Patience, &left_code.ops, &right_code.ops, None);
+    config: &DiffObjConfig,
+) -> Result<bool> {
+    let (ops, approximate) = align_instructions(left_code, right_code, config);
     if ops.is_empty() {
         left_diff.extend(
             left_code
@@ -129,7 +173,7 @@ fn diff_instructions(
                 .iter()
                 .map(|i| ObjInsDiff { ins: Some(i.clone()), ..Default::default() }),
         );
-        return Ok(());
+        return Ok(approximate);
     }
 
     for op in ops {
@@ -153,7 +197,7 @@ fn diff_instructions(
         }
     }
 
-    Ok(())
+    Ok(approximate)
 }
 
 fn resolve_branches(vec: &mut [ObjInsDiff]) {
@@ -211,7 +255,7 @@ fn section_name_eq(
     left_section.name == right_section.name
 }
 
-fn reloc_eq(
+pub(crate) fn reloc_eq(
     config: &DiffObjConfig,
     left_obj: &ObjInfo,
     right_obj: &ObjInfo,
@@ -289,9 +333,121 @@ fn arg_eq(
     }
 }
 
+/// A one-to-one mapping between the registers used by two functions.
+struct RegisterMapping<'a> {
+    left_arch: &'a dyn ObjArch,
+    right_arch: &'a dyn ObjArch,
+    /// Left DWARF register number to right DWARF register number
+    mapping: BTreeMap<u16, u16>,
+    /// Right DWARF register number to left DWARF register number
+    reverse: BTreeMap<u16, u16>,
+    /// DWARF register numbers of the register names used by each function
+    left_registers: HashMap<String, Option<u16>>,
+    right_registers: HashMap<String, Option<u16>>,
+}
+
+impl<'a> RegisterMapping<'a> {
+    /// Builds the mapping from register pairs used in the same position by aligned
+    /// instructions. The most frequent pairs are assigned first, and each register can
+    /// only be mapped once.
+    fn new(
+        left_arch: &'a dyn ObjArch,
+        right_arch: &'a dyn ObjArch,
+        left_diff: &[ObjInsDiff],
+        right_diff: &[ObjInsDiff],
+    ) -> Self {
+        let mut left_registers = HashMap::<String, Option<u16>>::new();
+        let mut right_registers = HashMap::<String, Option<u16>>::new();
+        let mut counts = BTreeMap::<(u16, u16), usize>::new();
+        for (left, right) in left_diff.iter().zip(right_diff) {
+            let (Some(left_ins), Some(right_ins)) = (&left.ins, &right.ins) else {
+                continue;
+            };
+            if left_ins.op != right_ins.op
+                || left_ins.iter_args().count() != right_ins.iter_args().count()
+            {
+                continue;
+            }
+            for (a, b) in left_ins.iter_args().zip(right_ins.iter_args()) {
+                let (
+                    ObjInsArg::Arg(ObjInsArgValue::Opaque(a)),
+                    ObjInsArg::Arg(ObjInsArgValue::Opaque(b)),
+                ) = (a, b)
+                else {
+                    continue;
+                };
+                let a = *left_registers
+                    .entry(a.to_string())
+                    .or_insert_with(|| left_arch.dwarf_register(a));
+                let b = *right_registers
+                    .entry(b.to_string())
+                    .or_insert_with(|| right_arch.dwarf_register(b));
+                if let (Some(a), Some(b)) = (a, b) {
+                    *counts.entry((a, b)).or_default() += 1;
+                }
+            }
+        }
+        let mut counts = counts.into_iter().collect::<Vec<_>>();
+        // Stable sort keeps register order for equal counts
+        counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+        let mut mapping = BTreeMap::new();
+        let mut reverse = BTreeMap::new();
+        for ((left, right), _) in counts {
+            if mapping.contains_key(&left) || reverse.contains_key(&right) {
+                continue;
+            }
+            mapping.insert(left, right);
+            reverse.insert(right, left);
+        }
+        Self { left_arch, right_arch, mapping, reverse, left_registers, right_registers }
+    }
+
+    /// Whether two arguments are registers that correspond under the mapping, in both
+    /// directions. Registers that aren't mapped only correspond to themselves. Returns `None`
+    /// if either argument isn't a register.
+    fn registers_match(&self, left: &ObjInsArg, right: &ObjInsArg) -> Option<bool> {
+        let (
+            ObjInsArg::Arg(ObjInsArgValue::Opaque(left)),
+            ObjInsArg::Arg(ObjInsArgValue::Opaque(right)),
+        ) = (left, right)
+        else {
+            return None;
+        };
+        let left = match self.left_registers.get(left.as_ref()) {
+            Some(register) => (*register)?,
+            None => self.left_arch.dwarf_register(left)?,
+        };
+        let right = match self.right_registers.get(right.as_ref()) {
+            Some(register) => (*register)?,
+            None => self.right_arch.dwarf_register(right)?,
+        };
+        Some(match (self.mapping.get(&left), self.reverse.get(&right)) {
+            (None, None) => left == right,
+            (mapped, reverse) => mapped == Some(&right) && reverse == Some(&left),
+        })
+    }
+
+    /// Registers that were mapped to a different register, by their preferred names.
+    fn renamed_registers(&self) -> Vec<ObjRegisterMapping> {
+        let name = |arch: &dyn ObjArch, register: u16| {
+            arch.dwarf_register_names(register)
+                .first()
+                .map_or_else(|| format!("dwarf_reg{register}"), |name| name.to_string())
+        };
+        self.mapping
+            .iter()
+            .filter(|(left, right)| left != right)
+            .map(|(&left, &right)| ObjRegisterMapping {
+                left: name(self.left_arch, left),
+                right: name(self.right_arch, right),
+            })
+            .collect()
+    }
+}
+
 #[derive(Default)]
-struct InsDiffState {
-    diff_count: usize,
+struct InsDiffState<'a> {
+    registers: Option<RegisterMapping<'a>>,
     left_arg_idx: usize,
     right_arg_idx: usize,
     left_args_idx: BTreeMap<String, usize>,
@@ -313,6 +469,7 @@ fn compare_ins(
     right: &ObjInsDiff,
     state: &mut InsDiffState,
 ) -> Result<InsDiffResult> {
+    let mut renamed = false;
     let mut result = InsDiffResult::default();
     if let (Some(left_ins), Some(right_ins)) = (&left.ins, &right.ins) {
         // Count only non-PlainText args
@@ -321,22 +478,24 @@ fn compare_ins(
         if left_args_count != right_args_count || left_ins.op != right_ins.op {
             // Totally different op
             result.kind = ObjInsDiffKind::Replace;
-            state.diff_count += 1;
             return Ok(result);
         }
         if left_ins.mnemonic != right_ins.mnemonic {
             // Same op but different mnemonic, still cmp args
             result.kind = ObjInsDiffKind::OpMismatch;
-            state.diff_count += 1;
         }
         for (a, b) in left_ins.iter_args().zip(right_ins.iter_args()) {
-            if arg_eq(config, left_obj, right_obj, a, b, left, right) {
+            let eq = arg_eq(config, left_obj, right_obj, a, b, left, right);
+            // Identical registers still mismatch if either was mapped to another register
+            let registers_match = state.registers.as_ref().and_then(|r| r.registers_match(a, b));
+            if eq && registers_match != Some(false) {
                 result.left_args_diff.push(None);
                 result.right_args_diff.push(None);
             } else {
-                if result.kind == ObjInsDiffKind::None {
+                if registers_match == Some(true) {
+                    renamed = true;
+                } else if result.kind == ObjInsDiffKind::None {
                     result.kind = ObjInsDiffKind::ArgMismatch;
-                    state.diff_count += 1;
                 }
                 let a_str = match a {
                     ObjInsArg::PlainText(arg) => arg.to_string(),
@@ -376,12 +535,13 @@ fn compare_ins(
                 result.right_args_diff.push(Some(b_diff));
             }
         }
+        if renamed && result.kind == ObjInsDiffKind::None {
+            result.kind = ObjInsDiffKind::RegisterRename;
+        }
     } else if left.ins.is_some() {
         result.kind = ObjInsDiffKind::Delete;
-        state.diff_count += 1;
     } else {
         result.kind = ObjInsDiffKind::Insert;
-        state.diff_count += 1;
     }
     Ok(result)
 }
@@ -1,13 +1,28 @@
-use std::cmp::{max, min, Ordering};
+use std::{
+    cmp::{max, min, Ordering},
+    collections::BTreeMap,
+};
 
 use anyhow::{anyhow, Result};
-use similar::{capture_diff_slices_deadline, get_diff_ratio, Algorithm};
+use object::Endian;
+use similar::{capture_diff_slices_deadline, get_diff_ratio, Algorithm, DiffOp};
 
 use crate::{
-    diff::{ObjDataDiff, ObjDataDiffKind, ObjSectionDiff, ObjSymbolDiff},
-    obj::{ObjInfo, ObjSection, SymbolRef},
+    diff::{
+        code::reloc_eq, display::display_data_reloc, DiffObjConfig, ObjDataDiff, ObjDataDiffKind,
+        ObjDataField, ObjDataFieldDiff, ObjSectionDiff, ObjSymbolDiff,
+    },
+    obj::{
+        ObjBaseTypeKind, ObjDataType, ObjInfo, ObjReloc, ObjSection, ObjSectionKind, ObjSymbol,
+        SymbolRef,
+    },
 };
 
+/// Upper bound on the number of fields a single data symbol is broken down into.
+const MAX_DATA_FIELDS: usize = 4096;
+/// Opaque values longer than this are truncated when formatted.
+const MAX_OPAQUE_BYTES: usize = 32;
+
 pub fn diff_bss_symbol(
     left_obj: &ObjInfo,
     right_obj: &ObjInfo,
@@ -22,27 +37,253 @@ pub fn diff_bss_symbol(
             symbol_ref: left_symbol_ref,
             target_symbol: Some(right_symbol_ref),
             instructions: vec![],
+            data_fields: vec![],
+            register_mapping: vec![],
+            blocks: vec![],
             match_percent: Some(percent),
+            score: None,
+            approximate: false,
         },
         ObjSymbolDiff {
             symbol_ref: right_symbol_ref,
             target_symbol: Some(left_symbol_ref),
             instructions: vec![],
+            data_fields: vec![],
+            register_mapping: vec![],
+            blocks: vec![],
             match_percent: Some(percent),
+            score: None,
+            approximate: false,
         },
     ))
 }
 
-pub fn no_diff_symbol(_obj: &ObjInfo, symbol_ref: SymbolRef) -> ObjSymbolDiff {
-    ObjSymbolDiff { symbol_ref, target_symbol: None, instructions: vec![], match_percent: None }
+pub fn no_diff_symbol(obj: &ObjInfo, symbol_ref: SymbolRef) -> ObjSymbolDiff {
+    let (_, symbol) = obj.section_symbol(symbol_ref);
+    let data_fields = match &symbol.data_type {
+        Some(ty) => symbol_fields(obj, symbol_ref, ty)
+            .into_iter()
+            .map(|field| ObjDataFieldDiff { field: Some(field), kind: ObjDataDiffKind::None })
+            .collect(),
+        None => vec![],
+    };
+    ObjSymbolDiff {
+        symbol_ref,
+        target_symbol: None,
+        instructions: vec![],
+        data_fields,
+        register_mapping: vec![],
+        blocks: vec![],
+        match_percent: None,
+        score: None,
+        approximate: false,
+    }
+}
+
+/// Relocations within the data symbols of a section, with their offset into the section, sorted
+/// by offset.
+struct DataRelocs<'a> {
+    obj: &'a ObjInfo,
+    relocs: Vec<(u64, &'a ObjReloc)>,
+}
+
+impl<'a> DataRelocs<'a> {
+    fn new(obj: &'a ObjInfo, section: &'a ObjSection, filter: impl Fn(u64) -> bool) -> Self {
+        let mut relocs = section
+            .relocations
+            .iter()
+            .map(|reloc| (reloc.address - section.address, reloc))
+            .filter(|&(offset, _)| filter(offset))
+            .collect::<Vec<_>>();
+        relocs.sort_by_key(|&(offset, _)| offset);
+        Self { obj, relocs }
+    }
+
+    /// Relocations within any symbol of the section.
+    fn section(obj: &'a ObjInfo, section: &'a ObjSection) -> Self {
+        let mut ranges = section
+            .symbols
+            .iter()
+            .map(|s| (s.section_address, s.section_address + s.size))
+            .collect::<Vec<_>>();
+        ranges.sort_unstable();
+        // Extend each range's end to the furthest end so far, to handle overlapping symbols
+        let mut max_end = 0;
+        for (_, end) in &mut ranges {
+            max_end = max(max_end, *end);
+            *end = max_end;
+        }
+        let in_symbol = |address: u64| {
+            let idx = ranges.partition_point(|&(start, _)| start <= address);
+            idx > 0 && ranges[idx - 1].1 > address
+        };
+        Self::new(obj, section, in_symbol)
+    }
+
+    /// Relocations within a symbol.
+    fn symbol(obj: &'a ObjInfo, section: &'a ObjSection, symbol: &ObjSymbol) -> Self {
+        let range = symbol.section_address..symbol.section_address + symbol.size;
+        Self::new(obj, section, |offset| range.contains(&offset))
+    }
+
+    /// Relocations starting within `start..start + len`, with their offset from `start`.
+    fn range(&self, start: u64, len: u64) -> impl Iterator<Item = (u64, &'a ObjReloc)> + '_ {
+        let idx = self.relocs.partition_point(|&(offset, _)| offset < start);
+        self.relocs[idx..]
+            .iter()
+            .take_while(move |&&(offset, _)| offset < start + len)
+            .map(move |&(offset, reloc)| (offset - start, reloc))
+    }
+}
+
+/// Relocations at the same offset into two aligned ranges of data.
+struct RelocPair<'a> {
+    offset: u64,
+    size: u64,
+    left: Option<&'a ObjReloc>,
+    right: Option<&'a ObjReloc>,
+}
+
+/// Pairs up the relocations of two ranges of data by their offset into the range, given the
+/// start address of each side's range. Relocations overlapping a previous one are skipped.
+fn pair_relocs<'a>(
+    left: Option<(&DataRelocs<'a>, u64)>,
+    right: Option<(&DataRelocs<'a>, u64)>,
+    len: u64,
+) -> Vec<RelocPair<'a>> {
+    let mut pairs = BTreeMap::<u64, RelocPair<'a>>::new();
+    for (side, relocs) in [left, right].into_iter().enumerate() {
+        let Some((relocs, start)) = relocs else {
+            continue;
+        };
+        for (offset, reloc) in relocs.range(start, len) {
+            let pair = pairs.entry(offset).or_insert(RelocPair {
+                offset,
+                size: 0,
+                left: None,
+                right: None,
+            });
+            pair.size = max(pair.size, relocs.obj.arch.data_reloc_size(reloc.flags) as u64);
+            if side == 0 {
+                pair.left = Some(reloc);
+            } else {
+                pair.right = Some(reloc);
+            }
+        }
+    }
+    let mut end = 0;
+    pairs
+        .into_values()
+        .filter_map(|mut pair| {
+            if pair.offset < end {
+                return None;
+            }
+            pair.size = min(pair.size, len - pair.offset);
+            end = pair.offset + pair.size;
+            Some(pair)
+        })
+        .collect()
+}
+
+struct DataDiffContext<'a> {
+    config: &'a DiffObjConfig,
+    left: DataRelocs<'a>,
+    right: DataRelocs<'a>,
+}
+
+impl DataDiffContext<'_> {
+    fn reloc_eq(&self, pair: &RelocPair) -> bool {
+        reloc_eq(self.config, self.left.obj, self.right.obj, pair.left, pair.right)
+    }
+
+    /// Number of bytes of differing relocations in two equal ranges of data.
+    fn reloc_mismatch_len(&self, left_start: u64, right_start: u64, len: u64) -> usize {
+        pair_relocs(Some((&self.left, left_start)), Some((&self.right, right_start)), len)
+            .iter()
+            .filter(|pair| !self.reloc_eq(pair))
+            .map(|pair| pair.size as usize)
+            .sum()
+    }
+
+    /// Pushes a diff of `len` bytes to each side, given the start address and data of each side
+    /// that has any. Relocations are split out into their own diffs, and differing relocations in
+    /// equal data are marked as replaced. Returns the number of bytes of differing relocations.
+    fn push_diff(
+        &self,
+        left_out: &mut Vec<ObjDataDiff>,
+        right_out: &mut Vec<ObjDataDiff>,
+        left: Option<(u64, &[u8])>,
+        right: Option<(u64, &[u8])>,
+        len: usize,
+        kind: ObjDataDiffKind,
+    ) -> usize {
+        let mut push = |start: usize,
+                        end: usize,
+                        kind: ObjDataDiffKind,
+                        left_reloc: Option<&ObjReloc>,
+                        right_reloc: Option<&ObjReloc>| {
+            for (out, side, reloc) in
+                [(&mut *left_out, left, left_reloc), (&mut *right_out, right, right_reloc)]
+            {
+                out.push(ObjDataDiff {
+                    data: side.map(|(_, data)| data[start..end].to_vec()).unwrap_or_default(),
+                    kind,
+                    len: end - start,
+                    reloc: reloc.cloned(),
+                    ..Default::default()
+                });
+            }
+        };
+        let pairs = pair_relocs(
+            left.map(|(start, _)| (&self.left, start)),
+            right.map(|(start, _)| (&self.right, start)),
+            len as u64,
+        );
+        let mut mismatch_len = 0;
+        let mut cur = 0;
+        for pair in pairs {
+            let (start, end) = (pair.offset as usize, (pair.offset + pair.size) as usize);
+            if start > cur {
+                push(cur, start, kind, None, None);
+            }
+            let mut pair_kind = kind;
+            if kind == ObjDataDiffKind::None && !self.reloc_eq(&pair) {
+                pair_kind = ObjDataDiffKind::Replace;
+                mismatch_len += end - start;
+            }
+            push(start, end, pair_kind, pair.left, pair.right);
+            cur = end;
+        }
+        if cur < len {
+            push(cur, len, kind, None, None);
+        }
+        mismatch_len
+    }
+}
+
+/// Ratio of equal bytes like [`get_diff_ratio`], counting differing relocations as unequal.
+fn match_ratio(
+    ops: &[DiffOp],
+    left_len: usize,
+    right_len: usize,
+    reloc_mismatch_len: usize,
+) -> f32 {
+    let ratio = get_diff_ratio(ops, left_len, right_len);
+    if reloc_mismatch_len == 0 {
+        return ratio;
+    }
+    ratio - (2 * reloc_mismatch_len) as f32 / (left_len + right_len) as f32
 }
 
 /// Compare the data sections of two object files.
 pub fn diff_data_section(
+    left_obj: &ObjInfo,
+    right_obj: &ObjInfo,
     left: &ObjSection,
     right: &ObjSection,
     left_section_diff: &ObjSectionDiff,
     right_section_diff: &ObjSectionDiff,
+    config: &DiffObjConfig,
 ) -> Result<(ObjSectionDiff, ObjSectionDiff)> {
     let left_max =
         left.symbols.iter().map(|s| s.section_address + s.size).max().unwrap_or(0).min(left.size);
@@ -51,75 +292,92 @@ pub fn diff_data_section(
     let left_data = &left.data[..left_max as usize];
     let right_data = &right.data[..right_max as usize];
     let ops = capture_diff_slices_deadline(Algorithm::Patience, left_data, right_data, None);
-    let match_percent = get_diff_ratio(&ops, left_data.len(), right_data.len()) * 100.0;
 
+    let ctx = DataDiffContext {
+        config,
+        left: DataRelocs::section(left_obj, left),
+        right: DataRelocs::section(right_obj, right),
+    };
+    let left_side = |start: usize, len: usize| Some((start as u64, &left_data[start..start + len]));
+    let right_side =
+        |start: usize, len: usize| Some((start as u64, &right_data[start..start + len]));
     let mut left_diff = Vec::<ObjDataDiff>::new();
     let mut right_diff = Vec::<ObjDataDiff>::new();
-    for op in ops {
+    let mut reloc_mismatch_len = 0;
+    for op in &ops {
         let (tag, left_range, right_range) = op.as_tag_tuple();
-        let left_len = left_range.len();
-        let right_len = right_range.len();
-        let mut len = max(left_len, right_len);
-        let kind = match tag {
-            similar::DiffTag::Equal => ObjDataDiffKind::None,
-            similar::DiffTag::Delete => ObjDataDiffKind::Delete,
-            similar::DiffTag::Insert => ObjDataDiffKind::Insert,
+        let (left_start, left_len) = (left_range.start, left_range.len());
+        let (right_start, right_len) = (right_range.start, right_range.len());
+        match tag {
+            similar::DiffTag::Equal => {
+                reloc_mismatch_len += ctx.push_diff(
+                    &mut left_diff,
+                    &mut right_diff,
+                    left_side(left_start, left_len),
+                    right_side(right_start, right_len),
+                    left_len,
+                    ObjDataDiffKind::None,
+                );
+            }
+            similar::DiffTag::Delete => {
+                ctx.push_diff(
+                    &mut left_diff,
+                    &mut right_diff,
+                    left_side(left_start, left_len),
+                    None,
+                    left_len,
+                    ObjDataDiffKind::Delete,
+                );
+            }
+            similar::DiffTag::Insert => {
+                ctx.push_diff(
+                    &mut left_diff,
+                    &mut right_diff,
+                    None,
+                    right_side(right_start, right_len),
+                    right_len,
+                    ObjDataDiffKind::Insert,
+                );
+            }
             similar::DiffTag::Replace => {
                 // Ensure replacements are equal length
-                len = min(left_len, right_len);
-                ObjDataDiffKind::Replace
-            }
-        };
-        let left_data = &left.data[left_range];
-        let right_data = &right.data[right_range];
-        left_diff.push(ObjDataDiff {
-            data: left_data[..min(len, left_data.len())].to_vec(),
-            kind,
-            len,
-            ..Default::default()
-        });
-        right_diff.push(ObjDataDiff {
-            data: right_data[..min(len, right_data.len())].to_vec(),
-            kind,
-            len,
-            ..Default::default()
-        });
-        if kind == ObjDataDiffKind::Replace {
-            match left_len.cmp(&right_len) {
-                Ordering::Less => {
-                    let len = right_len - left_len;
-                    left_diff.push(ObjDataDiff {
-                        data: vec![],
-                        kind: ObjDataDiffKind::Insert,
-                        len,
-                        ..Default::default()
-                    });
-                    right_diff.push(ObjDataDiff {
-                        data: right_data[left_len..right_len].to_vec(),
-                        kind: ObjDataDiffKind::Insert,
-                        len,
-                        ..Default::default()
-                    });
-                }
-                Ordering::Greater => {
-                    let len = left_len - right_len;
-                    left_diff.push(ObjDataDiff {
-                        data: left_data[right_len..left_len].to_vec(),
-                        kind: ObjDataDiffKind::Delete,
-                        len,
-                        ..Default::default()
-                    });
-                    right_diff.push(ObjDataDiff {
-                        data: vec![],
-                        kind: ObjDataDiffKind::Delete,
-                        len,
-                        ..Default::default()
-                    });
+                let len = min(left_len, right_len);
+                ctx.push_diff(
+                    &mut left_diff,
+                    &mut right_diff,
+                    left_side(left_start, len),
+                    right_side(right_start, len),
+                    len,
+                    ObjDataDiffKind::Replace,
+                );
+                match left_len.cmp(&right_len) {
+                    Ordering::Less => {
+                        ctx.push_diff(
+                            &mut left_diff,
+                            &mut right_diff,
+                            None,
+                            right_side(right_start + len, right_len - len),
+                            right_len - len,
+                            ObjDataDiffKind::Insert,
+                        );
+                    }
+                    Ordering::Greater => {
+                        ctx.push_diff(
+                            &mut left_diff,
+                            &mut right_diff,
+                            left_side(left_start + len, left_len - len),
+                            None,
+                            left_len - len,
+                            ObjDataDiffKind::Delete,
+                        );
+                    }
+                    Ordering::Equal => {}
                 }
-                Ordering::Equal => {}
             }
         }
     }
+    let match_percent =
+        match_ratio(&ops, left_data.len(), right_data.len(), reloc_mismatch_len) * 100.0;
 
     let (mut left_section_diff, mut right_section_diff) =
         diff_generic_section(left, right, left_section_diff, right_section_diff)?;
@@ -140,6 +398,7 @@ pub fn diff_data_symbol(
     right_obj: &ObjInfo,
     left_symbol_ref: SymbolRef,
     right_symbol_ref: SymbolRef,
+    config: &DiffObjConfig,
 ) -> Result<(ObjSymbolDiff, ObjSymbolDiff)> {
     let (left_section, left_symbol) = left_obj.section_symbol(left_symbol_ref);
     let (right_section, right_symbol) = right_obj.section_symbol(right_symbol_ref);
@@ -153,24 +412,252 @@ pub fn diff_data_symbol(
         ..(right_symbol.section_address + right_symbol.size) as usize];
 
     let ops = capture_diff_slices_deadline(Algorithm::Patience, left_data, right_data, None);
-    let match_percent = get_diff_ratio(&ops, left_data.len(), right_data.len()) * 100.0;
+    let ctx = DataDiffContext {
+        config,
+        left: DataRelocs::symbol(left_obj, left_section, left_symbol),
+        right: DataRelocs::symbol(right_obj, right_section, right_symbol),
+    };
+    let reloc_mismatch_len = ops
+        .iter()
+        .filter_map(|op| match op.as_tag_tuple() {
+            (similar::DiffTag::Equal, left_range, right_range) => Some(ctx.reloc_mismatch_len(
+                left_symbol.section_address + left_range.start as u64,
+                right_symbol.section_address + right_range.start as u64,
+                left_range.len() as u64,
+            )),
+            _ => None,
+        })
+        .sum();
+    let match_percent =
+        match_ratio(&ops, left_data.len(), right_data.len(), reloc_mismatch_len) * 100.0;
+
+    // If only one side has type information, use it to interpret both
+    let (left_fields, right_fields) =
+        match (left_symbol.data_type.as_deref(), right_symbol.data_type.as_deref()) {
+            (Some(left_ty), Some(right_ty)) => diff_data_fields(
+                symbol_fields(left_obj, left_symbol_ref, left_ty),
+                symbol_fields(right_obj, right_symbol_ref, right_ty),
+            ),
+            (Some(ty), None) | (None, Some(ty)) => diff_data_fields(
+                symbol_fields(left_obj, left_symbol_ref, ty),
+                symbol_fields(right_obj, right_symbol_ref, ty),
+            ),
+            (None, None) => (vec![], vec![]),
+        };
 
     Ok((
         ObjSymbolDiff {
             symbol_ref: left_symbol_ref,
             target_symbol: Some(right_symbol_ref),
             instructions: vec![],
+            data_fields: left_fields,
+            register_mapping: vec![],
+            blocks: vec![],
             match_percent: Some(match_percent),
+            score: None,
+            approximate: false,
         },
         ObjSymbolDiff {
             symbol_ref: right_symbol_ref,
             target_symbol: Some(left_symbol_ref),
             instructions: vec![],
+            data_fields: right_fields,
+            register_mapping: vec![],
+            blocks: vec![],
             match_percent: Some(match_percent),
+            score: None,
+            approximate: false,
         },
     ))
 }
 
+/// Aligns the fields of two data symbols by path and compares their values.
+fn diff_data_fields(
+    left: Vec<ObjDataField>,
+    right: Vec<ObjDataField>,
+) -> (Vec<ObjDataFieldDiff>, Vec<ObjDataFieldDiff>) {
+    let left_paths = left.iter().map(|f| f.path.as_str()).collect::<Vec<_>>();
+    let right_paths = right.iter().map(|f| f.path.as_str()).collect::<Vec<_>>();
+    let ops = capture_diff_slices_deadline(Algorithm::Patience, &left_paths, &right_paths, None);
+
+    let mut left_diff = Vec::<ObjDataFieldDiff>::with_capacity(left.len());
+    let mut right_diff = Vec::<ObjDataFieldDiff>::with_capacity(right.len());
+    for op in ops {
+        let (tag, left_range, right_range) = op.as_tag_tuple();
+        let left_fields = &left[left_range];
+        let right_fields = &right[right_range];
+        let len = match tag {
+            similar::DiffTag::Equal | similar::DiffTag::Replace => {
+                min(left_fields.len(), right_fields.len())
+            }
+            similar::DiffTag::Delete | similar::DiffTag::Insert => 0,
+        };
+        for (left_field, right_field) in left_fields[..len].iter().zip(&right_fields[..len]) {
+            let kind = if tag == similar::DiffTag::Equal
+                && left_field.size == right_field.size
+                && left_field.value == right_field.value
+            {
+                ObjDataDiffKind::None
+            } else {
+                ObjDataDiffKind::Replace
+            };
+            left_diff.push(ObjDataFieldDiff { field: Some(left_field.clone()), kind });
+            right_diff.push(ObjDataFieldDiff { field: Some(right_field.clone()), kind });
+        }
+        for left_field in &left_fields[len..] {
+            let kind = ObjDataDiffKind::Delete;
+            left_diff.push(ObjDataFieldDiff { field: Some(left_field.clone()), kind });
+            right_diff.push(ObjDataFieldDiff { field: None, kind });
+        }
+        for right_field in &right_fields[len..] {
+            let kind = ObjDataDiffKind::Insert;
+            left_diff.push(ObjDataFieldDiff { field: None, kind });
+            right_diff.push(ObjDataFieldDiff { field: Some(right_field.clone()), kind });
+        }
+    }
+    (left_diff, right_diff)
+}
+
+/// Breaks a data symbol down into its leaf fields and formats their values.
+fn symbol_fields(obj: &ObjInfo, symbol_ref: SymbolRef, ty: &ObjDataType) -> Vec<ObjDataField> {
+    let mut leaves = Vec::new();
+    flatten_type(ty, String::new(), 0, &mut leaves);
+    let (section, symbol) = obj.section_symbol(symbol_ref);
+    leaves
+        .into_iter()
+        .map(|(path, offset, ty)| {
+            let value = section.filter(|section| section.kind == ObjSectionKind::Data).and_then(
+                |section| {
+                    let start = symbol.section_address.checked_add(offset)?;
+                    let end = start.checked_add(ty.size())?;
+                    if end > symbol.section_address + symbol.size {
+                        return None;
+                    }
+                    let data = section.data.get(start as usize..end as usize)?;
+                    Some(format_field_value(obj, section, start, data, ty))
+                },
+            );
+            ObjDataField { path, offset, size: ty.size(), value }
+        })
+        .collect()
+}
+
+fn is_char_type(ty: &ObjDataType) -> bool {
+    matches!(ty, ObjDataType::Base { kind: ObjBaseTypeKind::Char, .. })
+}
+
+fn flatten_type<'a>(
+    ty: &'a ObjDataType,
+    path: String,
+    offset: u64,
+    out: &mut Vec<(String, u64, &'a ObjDataType)>,
+) {
+    if out.len() >= MAX_DATA_FIELDS {
+        return;
+    }
+    match ty {
+        ObjDataType::Struct { members, .. } if !members.is_empty() => {
+            for member in members {
+                let path = format!("{}.{}", path, member.name);
+                flatten_type(&member.ty, path, offset + member.offset, out);
+            }
+        }
+        // Character arrays are displayed as strings
+        ObjDataType::Array { element, count } if *count > 0 && !is_char_type(element) => {
+            let element_size = element.size();
+            for i in 0..*count {
+                if out.len() >= MAX_DATA_FIELDS {
+                    break;
+                }
+                flatten_type(element, format!("{path}[{i}]"), offset + i * element_size, out);
+            }
+        }
+        _ => out.push((path, offset, ty)),
+    }
+}
+
+fn read_uint(obj: &ObjInfo, data: &[u8]) -> Option<u64> {
+    let e = obj.endianness;
+    Some(match data.len() {
+        1 => data[0] as u64,
+        2 => e.read_u16_bytes(data.try_into().ok()?) as u64,
+        4 => e.read_u32_bytes(data.try_into().ok()?) as u64,
+        8 => e.read_u64_bytes(data.try_into().ok()?),
+        _ => return None,
+    })
+}
+
+fn read_int(obj: &ObjInfo, data: &[u8]) -> Option<i64> {
+    let value = read_uint(obj, data)?;
+    let shift = 64 - data.len() as u32 * 8;
+    Some(((value << shift) as i64) >> shift)
+}
+
+fn format_opaque(data: &[u8]) -> String {
+    let mut out = data
+        .iter()
+        .take(MAX_OPAQUE_BYTES)
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if data.len() > MAX_OPAQUE_BYTES {
+        out.push_str(" …");
+    }
+    out
+}
+
+/// Formats the value of a field located at `offset` within `section`.
+fn format_field_value(
+    obj: &ObjInfo,
+    section: &ObjSection,
+    offset: u64,
+    data: &[u8],
+    ty: &ObjDataType,
+) -> String {
+    // Relocated values are only known by their target
+    if let Some(reloc) = section.relocations.iter().find(|r| r.address == section.address + offset)
+    {
+        return display_data_reloc(reloc);
+    }
+    let value = match ty {
+        ObjDataType::Base { kind: ObjBaseTypeKind::Bool, .. } => match read_uint(obj, data) {
+            Some(0) => Some("false".to_string()),
+            Some(1) => Some("true".to_string()),
+            _ => None,
+        },
+        ObjDataType::Base { kind: ObjBaseTypeKind::Char, .. } => {
+            Some(format!("'{}'", data.escape_ascii()))
+        }
+        ObjDataType::Base { kind: ObjBaseTypeKind::Signed, .. } => {
+            read_int(obj, data).map(|v| v.to_string())
+        }
+        ObjDataType::Base { kind: ObjBaseTypeKind::Unsigned, .. } => {
+            read_uint(obj, data).map(|v| v.to_string())
+        }
+        ObjDataType::Base { kind: ObjBaseTypeKind::Float, .. } => {
+            read_uint(obj, data).and_then(|v| match data.len() {
+                4 => Some(format!("{:?}f", f32::from_bits(v as u32))),
+                8 => Some(format!("{:?}", f64::from_bits(v))),
+                _ => None,
+            })
+        }
+        ObjDataType::Pointer { .. } => read_uint(obj, data).map(|v| format!("{v:#x}")),
+        ObjDataType::Enum { values, .. } => {
+            read_int(obj, data).map(|v| match values.iter().find(|(_, value)| *value == v) {
+                Some((name, _)) => name.clone(),
+                None => v.to_string(),
+            })
+        }
+        ObjDataType::Array { element, .. } if is_char_type(element) => {
+            // Trailing NUL padding is omitted
+            let end = data.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+            Some(format!("\"{}\"", data[..end].escape_ascii()))
+        }
+        _ => None,
+    };
+    value.unwrap_or_else(|| format_opaque(data))
+}
+
 /// Compares a section of two object files.
 /// This essentially adds up the match percentage of each symbol in the section.
 pub fn diff_generic_section(
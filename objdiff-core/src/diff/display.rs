@@ -1,8 +1,9 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::BTreeMap};
 
 use crate::{
-    diff::{ObjInsArgDiff, ObjInsDiff},
-    obj::{ObjInsArg, ObjInsArgValue, ObjReloc, ObjSymbol},
+    arch::{register_name_eq, ObjArch},
+    diff::{ObjInsArgDiff, ObjInsDiff, ObjInsDiffKind, ObjSymbolDiff},
+    obj::{ObjIns, ObjInsArg, ObjInsArgValue, ObjReloc, ObjSymbol, ObjVariableLocation},
 };
 
 #[derive(Debug, Copy, Clone)]
@@ -93,6 +94,28 @@ pub fn display_diff<E>(
     Ok(())
 }
 
+/// Maps each source line of a function to whether any of its instructions differ. Empty if the
+/// object has no line info.
+pub fn source_line_diffs(symbol_diff: &ObjSymbolDiff) -> BTreeMap<u32, bool> {
+    let mut lines = BTreeMap::<u32, bool>::new();
+    for ins_diff in &symbol_diff.instructions {
+        if let Some(line) = ins_diff.ins.as_ref().and_then(|ins| ins.line) {
+            *lines.entry(line).or_default() |= ins_diff.kind != ObjInsDiffKind::None;
+        }
+    }
+    lines
+}
+
+/// Formats the target of a relocation in data, e.g. `&name+0x4`.
+pub fn display_data_reloc(reloc: &ObjReloc) -> String {
+    let name = reloc.target.demangled_name.as_deref().unwrap_or(&reloc.target.name);
+    match reloc.addend {
+        0 => format!("&{name}"),
+        addend if addend < 0 => format!("&{name}-{:#x}", addend.unsigned_abs()),
+        addend => format!("&{name}+{addend:#x}"),
+    }
+}
+
 fn display_reloc_name<E>(
     reloc: &ObjReloc,
     mut cb: impl FnMut(DiffText) -> Result<(), E>,
@@ -106,6 +129,163 @@ fn display_reloc_name<E>(
     }
 }
 
+/// Describes an instruction using the function's debug info: inlined call sites
+/// starting at the instruction, and parameters or locals referenced by its operands.
+pub fn ins_annotations(arch: &dyn ObjArch, symbol: &ObjSymbol, ins: &ObjIns) -> Vec<String> {
+    let mut annotations = Vec::new();
+    let Some(info) = &symbol.function_info else {
+        return annotations;
+    };
+    for range in info.inlined_at(ins.address) {
+        if range.start == ins.address {
+            annotations.push(format!("inlined: {}", range.name));
+        }
+    }
+    let operands = register_operands(ins);
+    if operands.is_empty() {
+        return annotations;
+    }
+    for (kind, variables) in [("param", &info.params), ("local", &info.locals)] {
+        for variable in variables {
+            let Some((register, offset)) = variable
+                .location
+                .as_ref()
+                .and_then(|location| info.resolve_location(location, ins.address))
+            else {
+                continue;
+            };
+            let names = arch.dwarf_register_names(register);
+            if operands.iter().any(|operand| {
+                operand.offset == offset
+                    && operand
+                        .registers
+                        .iter()
+                        .any(|r| names.iter().any(|n| register_name_eq(r, n)))
+            }) {
+                annotations.push(format!("{kind}: {}", variable.name));
+            }
+        }
+    }
+    annotations
+}
+
+/// Formats a variable location using the architecture's register names.
+pub fn display_variable_location(arch: &dyn ObjArch, location: &ObjVariableLocation) -> String {
+    let register_name = |register: u16| match arch.dwarf_register_names(register).first() {
+        Some(name) => name.to_string(),
+        None => format!("dwarf_reg{register}"),
+    };
+    let display_offset = |offset: i64| {
+        if offset < 0 {
+            format!("-{:#x}", offset.unsigned_abs())
+        } else {
+            format!("+{:#x}", offset)
+        }
+    };
+    match *location {
+        ObjVariableLocation::Register(register) => register_name(register),
+        ObjVariableLocation::RegisterOffset { register, offset } => {
+            format!("[{}{}]", register_name(register), display_offset(offset))
+        }
+        ObjVariableLocation::FrameOffset(offset) => format!("[CFA{}]", display_offset(offset)),
+    }
+}
+
+#[derive(Debug, Default)]
+struct RegisterOperand<'a> {
+    registers: Vec<&'a str>,
+    /// Offset for memory operands, `None` for plain registers
+    offset: Option<i64>,
+}
+
+/// Splits instruction arguments into operands, collecting the registers and
+/// offsets of each. Operands without registers are skipped.
+fn register_operands(ins: &ObjIns) -> Vec<RegisterOperand<'_>> {
+    let mut operands = Vec::new();
+    let mut current = RegisterOperand::default();
+    let mut offset = 0i64;
+    let mut depth = 0usize;
+    let mut negate = false;
+    let mut scale = false;
+    for arg in &ins.args {
+        match arg {
+            ObjInsArg::PlainText(text) => {
+                for c in text.chars() {
+                    match c {
+                        '(' | '[' => {
+                            depth += 1;
+                            current.offset = Some(0);
+                        }
+                        ')' | ']' => depth = depth.saturating_sub(1),
+                        ',' if depth == 0 => {
+                            finish_operand(&mut operands, &mut current, &mut offset)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            ObjInsArg::Arg(ObjInsArgValue::Opaque(text)) if text == "-" => {
+                negate = true;
+                continue;
+            }
+            ObjInsArg::Arg(ObjInsArgValue::Opaque(text)) if text == "*" => {
+                // Index scale, not part of the offset
+                scale = true;
+                continue;
+            }
+            _ if scale => {}
+            ObjInsArg::Arg(ObjInsArgValue::Opaque(text)) => match parse_offset(text) {
+                Some(value) => offset = offset.wrapping_add(value),
+                None => current.registers.push(text),
+            },
+            ObjInsArg::Arg(ObjInsArgValue::Signed(value)) => {
+                offset = offset.wrapping_add(if negate { value.wrapping_neg() } else { *value });
+            }
+            ObjInsArg::Arg(ObjInsArgValue::Unsigned(value)) => {
+                let value = *value as i64;
+                offset = offset.wrapping_add(if negate { value.wrapping_neg() } else { value });
+            }
+            ObjInsArg::Reloc | ObjInsArg::BranchDest(_) => {}
+        }
+        negate = false;
+        scale = false;
+    }
+    finish_operand(&mut operands, &mut current, &mut offset);
+    operands
+}
+
+fn finish_operand<'a>(
+    operands: &mut Vec<RegisterOperand<'a>>,
+    current: &mut RegisterOperand<'a>,
+    offset: &mut i64,
+) {
+    let mut operand = std::mem::take(current);
+    if let Some(value) = &mut operand.offset {
+        *value = *offset;
+    }
+    *offset = 0;
+    if !operand.registers.is_empty() {
+        operands.push(operand);
+    }
+}
+
+/// Parses immediates that the disassembler formats as text, e.g. `-0x10` or `#4`.
+fn parse_offset(text: &str) -> Option<i64> {
+    let text = text.strip_prefix('#').unwrap_or(text);
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let value = match text.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None if !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()) => {
+            text.parse().ok()?
+        }
+        None => return None,
+    };
+    Some(if negative { -value } else { value })
+}
+
 impl PartialEq<DiffText<'_>> for HighlightKind {
     fn eq(&self, other: &DiffText) -> bool {
         match (self, other) {
@@ -0,0 +1,86 @@
+use anyhow::Result;
+use similar::{capture_diff_slices_deadline, get_diff_ratio, Algorithm};
+
+use crate::{
+    arch::ProcessCodeResult,
+    diff::{code::process_code_symbol, DiffObjConfig},
+    obj::{ObjInfo, ObjSymbolFlags, SymbolRef},
+};
+
+/// Functions less similar than this are not reported as similar.
+pub const MIN_SIMILARITY: f32 = 0.75;
+
+const OPS_WEIGHT: f32 = 0.8;
+const RELOCS_WEIGHT: f32 = 0.2;
+
+/// Summary of a function's code that doesn't depend on where it was linked or how its registers
+/// were allocated, for finding similar functions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FunctionFingerprint {
+    /// Opcode of each instruction
+    pub ops: Vec<u16>,
+    /// Target of each relocation, in instruction order. Targets that aren't global symbols are
+    /// left empty, as their names usually depend on their address.
+    pub relocs: Vec<String>,
+}
+
+impl FunctionFingerprint {
+    pub fn new(code: &ProcessCodeResult) -> Self {
+        let relocs = code
+            .insts
+            .iter()
+            .filter_map(|ins| ins.reloc.as_ref())
+            .map(|reloc| {
+                let flags = reloc.target.flags.0;
+                if flags.contains(ObjSymbolFlags::Global) || flags.contains(ObjSymbolFlags::Weak) {
+                    reloc.target.name.clone()
+                } else {
+                    String::new()
+                }
+            })
+            .collect();
+        Self { ops: code.ops.clone(), relocs }
+    }
+
+    /// Similarity of the two functions, from 0.0 to 1.0. Relocations are only compared if both
+    /// functions have them, as linked executables don't.
+    pub fn similarity(&self, other: &Self) -> f32 {
+        let ops = diff_ratio(&self.ops, &other.ops);
+        if self.relocs.is_empty() || other.relocs.is_empty() {
+            return ops;
+        }
+        ops * OPS_WEIGHT + diff_ratio(&self.relocs, &other.relocs) * RELOCS_WEIGHT
+    }
+
+    /// Upper bound of [`Self::similarity`], which only depends on the number of instructions.
+    pub fn max_similarity(&self, other: &Self) -> f32 {
+        let (a, b) = (self.ops.len(), other.ops.len());
+        if a + b == 0 {
+            return 1.0;
+        }
+        let ops = (2 * a.min(b)) as f32 / (a + b) as f32;
+        if self.relocs.is_empty() || other.relocs.is_empty() {
+            ops
+        } else {
+            ops * OPS_WEIGHT + RELOCS_WEIGHT
+        }
+    }
+}
+
+fn diff_ratio<T: Eq + std::hash::Hash + Ord>(a: &[T], b: &[T]) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let ops = capture_diff_slices_deadline(Algorithm::Myers, a, b, None);
+    get_diff_ratio(&ops, a.len(), b.len())
+}
+
+/// Computes the fingerprint of a function.
+pub fn fingerprint_symbol(
+    obj: &ObjInfo,
+    symbol_ref: SymbolRef,
+    config: &DiffObjConfig,
+) -> Result<FunctionFingerprint> {
+    let code = process_code_symbol(obj, symbol_ref, config)?;
+    Ok(FunctionFingerprint::new(&code))
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use crate::{
+    diff::{code::reloc_eq, DiffObjConfig, ObjDiff, ObjSymbolDiff},
+    obj::{ObjInfo, ObjReloc, ObjSymbol, SymbolRef},
+};
+
+/// The result of a previous diff of the same objects. The diffs of functions that haven't
+/// changed since are reused instead of being recomputed.
+#[derive(Copy, Clone)]
+pub struct CachedDiff<'a> {
+    /// The config the objects were diffed with. Nothing is reused if it has changed.
+    pub config: &'a DiffObjConfig,
+    pub left: Option<(&'a ObjInfo, &'a ObjDiff)>,
+    pub right: Option<(&'a ObjInfo, &'a ObjDiff)>,
+}
+
+/// Symbols of an object by section and symbol name, or `None` if the name isn't unique.
+struct SymbolIndex<'a>(HashMap<(&'a str, &'a str), Option<SymbolRef>>);
+
+impl<'a> SymbolIndex<'a> {
+    fn new(obj: &'a ObjInfo) -> Self {
+        let mut symbols = HashMap::new();
+        for (section_idx, section) in obj.sections.iter().enumerate() {
+            for (symbol_idx, symbol) in section.symbols.iter().enumerate() {
+                symbols
+                    .entry((section.name.as_str(), symbol.name.as_str()))
+                    .and_modify(|entry| *entry = None)
+                    .or_insert(Some(SymbolRef { section_idx, symbol_idx }));
+            }
+        }
+        Self(symbols)
+    }
+
+    fn get(&self, section_name: &str, symbol_name: &str) -> Option<SymbolRef> {
+        *self.0.get(&(section_name, symbol_name))?
+    }
+}
+
+/// Previous diffs of one object, along with the current version of the object.
+struct CachedObj<'a> {
+    prev_obj: &'a ObjInfo,
+    prev_diff: &'a ObjDiff,
+    prev_symbols: SymbolIndex<'a>,
+    obj: &'a ObjInfo,
+    symbols: SymbolIndex<'a>,
+}
+
+impl<'a> CachedObj<'a> {
+    fn new(prev_obj: &'a ObjInfo, prev_diff: &'a ObjDiff, obj: &'a ObjInfo) -> Self {
+        Self {
+            prev_obj,
+            prev_diff,
+            prev_symbols: SymbolIndex::new(prev_obj),
+            obj,
+            symbols: SymbolIndex::new(obj),
+        }
+    }
+
+    /// Finds the previous version of a function, if it's unchanged: at the same address, with
+    /// the same code, relocations and line info, and with its relocations resolving to the same
+    /// symbols. Functions that moved are rediffed, as their instructions depend on their address.
+    fn find_unchanged(&self, symbol_ref: SymbolRef) -> Option<SymbolRef> {
+        let (section, symbol) = self.obj.section_symbol(symbol_ref);
+        let section = section?;
+        let prev_ref = self.prev_symbols.get(&section.name, &symbol.name)?;
+        let (prev_section, prev_symbol) = self.prev_obj.section_symbol(prev_ref);
+        let prev_section = prev_section?;
+        if symbol.address != prev_symbol.address || symbol.size != prev_symbol.size {
+            return None;
+        }
+
+        let start = symbol.section_address as usize;
+        let prev_start = prev_symbol.section_address as usize;
+        let len = symbol.size as usize;
+        if section.data.get(start..start + len)?
+            != prev_section.data.get(prev_start..prev_start + len)?
+        {
+            return None;
+        }
+        let range = symbol.address..symbol.address + symbol.size;
+        let relocations = section.relocations.iter().filter(|r| range.contains(&r.address));
+        let prev_relocations =
+            prev_section.relocations.iter().filter(|r| range.contains(&r.address));
+        if relocations.clone().count() != prev_relocations.clone().count()
+            || !relocations.zip(prev_relocations).all(|(a, b)| reloc_input_eq(a, b))
+            || !section.line_info.range(range.clone()).eq(prev_section.line_info.range(range))
+        {
+            return None;
+        }
+
+        // Relocations to section offsets are resolved to the symbols containing them, which
+        // may have changed
+        let resolved_unchanged = self
+            .prev_diff
+            .symbol_diff(prev_ref)
+            .instructions
+            .iter()
+            .filter_map(|ins_diff| ins_diff.ins.as_ref()?.reloc.as_ref())
+            .all(|reloc| self.target_unchanged(&reloc.target));
+        resolved_unchanged.then_some(prev_ref)
+    }
+
+    /// Whether a relocation target of the previous object is at the same place in the current
+    /// object.
+    fn target_unchanged(&self, target: &ObjSymbol) -> bool {
+        let Some(orig_section_index) = target.orig_section_index else {
+            // Undefined symbols are compared by name
+            return true;
+        };
+        if target.name.is_empty() {
+            // Unresolved section offset, compared with the relocation itself
+            return true;
+        }
+        let Some(prev_section) =
+            self.prev_obj.sections.iter().find(|s| s.orig_index == orig_section_index)
+        else {
+            return false;
+        };
+        let Some(symbol_ref) = self.symbols.get(&prev_section.name, &target.name) else {
+            return false;
+        };
+        let (_, symbol) = self.obj.section_symbol(symbol_ref);
+        symbol.address == target.address
+            && symbol.size == target.size
+            && symbol.orig_section_index == target.orig_section_index
+    }
+}
+
+/// Whether two relocations of an unchanged function are the same.
+fn reloc_input_eq(left: &ObjReloc, right: &ObjReloc) -> bool {
+    left.flags == right.flags
+        && left.address == right.address
+        && left.addend == right.addend
+        && left.target.name == right.target.name
+        && left.target.address == right.target.address
+        && left.target.size == right.target.size
+        && left.target.orig_section_index == right.target.orig_section_index
+}
+
+/// Reuses the diffs of a previous [`CachedDiff`] for functions that are unchanged.
+pub(crate) struct SymbolDiffCache<'a> {
+    left: CachedObj<'a>,
+    right: CachedObj<'a>,
+}
+
+impl<'a> SymbolDiffCache<'a> {
+    /// Returns `None` if nothing can be reused with the current config and objects.
+    pub fn new(
+        cached: &CachedDiff<'a>,
+        config: &DiffObjConfig,
+        left: Option<&'a ObjInfo>,
+        right: Option<&'a ObjInfo>,
+    ) -> Option<Self> {
+        if cached.config != config {
+            return None;
+        }
+        let (prev_left_obj, prev_left_diff) = cached.left?;
+        let (prev_right_obj, prev_right_diff) = cached.right?;
+        Some(Self {
+            left: CachedObj::new(prev_left_obj, prev_left_diff, left?),
+            right: CachedObj::new(prev_right_obj, prev_right_diff, right?),
+        })
+    }
+
+    /// Returns the previous diff of two functions if they were diffed against each other and
+    /// both are unchanged, without processing their instructions.
+    pub fn code_diff(
+        &self,
+        left_symbol_ref: SymbolRef,
+        right_symbol_ref: SymbolRef,
+        config: &DiffObjConfig,
+    ) -> Option<(ObjSymbolDiff, ObjSymbolDiff)> {
+        let prev_left_ref = self.left.find_unchanged(left_symbol_ref)?;
+        let prev_right_ref = self.right.find_unchanged(right_symbol_ref)?;
+        let prev_left = self.left.prev_diff.symbol_diff(prev_left_ref);
+        let prev_right = self.right.prev_diff.symbol_diff(prev_right_ref);
+        if prev_left.target_symbol != Some(prev_right_ref)
+            || prev_right.target_symbol != Some(prev_left_ref)
+        {
+            return None;
+        }
+
+        // Relocations with different target names are compared by section name, which may have
+        // changed even if the functions haven't
+        for idx in 0..prev_left.instructions.len() {
+            let left_reloc = row_reloc(prev_left, idx);
+            let right_reloc = row_reloc(prev_right, idx);
+            let prev_eq =
+                reloc_eq(config, self.left.prev_obj, self.right.prev_obj, left_reloc, right_reloc);
+            let eq = reloc_eq(config, self.left.obj, self.right.obj, left_reloc, right_reloc);
+            if prev_eq != eq {
+                return None;
+            }
+        }
+
+        Some((
+            ObjSymbolDiff {
+                symbol_ref: left_symbol_ref,
+                target_symbol: Some(right_symbol_ref),
+                ..prev_left.clone()
+            },
+            ObjSymbolDiff {
+                symbol_ref: right_symbol_ref,
+                target_symbol: Some(left_symbol_ref),
+                ..prev_right.clone()
+            },
+        ))
+    }
+}
+
+/// Relocation of the instruction in a row of a symbol diff.
+fn row_reloc(diff: &ObjSymbolDiff, idx: usize) -> Option<&ObjReloc> {
+    diff.instructions[idx].ins.as_ref().and_then(|ins| ins.reloc.as_ref())
+}
@@ -10,13 +10,23 @@ use crate::{
             diff_bss_section, diff_bss_symbol, diff_data_section, diff_data_symbol,
             diff_generic_section, no_diff_symbol,
         },
+        incremental::{CachedDiff, SymbolDiffCache},
+        pairing::{pair_unmatched_symbols, SymbolPairing},
+    },
+    obj::{
+        ObjInfo, ObjIns, ObjReloc, ObjSection, ObjSectionKind, ObjSymbol, SymbolRef, SECTION_COMMON,
     },
-    obj::{ObjInfo, ObjIns, ObjSection, ObjSectionKind, ObjSymbol, SymbolRef, SECTION_COMMON},
 };
 
+pub mod align;
+pub mod cfg;
 pub mod code;
 pub mod data;
 pub mod display;
+pub mod fingerprint;
+pub mod incremental;
+pub mod pairing;
+pub mod three_way;
 
 #[derive(
     Debug,
@@ -151,9 +161,146 @@ pub enum ArmR9Usage {
     Tr,
 }
 
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+pub enum SymbolPairingMode {
+    #[default]
+    #[strum(message = "Off (default)", detailed_message = "Only match symbols by name.")]
+    Off,
+    #[strum(
+        message = "Suggest",
+        detailed_message = "Suggest pairings for symbols that couldn't be matched by name."
+    )]
+    Suggest,
+    #[strum(
+        message = "Apply",
+        detailed_message = "Suggest pairings and use the most confident ones for the diff."
+    )]
+    Apply,
+}
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+    strum::EnumString,
+)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[strum(serialize_all = "lowercase")]
+pub enum DiffAlgorithm {
+    #[default]
+    #[strum(
+        message = "Patience (default)",
+        detailed_message = "Aligns unique instructions first. Fast, but can align repetitive code poorly."
+    )]
+    Patience,
+    #[strum(
+        message = "Myers",
+        detailed_message = "Finds the fewest inserted and deleted instructions."
+    )]
+    Myers,
+    #[strum(
+        message = "Operand-aware",
+        detailed_message = "Prefers aligning instructions with the same arguments. Slower on large functions."
+    )]
+    Operand,
+}
+
 #[inline]
 const fn default_true() -> bool { true }
 
+/// Penalties for each kind of instruction difference, used to score function diffs in the
+/// spirit of decomp-permuter. A function's score is the sum of the penalties of its
+/// instructions, and its match percent is the score relative to every instruction having the
+/// largest penalty.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(default)]
+pub struct DiffScoreWeights {
+    pub op_mismatch: u32,
+    pub arg_mismatch: u32,
+    pub register_rename: u32,
+    pub replace: u32,
+    pub delete: u32,
+    pub insert: u32,
+}
+
+impl Default for DiffScoreWeights {
+    fn default() -> Self {
+        Self {
+            op_mismatch: 100,
+            arg_mismatch: 100,
+            register_rename: 25,
+            replace: 100,
+            delete: 100,
+            insert: 100,
+        }
+    }
+}
+
+impl DiffScoreWeights {
+    pub fn penalty(&self, kind: ObjInsDiffKind) -> u32 {
+        match kind {
+            ObjInsDiffKind::None => 0,
+            ObjInsDiffKind::OpMismatch => self.op_mismatch,
+            ObjInsDiffKind::ArgMismatch => self.arg_mismatch,
+            ObjInsDiffKind::RegisterRename => self.register_rename,
+            ObjInsDiffKind::Replace => self.replace,
+            ObjInsDiffKind::Delete => self.delete,
+            ObjInsDiffKind::Insert => self.insert,
+        }
+    }
+
+    /// The largest penalty, which a completely different instruction is expected to cost.
+    pub fn max_penalty(&self) -> u32 {
+        [
+            self.op_mismatch,
+            self.arg_mismatch,
+            self.register_rename,
+            self.replace,
+            self.delete,
+            self.insert,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or_default()
+        .max(1)
+    }
+
+    /// Sums the penalties of the aligned instruction diffs.
+    pub fn score(&self, kinds: impl IntoIterator<Item = ObjInsDiffKind>) -> u64 {
+        kinds.into_iter().map(|kind| self.penalty(kind) as u64).sum()
+    }
+
+    /// Match percent of a function with `total` instructions on its longer side.
+    pub fn match_percent(&self, score: u64, total: usize) -> f32 {
+        let max_score = total as u64 * self.max_penalty() as u64;
+        if score >= max_score {
+            0.0
+        } else {
+            (max_score - score) as f32 / max_score as f32 * 100.0
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 #[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(from_wasm_abi))]
@@ -163,8 +310,19 @@ pub struct DiffObjConfig {
     #[serde(default = "default_true")]
     pub space_between_args: bool,
     pub combine_data_sections: bool,
+    /// Treat instructions that only differ by a consistent register renaming as near-matches
+    pub register_renaming: bool,
     #[serde(default)]
     pub symbol_mappings: MappingConfig,
+    /// Pair symbols that couldn't be matched by name by their similarity
+    pub symbol_pairing: SymbolPairingMode,
+    /// Penalties used to score function diffs
+    pub score_weights: DiffScoreWeights,
+    /// Algorithm used to align the instructions of functions
+    pub diff_algorithm: DiffAlgorithm,
+    /// Time budget for aligning a function's instructions, after which the alignment is
+    /// approximate. Not supported on wasm32.
+    pub diff_time_budget_ms: Option<u32>,
     // x86
     pub x86_formatter: X86Formatter,
     // MIPS
@@ -186,7 +344,12 @@ impl Default for DiffObjConfig {
             relax_reloc_diffs: false,
             space_between_args: true,
             combine_data_sections: false,
+            register_renaming: false,
             symbol_mappings: Default::default(),
+            symbol_pairing: Default::default(),
+            score_weights: Default::default(),
+            diff_algorithm: Default::default(),
+            diff_time_budget_ms: None,
             x86_formatter: Default::default(),
             mips_abi: Default::default(),
             mips_instr_category: Default::default(),
@@ -233,7 +396,51 @@ pub struct ObjSymbolDiff {
     /// The symbol ref in the _other_ object that this symbol was diffed against
     pub target_symbol: Option<SymbolRef>,
     pub instructions: Vec<ObjInsDiff>,
+    /// Field diffs for data symbols with type information
+    pub data_fields: Vec<ObjDataFieldDiff>,
+    /// Register renaming between the left and right functions, if enabled
+    pub register_mapping: Vec<ObjRegisterMapping>,
+    /// Basic blocks for code symbols
+    pub blocks: Vec<ObjBasicBlock>,
     pub match_percent: Option<f32>,
+    /// Sum of the instruction diff penalties for code symbols, lower is better
+    /// (see [`DiffScoreWeights`])
+    pub score: Option<u64>,
+    /// Whether the instruction alignment is approximate, because it exceeded the time budget or
+    /// the function was too large for the selected algorithm
+    pub approximate: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ObjRegisterMapping {
+    /// Register in the left (target) function
+    pub left: String,
+    /// Register in the right (base) function
+    pub right: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ObjBasicBlock {
+    /// First instruction index (inclusive)
+    pub start: usize,
+    /// Last instruction index (exclusive)
+    pub end: usize,
+    /// Successor block indices
+    pub successors: Vec<usize>,
+    /// Matching block index in the _other_ symbol diff
+    pub target_block: Option<usize>,
+    /// Diff kind
+    pub kind: ObjBlockDiffKind,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum ObjBlockDiffKind {
+    #[default]
+    None,
+    /// Matched, but contains differing instructions
+    Mismatch,
+    Delete,
+    Insert,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -255,6 +462,8 @@ pub enum ObjInsDiffKind {
     None,
     OpMismatch,
     ArgMismatch,
+    /// Arguments only differ by a consistent register renaming
+    RegisterRename,
     Replace,
     Delete,
     Insert,
@@ -266,6 +475,9 @@ pub struct ObjDataDiff {
     pub kind: ObjDataDiffKind,
     pub len: usize,
     pub symbol: String,
+    /// Relocation patching these bytes. Each relocation in a data symbol is split out into its
+    /// own diff, and marked as replaced if its target differs from the other side's.
+    pub reloc: Option<ObjReloc>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
@@ -277,6 +489,23 @@ pub enum ObjDataDiffKind {
     Insert,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct ObjDataField {
+    /// Path relative to the symbol (e.g. `.pos.x`, `[2]`), empty for the symbol itself
+    pub path: String,
+    /// Offset from the start of the symbol
+    pub offset: u64,
+    pub size: u64,
+    /// Formatted value, if the symbol has data
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ObjDataFieldDiff {
+    pub field: Option<ObjDataField>,
+    pub kind: ObjDataDiffKind,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct ObjInsArgDiff {
     /// Incrementing index for coloring
@@ -324,7 +553,12 @@ impl ObjDiff {
                     symbol_ref: SymbolRef { section_idx, symbol_idx },
                     target_symbol: None,
                     instructions: vec![],
+                    data_fields: vec![],
+                    register_mapping: vec![],
+                    blocks: vec![],
                     match_percent: None,
+                    score: None,
+                    approximate: false,
                 });
             }
             result.sections.push(ObjSectionDiff {
@@ -334,6 +568,7 @@ impl ObjDiff {
                     kind: ObjDataDiffKind::None,
                     len: section.data.len(),
                     symbol: section.name.clone(),
+                    reloc: None,
                 }],
                 match_percent: None,
             });
@@ -343,7 +578,12 @@ impl ObjDiff {
                 symbol_ref: SymbolRef { section_idx: SECTION_COMMON, symbol_idx },
                 target_symbol: None,
                 instructions: vec![],
+                data_fields: vec![],
+                register_mapping: vec![],
+                blocks: vec![],
                 match_percent: None,
+                score: None,
+                approximate: false,
             });
         }
         result
@@ -383,6 +623,8 @@ pub struct DiffObjsResult {
     pub left: Option<ObjDiff>,
     pub right: Option<ObjDiff>,
     pub prev: Option<ObjDiff>,
+    /// Suggested pairings for symbols without a match, most confident first
+    pub pairings: Vec<SymbolPairing>,
 }
 
 pub fn diff_objs(
@@ -391,7 +633,28 @@ pub fn diff_objs(
     right: Option<&ObjInfo>,
     prev: Option<&ObjInfo>,
 ) -> Result<DiffObjsResult> {
-    let symbol_matches = matching_symbols(left, right, prev, &config.symbol_mappings)?;
+    diff_objs_incremental(config, left, right, prev, None)
+}
+
+/// Like [`diff_objs`], but reuses the diffs of functions from a previous diff of the same
+/// objects, wherever their address, bytes, relocations and the config are unchanged. Unchanged
+/// functions aren't disassembled again.
+pub fn diff_objs_incremental(
+    config: &DiffObjConfig,
+    left: Option<&ObjInfo>,
+    right: Option<&ObjInfo>,
+    prev: Option<&ObjInfo>,
+    cached: Option<CachedDiff>,
+) -> Result<DiffObjsResult> {
+    let cache =
+        cached.as_ref().and_then(|cached| SymbolDiffCache::new(cached, config, left, right));
+    let mut symbol_matches = matching_symbols(left, right, prev, &config.symbol_mappings)?;
+    let pairings = match (left, right) {
+        (Some(left), Some(right)) if config.symbol_pairing != SymbolPairingMode::Off => {
+            pair_unmatched_symbols(left, right, &mut symbol_matches, config)?
+        }
+        _ => vec![],
+    };
     let section_matches = matching_sections(left, right)?;
     let mut left = left.map(|p| (p, ObjDiff::new_from_obj(p)));
     let mut right = right.map(|p| (p, ObjDiff::new_from_obj(p)));
@@ -409,21 +672,39 @@ pub fn diff_objs(
                 let (right_obj, right_out) = right.as_mut().unwrap();
                 match section_kind {
                     ObjSectionKind::Code => {
-                        let left_code = process_code_symbol(left_obj, left_symbol_ref, config)?;
-                        let right_code = process_code_symbol(right_obj, right_symbol_ref, config)?;
-                        let (left_diff, right_diff) = diff_code(
-                            left_obj,
-                            right_obj,
-                            &left_code,
-                            &right_code,
-                            left_symbol_ref,
-                            right_symbol_ref,
-                            config,
-                        )?;
+                        let cached_diff = cache.as_ref().and_then(|cache| {
+                            cache.code_diff(left_symbol_ref, right_symbol_ref, config)
+                        });
+                        let mut right_code = None;
+                        let (left_diff, right_diff) = match cached_diff {
+                            Some(diffs) => diffs,
+                            None => {
+                                let left_code =
+                                    process_code_symbol(left_obj, left_symbol_ref, config)?;
+                                let right_code = right_code.insert(process_code_symbol(
+                                    right_obj,
+                                    right_symbol_ref,
+                                    config,
+                                )?);
+                                diff_code(
+                                    left_obj,
+                                    right_obj,
+                                    &left_code,
+                                    right_code,
+                                    left_symbol_ref,
+                                    right_symbol_ref,
+                                    config,
+                                )?
+                            }
+                        };
                         *left_out.symbol_diff_mut(left_symbol_ref) = left_diff;
                         *right_out.symbol_diff_mut(right_symbol_ref) = right_diff;
 
                         if let Some(prev_symbol_ref) = prev_symbol_ref {
+                            let right_code = match right_code {
+                                Some(code) => code,
+                                None => process_code_symbol(right_obj, right_symbol_ref, config)?,
+                            };
                             let (prev_obj, prev_out) = prev.as_mut().unwrap();
                             let prev_code = process_code_symbol(prev_obj, prev_symbol_ref, config)?;
                             let (_, prev_diff) = diff_code(
@@ -444,6 +725,7 @@ pub fn diff_objs(
                             right_obj,
                             left_symbol_ref,
                             right_symbol_ref,
+                            config,
                         )?;
                         *left_out.symbol_diff_mut(left_symbol_ref) = left_diff;
                         *right_out.symbol_diff_mut(right_symbol_ref) = right_diff;
@@ -466,7 +748,7 @@ pub fn diff_objs(
                     ObjSectionKind::Code => {
                         let code = process_code_symbol(left_obj, left_symbol_ref, config)?;
                         *left_out.symbol_diff_mut(left_symbol_ref) =
-                            no_diff_code(&code, left_symbol_ref)?;
+                            no_diff_code(left_obj, &code, left_symbol_ref)?;
                     }
                     ObjSectionKind::Data | ObjSectionKind::Bss => {
                         *left_out.symbol_diff_mut(left_symbol_ref) =
@@ -480,7 +762,7 @@ pub fn diff_objs(
                     ObjSectionKind::Code => {
                         let code = process_code_symbol(right_obj, right_symbol_ref, config)?;
                         *right_out.symbol_diff_mut(right_symbol_ref) =
-                            no_diff_code(&code, right_symbol_ref)?;
+                            no_diff_code(right_obj, &code, right_symbol_ref)?;
                     }
                     ObjSectionKind::Data | ObjSectionKind::Bss => {
                         *right_out.symbol_diff_mut(right_symbol_ref) =
@@ -522,10 +804,13 @@ pub fn diff_objs(
                     let left_section_diff = left_out.section_diff(left_section_idx);
                     let right_section_diff = right_out.section_diff(right_section_idx);
                     let (left_diff, right_diff) = diff_data_section(
+                        left_obj,
+                        right_obj,
                         left_section,
                         right_section,
                         left_section_diff,
                         right_section_diff,
+                        config,
                     )?;
                     left_out.section_diff_mut(left_section_idx).merge(left_diff);
                     right_out.section_diff_mut(right_section_idx).merge(right_diff);
@@ -561,6 +846,7 @@ pub fn diff_objs(
         left: left.map(|(_, o)| o),
         right: right.map(|(_, o)| o),
         prev: prev.map(|(_, o)| o),
+        pairings,
     })
 }
 
@@ -606,8 +892,13 @@ fn generate_mapping_symbols(
                     target_out.mapping_symbols.push(left_diff);
                 }
                 ObjSectionKind::Data => {
-                    let (left_diff, _right_diff) =
-                        diff_data_symbol(target_obj, base_obj, target_symbol_ref, base_symbol_ref)?;
+                    let (left_diff, _right_diff) = diff_data_symbol(
+                        target_obj,
+                        base_obj,
+                        target_symbol_ref,
+                        base_symbol_ref,
+                        config,
+                    )?;
                     target_out.mapping_symbols.push(left_diff);
                 }
                 ObjSectionKind::Bss => {
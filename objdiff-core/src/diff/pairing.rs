@@ -0,0 +1,182 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use similar::{capture_diff_slices_deadline, get_diff_ratio, Algorithm};
+
+use crate::{
+    diff::{code::process_code_symbol, DiffObjConfig, SymbolMatch, SymbolPairingMode},
+    obj::{ObjInfo, ObjSectionKind, ObjSymbolKind, SymbolRef, SECTION_COMMON},
+};
+
+/// Pairings below this confidence are not suggested.
+pub const MIN_PAIRING_CONFIDENCE: f32 = 0.5;
+/// Pairings at or above this confidence are applied in [`SymbolPairingMode::Apply`].
+pub const AUTO_PAIRING_CONFIDENCE: f32 = 0.9;
+
+/// A suggested pairing between a left and right symbol that could not be matched by name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolPairing {
+    pub left: SymbolRef,
+    pub right: SymbolRef,
+    /// Similarity of the two symbols, from 0.0 to 1.0
+    pub confidence: f32,
+    /// Whether the pairing was used for the diff
+    pub applied: bool,
+}
+
+/// Properties of an unmatched symbol used to compare it against candidates.
+struct Candidate {
+    symbol_ref: SymbolRef,
+    section_kind: ObjSectionKind,
+    section_name: String,
+    size: u64,
+    /// Opcode sequence for code, raw bytes for data
+    content: Vec<u16>,
+    reloc_targets: BTreeSet<String>,
+    used: bool,
+}
+
+fn candidate(
+    obj: &ObjInfo,
+    symbol_ref: SymbolRef,
+    section_kind: ObjSectionKind,
+    config: &DiffObjConfig,
+) -> Option<Candidate> {
+    if symbol_ref.section_idx == SECTION_COMMON {
+        return None;
+    }
+    let (section, symbol) = obj.section_symbol(symbol_ref);
+    let section = section?;
+    if symbol.size == 0 || symbol.kind == ObjSymbolKind::Section {
+        return None;
+    }
+    let content = match section_kind {
+        ObjSectionKind::Code => process_code_symbol(obj, symbol_ref, config).ok()?.ops,
+        ObjSectionKind::Data => {
+            let start = symbol.section_address as usize;
+            let end = start + symbol.size as usize;
+            section.data.get(start..end)?.iter().map(|&b| b as u16).collect()
+        }
+        ObjSectionKind::Bss => vec![],
+    };
+    let end = symbol.address + symbol.size;
+    let reloc_targets = section
+        .relocations
+        .iter()
+        .filter(|r| r.address >= symbol.address && r.address < end)
+        .map(|r| r.target.name.clone())
+        .collect();
+    Some(Candidate {
+        symbol_ref,
+        section_kind,
+        section_name: section.name.clone(),
+        size: symbol.size,
+        content,
+        reloc_targets,
+        used: false,
+    })
+}
+
+fn ratio(a: u64, b: u64) -> f32 { a.min(b) as f32 / a.max(b) as f32 }
+
+/// Scores the similarity of two candidates of the same section kind.
+fn confidence(left: &Candidate, right: &Candidate) -> f32 {
+    let size = ratio(left.size, right.size);
+    // Skip comparing contents of symbols that can't be similar
+    if size < MIN_PAIRING_CONFIDENCE {
+        return 0.0;
+    }
+    let section = if left.section_name == right.section_name { 1.0 } else { 0.0 };
+    if left.section_kind == ObjSectionKind::Bss {
+        // Size and section alone are weak evidence, so never auto-apply these
+        return size * 0.6 + section * 0.2;
+    }
+    let ops = capture_diff_slices_deadline(Algorithm::Myers, &left.content, &right.content, None);
+    let content = get_diff_ratio(&ops, left.content.len(), right.content.len());
+    let (mut score, mut total) = (content * 0.6 + size * 0.15 + section * 0.1, 0.85);
+    if !left.reloc_targets.is_empty() || !right.reloc_targets.is_empty() {
+        let common = left.reloc_targets.intersection(&right.reloc_targets).count();
+        let all = left.reloc_targets.union(&right.reloc_targets).count();
+        score += common as f32 / all as f32 * 0.15;
+        total += 0.15;
+    }
+    score / total
+}
+
+/// Suggests pairings between symbols left unmatched by name, by similarity of size, contents,
+/// relocation targets and section. In [`SymbolPairingMode::Apply`], pairings with a confidence
+/// of at least [`AUTO_PAIRING_CONFIDENCE`] are merged into `matches`.
+pub(super) fn pair_unmatched_symbols(
+    left: &ObjInfo,
+    right: &ObjInfo,
+    matches: &mut Vec<SymbolMatch>,
+    config: &DiffObjConfig,
+) -> Result<Vec<SymbolPairing>> {
+    let mut left_candidates = vec![];
+    let mut right_candidates = vec![];
+    for symbol_match in matches.iter() {
+        match (symbol_match.left, symbol_match.right) {
+            (Some(symbol_ref), None) => left_candidates.extend(candidate(
+                left,
+                symbol_ref,
+                symbol_match.section_kind,
+                config,
+            )),
+            (None, Some(symbol_ref)) => right_candidates.extend(candidate(
+                right,
+                symbol_ref,
+                symbol_match.section_kind,
+                config,
+            )),
+            _ => {}
+        }
+    }
+
+    let mut scores = vec![];
+    for (left_idx, left_candidate) in left_candidates.iter().enumerate() {
+        for (right_idx, right_candidate) in right_candidates.iter().enumerate() {
+            if left_candidate.section_kind != right_candidate.section_kind {
+                continue;
+            }
+            let confidence = confidence(left_candidate, right_candidate);
+            if confidence >= MIN_PAIRING_CONFIDENCE {
+                scores.push((confidence, left_idx, right_idx));
+            }
+        }
+    }
+    // Greedily take the most confident pairings first
+    scores.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut pairings = vec![];
+    for (confidence, left_idx, right_idx) in scores {
+        if left_candidates[left_idx].used || right_candidates[right_idx].used {
+            continue;
+        }
+        left_candidates[left_idx].used = true;
+        right_candidates[right_idx].used = true;
+        let applied = config.symbol_pairing == SymbolPairingMode::Apply
+            && confidence >= AUTO_PAIRING_CONFIDENCE;
+        pairings.push(SymbolPairing {
+            left: left_candidates[left_idx].symbol_ref,
+            right: right_candidates[right_idx].symbol_ref,
+            confidence,
+            applied,
+        });
+    }
+
+    for pairing in pairings.iter().filter(|p| p.applied) {
+        let Some(right_pos) =
+            matches.iter().position(|m| m.left.is_none() && m.right == Some(pairing.right))
+        else {
+            continue;
+        };
+        let right_match = matches.remove(right_pos);
+        if let Some(left_match) =
+            matches.iter_mut().find(|m| m.left == Some(pairing.left) && m.right.is_none())
+        {
+            left_match.right = right_match.right;
+            left_match.prev = right_match.prev;
+        }
+    }
+    Ok(pairings)
+}
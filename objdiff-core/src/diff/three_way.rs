@@ -0,0 +1,46 @@
+use crate::diff::{ObjInsDiff, ObjInsDiffKind, ObjSymbolDiff};
+
+/// How an instruction compares to the same target instruction in an earlier build.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ObjInsProgress {
+    /// Matches the target now, but didn't in the earlier build
+    Better,
+    /// Matched the target in the earlier build, but doesn't now
+    Worse,
+}
+
+/// Aligns the rows of a function's diff against an earlier build of the base with the rows of
+/// its current diff, through the target instructions both were diffed against. `target_diff` and
+/// `prev_target_diff` are the target side of the current and earlier diffs.
+///
+/// Returns the row of the earlier diff for each current row, or `None` for rows without a target
+/// instruction. Earlier instructions that weren't aligned to a target instruction are skipped.
+pub fn align_prev_rows(
+    target_diff: &ObjSymbolDiff,
+    prev_target_diff: &ObjSymbolDiff,
+) -> Vec<Option<usize>> {
+    let mut prev_rows = prev_target_diff
+        .instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, ins_diff)| ins_diff.ins.is_some())
+        .map(|(idx, _)| idx);
+    target_diff
+        .instructions
+        .iter()
+        .map(|ins_diff| {
+            ins_diff.ins.as_ref()?;
+            prev_rows.next()
+        })
+        .collect()
+}
+
+/// Compares a row of the current diff with the aligned row of the earlier diff, as returned by
+/// [`align_prev_rows`].
+pub fn ins_progress(current: &ObjInsDiff, prev: &ObjInsDiff) -> Option<ObjInsProgress> {
+    match (current.kind == ObjInsDiffKind::None, prev.kind == ObjInsDiffKind::None) {
+        (true, false) => Some(ObjInsProgress::Better),
+        (false, true) => Some(ObjInsProgress::Worse),
+        _ => None,
+    }
+}
@@ -0,0 +1,258 @@
+//! Symbol index over all units of a project, for searching symbols across the whole program.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    config::{parse_address, ProjectObject},
+    diff::{
+        diff_objs,
+        fingerprint::{fingerprint_symbol, FunctionFingerprint},
+        DiffObjConfig, ObjDiff,
+    },
+    obj::{map::LinkerMap, read, ObjInfo, ObjSectionKind, SymbolRef},
+};
+
+/// A symbol found in one of the project's units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedSymbol {
+    /// Name of the unit containing the symbol
+    pub unit: String,
+    pub section: String,
+    pub section_kind: ObjSectionKind,
+    /// Symbol name in the target object, if present
+    pub target_name: Option<String>,
+    /// Symbol name in the base object, if present
+    pub base_name: Option<String>,
+    pub demangled_name: Option<String>,
+    pub virtual_address: Option<u64>,
+    pub size: u64,
+    pub match_percent: Option<f32>,
+    /// Fingerprint of the function's code, preferring the target object's
+    pub fingerprint: Option<FunctionFingerprint>,
+}
+
+impl IndexedSymbol {
+    /// The symbol's name, preferring the target object's.
+    pub fn name(&self) -> &str {
+        self.target_name.as_deref().or(self.base_name.as_deref()).unwrap_or_default()
+    }
+
+    /// Ranks how well the symbol matches a lowercase search query. Lower is better.
+    fn rank(&self, query: &str) -> Option<u8> {
+        [self.target_name.as_deref(), self.base_name.as_deref(), self.demangled_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter_map(|name| {
+                let name = name.to_ascii_lowercase();
+                if name == query {
+                    Some(0)
+                } else if name.starts_with(query) {
+                    Some(1)
+                } else if name.contains(query) {
+                    Some(2)
+                } else {
+                    None
+                }
+            })
+            .min()
+    }
+}
+
+/// A function found by [`ProjectIndex::find_similar`].
+#[derive(Debug, Clone, Copy)]
+pub struct SimilarFunction<'a> {
+    pub symbol: &'a IndexedSymbol,
+    /// Similarity to the queried function, from 0.0 to 1.0
+    pub similarity: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProjectIndex {
+    pub symbols: Vec<IndexedSymbol>,
+}
+
+impl ProjectIndex {
+    /// Adds the symbols of a diffed unit. Base symbols paired with a target symbol are only added
+    /// once, with the target symbol.
+    pub fn add_unit(
+        &mut self,
+        unit: &str,
+        target: Option<(&ObjInfo, &ObjDiff)>,
+        base: Option<(&ObjInfo, &ObjDiff)>,
+        config: &DiffObjConfig,
+    ) {
+        let fingerprint = |obj: &ObjInfo, section_idx: usize, symbol_idx: usize| {
+            let section = &obj.sections[section_idx];
+            if section.kind != ObjSectionKind::Code || section.symbols[symbol_idx].size == 0 {
+                return None;
+            }
+            fingerprint_symbol(obj, SymbolRef { section_idx, symbol_idx }, config).ok()
+        };
+        if let Some((obj, diff)) = target {
+            for (section_idx, (section, section_diff)) in
+                obj.sections.iter().zip(&diff.sections).enumerate()
+            {
+                for (symbol_idx, (symbol, symbol_diff)) in
+                    section.symbols.iter().zip(&section_diff.symbols).enumerate()
+                {
+                    let base_symbol = symbol_diff.target_symbol.and_then(|symbol_ref| {
+                        base.map(|(base_obj, _)| base_obj.section_symbol(symbol_ref).1)
+                    });
+                    self.symbols.push(IndexedSymbol {
+                        unit: unit.to_string(),
+                        section: section.name.clone(),
+                        section_kind: section.kind,
+                        target_name: Some(symbol.name.clone()),
+                        base_name: base_symbol.map(|s| s.name.clone()),
+                        demangled_name: symbol.demangled_name.clone(),
+                        virtual_address: symbol
+                            .virtual_address
+                            .or_else(|| base_symbol.and_then(|s| s.virtual_address)),
+                        size: symbol.size,
+                        match_percent: symbol_diff.match_percent,
+                        fingerprint: fingerprint(obj, section_idx, symbol_idx),
+                    });
+                }
+            }
+        }
+        if let Some((obj, diff)) = base {
+            for (section_idx, (section, section_diff)) in
+                obj.sections.iter().zip(&diff.sections).enumerate()
+            {
+                for (symbol_idx, (symbol, symbol_diff)) in
+                    section.symbols.iter().zip(&section_diff.symbols).enumerate()
+                {
+                    if target.is_some() && symbol_diff.target_symbol.is_some() {
+                        continue;
+                    }
+                    self.symbols.push(IndexedSymbol {
+                        unit: unit.to_string(),
+                        section: section.name.clone(),
+                        section_kind: section.kind,
+                        target_name: None,
+                        base_name: Some(symbol.name.clone()),
+                        demangled_name: symbol.demangled_name.clone(),
+                        virtual_address: symbol.virtual_address,
+                        size: symbol.size,
+                        match_percent: symbol_diff.match_percent,
+                        fingerprint: fingerprint(obj, section_idx, symbol_idx),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Searches symbol names and demangled names, case-insensitively. Queries starting with
+    /// `0x` also find the symbols whose virtual address range contains that address. Exact
+    /// matches come first, then prefix matches, then any other matches.
+    pub fn search(&self, query: &str) -> Vec<&IndexedSymbol> {
+        let query = query.trim();
+        if query.is_empty() {
+            return vec![];
+        }
+        let address = query.starts_with("0x").then(|| parse_address(query).ok()).flatten();
+        let query = query.to_ascii_lowercase();
+        let mut results = self
+            .symbols
+            .iter()
+            .filter_map(|symbol| {
+                let in_range = address.zip(symbol.virtual_address).is_some_and(|(addr, start)| {
+                    addr >= start && addr < start + symbol.size.max(1)
+                });
+                if in_range {
+                    Some((0, symbol))
+                } else {
+                    symbol.rank(&query).map(|rank| (rank, symbol))
+                }
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|(a_rank, a), (b_rank, b)| {
+            a_rank
+                .cmp(b_rank)
+                .then_with(|| a.name().cmp(b.name()))
+                .then_with(|| a.unit.cmp(&b.unit))
+        });
+        results.into_iter().map(|(_, symbol)| symbol).collect()
+    }
+
+    /// Finds the symbol with the given target or base name in a unit.
+    pub fn find_symbol(&self, unit: &str, name: &str) -> Option<&IndexedSymbol> {
+        self.symbols.iter().find(|symbol| {
+            symbol.unit == unit
+                && (symbol.target_name.as_deref() == Some(name)
+                    || symbol.base_name.as_deref() == Some(name))
+        })
+    }
+
+    /// Finds the functions at least `min_similarity` similar to a fingerprint, most similar
+    /// first.
+    pub fn find_similar(
+        &self,
+        fingerprint: &FunctionFingerprint,
+        min_similarity: f32,
+    ) -> Vec<SimilarFunction<'_>> {
+        let mut results = self
+            .symbols
+            .iter()
+            .filter_map(|symbol| {
+                let other = symbol.fingerprint.as_ref()?;
+                // Skip comparing functions that can't be similar
+                if fingerprint.max_similarity(other) < min_similarity {
+                    return None;
+                }
+                let similarity = fingerprint.similarity(other);
+                (similarity >= min_similarity).then_some(SimilarFunction { symbol, similarity })
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then_with(|| a.symbol.name().cmp(b.symbol.name()))
+                .then_with(|| a.symbol.unit.cmp(&b.symbol.unit))
+        });
+        results
+    }
+}
+
+/// Loads and diffs a unit with its symbol mappings, then adds its symbols to the index. The unit's
+/// paths must already be resolved. Objects that don't exist are skipped.
+pub fn index_unit(
+    index: &mut ProjectIndex,
+    object: &ProjectObject,
+    linker_map: Option<&LinkerMap>,
+    config: &DiffObjConfig,
+) -> Result<()> {
+    let mut config = config.clone();
+    config.symbol_mappings.mappings = object.symbol_mappings.clone().unwrap_or_default();
+    // Skip objects that haven't been built yet
+    let exists = |p: &&Path| read::split_member_path(p).0.exists();
+    let mut target = object
+        .target_path
+        .as_deref()
+        .filter(exists)
+        .map(|p| {
+            read::read_target(p, object.target_region.as_ref(), linker_map, &config)
+                .with_context(|| format!("Loading {}", p.display()))
+        })
+        .transpose()?;
+    let mut base = object
+        .base_path
+        .as_deref()
+        .filter(exists)
+        .map(|p| read::read(p, &config).with_context(|| format!("Loading {}", p.display())))
+        .transpose()?;
+    if let Some(linker_map) = linker_map {
+        let unit = object.target_region.as_ref().and_then(|r| r.unit.as_deref());
+        linker_map.apply(target.as_mut(), base.as_mut(), unit);
+    }
+    let result = diff_objs(&config, target.as_ref(), base.as_ref(), None)?;
+    index.add_unit(
+        object.name(),
+        target.as_ref().zip(result.left.as_ref()),
+        base.as_ref().zip(result.right.as_ref()),
+        &config,
+    );
+    Ok(())
+}
@@ -10,14 +10,18 @@ use std::{
 
 use anyhow::Result;
 
-use crate::jobs::{
-    check_update::CheckUpdateResult, create_scratch::CreateScratchResult, objdiff::ObjDiffResult,
-    update::UpdateResult,
+use crate::{
+    index::ProjectIndex,
+    jobs::{
+        check_update::CheckUpdateResult, create_scratch::CreateScratchResult,
+        objdiff::ObjDiffResult, update::UpdateResult,
+    },
 };
 
 pub mod check_update;
 pub mod create_scratch;
 pub mod objdiff;
+pub mod symbol_index;
 pub mod update;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -26,6 +30,7 @@ pub enum Job {
     CheckUpdate,
     Update,
     CreateScratch,
+    SymbolIndex,
 }
 pub static JOB_ID: AtomicUsize = AtomicUsize::new(0);
 
@@ -168,6 +173,7 @@ pub enum JobResult {
     CheckUpdate(Option<Box<CheckUpdateResult>>),
     Update(Box<UpdateResult>),
     CreateScratch(Option<Box<CreateScratchResult>>),
+    SymbolIndex(Option<Box<ProjectIndex>>),
 }
 
 fn should_cancel(rx: &Receiver<()>) -> bool {
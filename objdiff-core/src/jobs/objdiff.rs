@@ -1,14 +1,22 @@
-use std::{path::PathBuf, sync::mpsc::Receiver, task::Waker};
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{mpsc::Receiver, Arc},
+    task::Waker,
+};
 
 use anyhow::{anyhow, Error, Result};
 use time::OffsetDateTime;
 
 use crate::{
     build::{run_make, BuildConfig, BuildStatus},
-    config::SymbolMappings,
-    diff::{diff_objs, DiffObjConfig, MappingConfig, ObjDiff},
+    config::{SymbolMappings, TargetRegion},
+    diff::{
+        diff_objs_incremental, incremental::CachedDiff, pairing::SymbolPairing, DiffObjConfig,
+        MappingConfig, ObjDiff,
+    },
     jobs::{start_job, update_status, Job, JobContext, JobResult, JobState},
-    obj::{read, ObjInfo},
+    obj::{map::LinkerMap, read, ObjInfo},
 };
 
 pub struct ObjDiffConfig {
@@ -17,10 +25,17 @@ pub struct ObjDiffConfig {
     pub build_target: bool,
     pub target_path: Option<PathBuf>,
     pub base_path: Option<PathBuf>,
+    pub target_region: Option<TargetRegion>,
+    pub linker_map: Option<Arc<LinkerMap>>,
     pub diff_obj_config: DiffObjConfig,
     pub symbol_mappings: SymbolMappings,
     pub selecting_left: Option<String>,
     pub selecting_right: Option<String>,
+    /// The previous result for the same object, whose function diffs are reused where nothing
+    /// has changed
+    pub last_build: Option<Arc<ObjDiffResult>>,
+    /// An earlier base build to also diff the target against, for a three-way diff
+    pub compare_base: Option<Arc<BaseBuild>>,
 }
 
 pub struct ObjDiffResult {
@@ -28,9 +43,85 @@ pub struct ObjDiffResult {
     pub second_status: BuildStatus,
     pub first_obj: Option<(ObjInfo, ObjDiff)>,
     pub second_obj: Option<(ObjInfo, ObjDiff)>,
+    /// Suggested pairings between unmatched target and base symbols
+    pub pairings: Vec<SymbolPairing>,
+    pub time: OffsetDateTime,
+    /// The config the objects were diffed with
+    pub diff_obj_config: DiffObjConfig,
+    /// The target diffed against [`ObjDiffConfig::compare_base`]
+    pub compare: Option<CompareResult>,
+}
+
+/// A diff of the target against an earlier base build.
+pub struct CompareResult {
+    pub base: Arc<BaseBuild>,
+    pub target_diff: ObjDiff,
+    pub base_diff: ObjDiff,
+}
+
+/// Number of earlier base builds kept in a [`BaseBuildHistory`].
+pub const BASE_HISTORY_LEN: usize = 8;
+
+/// A successful build of the base object, kept to compare later builds against.
+pub struct BaseBuild {
+    pub obj: ObjInfo,
     pub time: OffsetDateTime,
 }
 
+/// Ring buffer of earlier base builds of one object, most recent first.
+#[derive(Default)]
+pub struct BaseBuildHistory {
+    /// Path of the base object the builds are of
+    path: Option<PathBuf>,
+    builds: VecDeque<Arc<BaseBuild>>,
+}
+
+impl BaseBuildHistory {
+    /// Records the base object of a build that was replaced by `current`, unless its file is
+    /// unchanged since. The history is cleared if either build is of another object.
+    pub fn push_replaced(&mut self, replaced: ObjDiffResult, current: &ObjDiffResult) {
+        let Some((obj, _)) = replaced.second_obj else {
+            return;
+        };
+        if let Some((current_obj, _)) = &current.second_obj {
+            if current_obj.path != obj.path {
+                self.clear();
+                self.path.clone_from(&current_obj.path);
+                return;
+            }
+        }
+        if self.path != obj.path {
+            self.clear();
+            self.path.clone_from(&obj.path);
+        }
+        let same_file =
+            |other: &ObjInfo| obj.timestamp.is_some() && other.timestamp == obj.timestamp;
+        if current.second_obj.as_ref().is_some_and(|(current_obj, _)| same_file(current_obj))
+            || self.builds.front().is_some_and(|build| same_file(&build.obj))
+        {
+            return;
+        }
+        if self.builds.len() == BASE_HISTORY_LEN {
+            self.builds.pop_back();
+        }
+        self.builds.push_front(Arc::new(BaseBuild { obj, time: replaced.time }));
+    }
+
+    pub fn clear(&mut self) {
+        self.path = None;
+        self.builds.clear();
+    }
+
+    #[inline]
+    pub fn get(&self, idx: usize) -> Option<&Arc<BaseBuild>> { self.builds.get(idx) }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<BaseBuild>> { self.builds.iter() }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.builds.is_empty() }
+}
+
 fn run_build(
     context: &JobContext,
     cancel: Receiver<()>,
@@ -52,7 +143,9 @@ fn run_build(
             .as_ref()
             .ok_or_else(|| Error::msg("Missing project dir"))?;
         if let Some(target_path) = &config.target_path {
-            target_path_rel = Some(target_path.strip_prefix(project_dir).map_err(|_| {
+            // Build the archive when selecting one of its members
+            let (target_file, _) = read::split_member_path(target_path);
+            target_path_rel = Some(target_file.strip_prefix(project_dir).map_err(|_| {
                 anyhow!(
                     "Target path '{}' doesn't begin with '{}'",
                     target_path.display(),
@@ -61,7 +154,8 @@ fn run_build(
             })?);
         }
         if let Some(base_path) = &config.base_path {
-            base_path_rel = Some(base_path.strip_prefix(project_dir).map_err(|_| {
+            let (base_file, _) = read::split_member_path(base_path);
+            base_path_rel = Some(base_file.strip_prefix(project_dir).map_err(|_| {
                 anyhow!(
                     "Base path '{}' doesn't begin with '{}'",
                     base_path.display(),
@@ -118,7 +212,7 @@ fn run_build(
 
     let time = OffsetDateTime::now_utc();
 
-    let first_obj = match &config.target_path {
+    let mut first_obj = match &config.target_path {
         Some(target_path) if first_status.success => {
             update_status(
                 context,
@@ -128,7 +222,12 @@ fn run_build(
                 &cancel,
             )?;
             step_idx += 1;
-            match read::read(target_path, &config.diff_obj_config) {
+            match read::read_target(
+                target_path,
+                config.target_region.as_ref(),
+                config.linker_map.as_deref(),
+                &config.diff_obj_config,
+            ) {
                 Ok(obj) => Some(obj),
                 Err(e) => {
                     first_status = BuildStatus {
@@ -148,7 +247,7 @@ fn run_build(
         _ => None,
     };
 
-    let second_obj = match &config.base_path {
+    let mut second_obj = match &config.base_path {
         Some(base_path) if second_status.success => {
             update_status(
                 context,
@@ -178,9 +277,51 @@ fn run_build(
         _ => None,
     };
 
+    if let Some(linker_map) = &config.linker_map {
+        let unit = config.target_region.as_ref().and_then(|r| r.unit.as_deref());
+        linker_map.apply(first_obj.as_mut(), second_obj.as_mut(), unit);
+    }
+
     update_status(context, "Performing diff".to_string(), step_idx, total, &cancel)?;
     step_idx += 1;
-    let result = diff_objs(&config.diff_obj_config, first_obj.as_ref(), second_obj.as_ref(), None)?;
+    let cached = config.last_build.as_deref().map(|last| CachedDiff {
+        config: &last.diff_obj_config,
+        left: last.first_obj.as_ref().map(|(o, d)| (o, d)),
+        right: last.second_obj.as_ref().map(|(o, d)| (o, d)),
+    });
+    let result = diff_objs_incremental(
+        &config.diff_obj_config,
+        first_obj.as_ref(),
+        second_obj.as_ref(),
+        None,
+        cached,
+    )?;
+    let compare = match (&config.compare_base, &first_obj) {
+        (Some(base), Some(target)) => {
+            // Reuse the previous comparison with the same base build
+            let last_compare = config.last_build.as_deref().and_then(|last| {
+                let compare = last.compare.as_ref().filter(|c| Arc::ptr_eq(&c.base, base))?;
+                Some(CachedDiff {
+                    config: &last.diff_obj_config,
+                    left: last.first_obj.as_ref().map(|(o, _)| (o, &compare.target_diff)),
+                    right: Some((&base.obj, &compare.base_diff)),
+                })
+            });
+            let result = diff_objs_incremental(
+                &config.diff_obj_config,
+                Some(target),
+                Some(&base.obj),
+                None,
+                last_compare,
+            )?;
+            result.left.zip(result.right).map(|(target_diff, base_diff)| CompareResult {
+                base: base.clone(),
+                target_diff,
+                base_diff,
+            })
+        }
+        _ => None,
+    };
 
     update_status(context, "Complete".to_string(), step_idx, total, &cancel)?;
     Ok(Box::new(ObjDiffResult {
@@ -188,7 +329,10 @@ fn run_build(
         second_status,
         first_obj: first_obj.and_then(|o| result.left.map(|d| (o, d))),
         second_obj: second_obj.and_then(|o| result.right.map(|d| (o, d))),
+        pairings: result.pairings,
         time,
+        diff_obj_config: config.diff_obj_config,
+        compare,
     }))
 }
 
@@ -0,0 +1,47 @@
+use std::{
+    sync::{mpsc::Receiver, Arc},
+    task::Waker,
+};
+
+use anyhow::Result;
+
+use crate::{
+    config::ProjectObject,
+    diff::DiffObjConfig,
+    index::{index_unit, ProjectIndex},
+    jobs::{start_job, update_status, Job, JobContext, JobResult, JobState},
+    obj::map::LinkerMap,
+};
+
+pub struct SymbolIndexConfig {
+    /// Units to index, with their paths resolved
+    pub units: Vec<ProjectObject>,
+    pub linker_map: Option<Arc<LinkerMap>>,
+    pub diff_obj_config: DiffObjConfig,
+}
+
+fn run_symbol_index(
+    context: &JobContext,
+    cancel: Receiver<()>,
+    config: SymbolIndexConfig,
+) -> Result<Box<ProjectIndex>> {
+    let total = config.units.len() as u32 + 1;
+    let mut index = ProjectIndex::default();
+    for (i, unit) in config.units.iter().enumerate() {
+        update_status(context, format!("Indexing {}", unit.name()), i as u32, total, &cancel)?;
+        if let Err(e) =
+            index_unit(&mut index, unit, config.linker_map.as_deref(), &config.diff_obj_config)
+        {
+            log::warn!("Failed to index {}: {e:#}", unit.name());
+        }
+    }
+    update_status(context, "Complete".to_string(), total, total, &cancel)?;
+    Ok(Box::new(index))
+}
+
+pub fn start_symbol_index(waker: Waker, config: SymbolIndexConfig) -> JobState {
+    start_job(waker, "Index symbols", Job::SymbolIndex, move |context, cancel| {
+        run_symbol_index(&context, cancel, config)
+            .map(|result| JobResult::SymbolIndex(Some(result)))
+    })
+}
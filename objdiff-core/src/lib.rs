@@ -8,6 +8,8 @@ pub mod build;
 pub mod config;
 #[cfg(feature = "any-arch")]
 pub mod diff;
+#[cfg(feature = "any-arch")]
+pub mod index;
 #[cfg(feature = "build")]
 pub mod jobs;
 #[cfg(feature = "any-arch")]
@@ -0,0 +1,677 @@
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use gimli::{AttributeValue, EndianSlice, Operation, RunTimeEndian, UnitOffset, UnwindSection};
+use object::{File, Object, ObjectSection};
+
+use crate::obj::{
+    ObjBaseTypeKind, ObjCfaRange, ObjDataMember, ObjDataType, ObjFunctionInfo, ObjInlinedRange,
+    ObjVariable, ObjVariableLocation,
+};
+
+/// Maximum depth when resolving nested types, to guard against malformed input.
+const MAX_TYPE_DEPTH: usize = 64;
+
+#[derive(Debug, Default)]
+struct DwarfSection<'data> {
+    data: Cow<'data, [u8]>,
+    relocations: object::read::RelocationMap,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RelocationMap<'a>(&'a object::read::RelocationMap);
+
+impl gimli::read::Relocate for RelocationMap<'_> {
+    fn relocate_address(&self, offset: usize, value: u64) -> gimli::Result<u64> {
+        Ok(self.0.relocate(offset as u64, value))
+    }
+
+    fn relocate_offset(&self, offset: usize, value: usize) -> gimli::Result<usize> {
+        <usize as gimli::ReaderOffset>::from_u64(self.0.relocate(offset as u64, value as u64))
+    }
+}
+
+type Reader<'a> = gimli::RelocateReader<EndianSlice<'a, RunTimeEndian>, RelocationMap<'a>>;
+type Dwarf<'a> = gimli::Dwarf<Reader<'a>>;
+type Unit<'a> = gimli::Unit<Reader<'a>>;
+type Entry<'a, 'u> = gimli::DebuggingInformationEntry<'a, 'u, Reader<'a>>;
+
+fn load_section<'data>(
+    obj_file: &File<'data>,
+    id: gimli::SectionId,
+) -> Result<DwarfSection<'data>> {
+    let Some(section) = obj_file.section_by_name(id.name()) else {
+        return Ok(DwarfSection::default());
+    };
+    let data = section.uncompressed_data()?;
+    // Relocations are required to read string and type references in relocatable objects
+    let relocations = section.relocation_map().unwrap_or_else(|e| {
+        log::warn!("Failed to read relocations for {}: {e}", id.name());
+        Default::default()
+    });
+    Ok(DwarfSection { data, relocations })
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct DebugInfo {
+    /// Variable types, keyed by symbol name
+    pub(crate) data_types: HashMap<String, Arc<ObjDataType>>,
+    /// Function metadata, keyed by symbol name
+    pub(crate) functions: HashMap<String, Arc<ObjFunctionInfo>>,
+}
+
+/// Decodes variable types and function metadata from DWARF 2+ debug info.
+pub(crate) fn debug_info(obj_file: &File<'_>) -> Result<DebugInfo> {
+    let mut result = DebugInfo::default();
+    if obj_file.section_by_name(".debug_info").is_none() {
+        return Ok(result);
+    }
+    let sections = gimli::DwarfSections::load(|id| load_section(obj_file, id))?;
+    let endian = match obj_file.endianness() {
+        object::Endianness::Little => RunTimeEndian::Little,
+        object::Endianness::Big => RunTimeEndian::Big,
+    };
+    let dwarf = sections.borrow(|section| {
+        gimli::RelocateReader::new(
+            EndianSlice::new(&section.data, endian),
+            RelocationMap(&section.relocations),
+        )
+    });
+    let debug_frame = load_section(obj_file, gimli::SectionId::DebugFrame)?;
+    let frames = frame_rules(
+        obj_file,
+        gimli::RelocateReader::new(
+            EndianSlice::new(&debug_frame.data, endian),
+            RelocationMap(&debug_frame.relocations),
+        ),
+    )
+    .unwrap_or_else(|e| {
+        log::warn!("Failed to read .debug_frame: {e}");
+        Default::default()
+    });
+
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let mut types = HashMap::new();
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            match entry.tag() {
+                gimli::DW_TAG_variable => {
+                    variable_type(&dwarf, &unit, entry, &mut types, &mut result.data_types)?
+                }
+                gimli::DW_TAG_subprogram => {
+                    if let Some((name, info)) = function_info(&dwarf, &unit, entry, &frames)? {
+                        result.functions.insert(name, Arc::new(info));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn variable_type(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<'_>,
+    entry: &Entry<'_, '_>,
+    types: &mut HashMap<UnitOffset, Arc<ObjDataType>>,
+    result: &mut HashMap<String, Arc<ObjDataType>>,
+) -> Result<()> {
+    // Only variables with a static address can correspond to a data symbol
+    let is_definition = match entry.attr_value(gimli::DW_AT_location)? {
+        Some(value) => match value.exprloc_value() {
+            Some(mut expression) => matches!(
+                Operation::parse(&mut expression.0, unit.encoding()),
+                Ok(Operation::Address { .. })
+            ),
+            None => false,
+        },
+        None => false,
+    };
+    if !is_definition && entry.attr_value(gimli::DW_AT_declaration)?.is_none() {
+        return Ok(());
+    }
+    // Definitions of previously declared variables (e.g. C++ static members)
+    // refer back to the declaration for their name and type.
+    let declaration = match entry.attr_value(gimli::DW_AT_specification)? {
+        Some(AttributeValue::UnitRef(offset)) => Some(unit.entry(offset)?),
+        _ => None,
+    };
+    let Some(name) = variable_name(dwarf, unit, entry)?.or(match &declaration {
+        Some(declaration) => variable_name(dwarf, unit, declaration)?,
+        None => None,
+    }) else {
+        return Ok(());
+    };
+    let type_offset = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(offset)) => offset,
+        _ => match declaration.as_ref().map(|d| d.attr_value(gimli::DW_AT_type)) {
+            Some(Ok(Some(AttributeValue::UnitRef(offset)))) => offset,
+            _ => return Ok(()),
+        },
+    };
+    let ty = resolve_type(dwarf, unit, type_offset, types, 0)?;
+    // Prefer definitions over declarations
+    if is_definition {
+        result.insert(name, ty);
+    } else {
+        result.entry(name).or_insert(ty);
+    }
+    Ok(())
+}
+
+/// CFA rules from .debug_frame, keyed by FDE address range.
+type FrameRules = HashMap<(u64, u64), Vec<ObjCfaRange>>;
+
+fn frame_rules(obj_file: &File<'_>, reader: Reader<'_>) -> Result<FrameRules> {
+    let mut result = FrameRules::new();
+    if gimli::Reader::is_empty(&reader) {
+        return Ok(result);
+    }
+    let mut debug_frame = gimli::DebugFrame::from(reader);
+    debug_frame.set_address_size(if obj_file.is_64() { 8 } else { 4 });
+    let bases = gimli::BaseAddresses::default();
+    let mut ctx = gimli::UnwindContext::new();
+    let mut entries = debug_frame.entries(&bases);
+    while let Some(entry) = entries.next()? {
+        let gimli::CieOrFde::Fde(partial) = entry else {
+            continue;
+        };
+        let fde = partial.parse(gimli::DebugFrame::cie_from_offset)?;
+        let mut rules = Vec::new();
+        let mut table = fde.rows(&debug_frame, &bases, &mut ctx)?;
+        while let Some(row) = table.next_row()? {
+            // Only simple register + offset rules are supported
+            if let gimli::CfaRule::RegisterAndOffset { register, offset } = row.cfa() {
+                rules.push(ObjCfaRange {
+                    start: row.start_address(),
+                    end: row.end_address(),
+                    register: register.0,
+                    offset: *offset,
+                });
+            }
+        }
+        result.insert((fde.initial_address(), fde.len()), rules);
+    }
+    Ok(result)
+}
+
+/// How `DW_OP_fbreg` locations are resolved for a function.
+#[derive(Debug, Copy, Clone)]
+enum FrameBase {
+    RegisterOffset(u16, i64),
+    Cfa,
+}
+
+fn function_info(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<'_>,
+    entry: &Entry<'_, '_>,
+    frames: &FrameRules,
+) -> Result<Option<(String, ObjFunctionInfo)>> {
+    // Skip declarations and abstract instances of inline functions
+    let Some(range) = dwarf.die_ranges(unit, entry)?.next()? else {
+        return Ok(None);
+    };
+    let Some(name) = origin_attr(unit, entry, |e| variable_name(dwarf, unit, e))? else {
+        return Ok(None);
+    };
+    let return_type = match origin_attr(unit, entry, |e| Ok(e.attr_value(gimli::DW_AT_type)?))? {
+        Some(AttributeValue::UnitRef(offset)) => Some(type_name(dwarf, unit, offset, 0)?),
+        _ => None,
+    };
+    let frame_base =
+        match entry.attr_value(gimli::DW_AT_frame_base)?.and_then(|v| v.exprloc_value()) {
+            Some(mut expression) => match single_operation(unit, &mut expression.0)? {
+                Some(Operation::Register { register }) => {
+                    Some(FrameBase::RegisterOffset(register.0, 0))
+                }
+                Some(Operation::RegisterOffset { register, offset, .. }) => {
+                    Some(FrameBase::RegisterOffset(register.0, offset))
+                }
+                Some(Operation::CallFrameCFA) => Some(FrameBase::Cfa),
+                _ => None,
+            },
+            None => None,
+        };
+    let mut info = ObjFunctionInfo {
+        return_type,
+        cfa: frames.get(&(range.begin, range.end - range.begin)).cloned().unwrap_or_default(),
+        ..Default::default()
+    };
+    let mut tree = unit.entries_tree(Some(entry.offset()))?;
+    function_children(dwarf, unit, tree.root()?, frame_base, 0, &mut info)?;
+    info.inlined.sort_by_key(|range| (range.start, range.depth));
+    Ok(Some((name, info)))
+}
+
+fn function_children(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<'_>,
+    node: gimli::EntriesTreeNode<'_, '_, '_, Reader<'_>>,
+    frame_base: Option<FrameBase>,
+    depth: u32,
+    info: &mut ObjFunctionInfo,
+) -> Result<()> {
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        match entry.tag() {
+            // Variables of inlined functions are not tracked
+            gimli::DW_TAG_formal_parameter | gimli::DW_TAG_variable if depth == 0 => {
+                let Some(variable) = variable_info(dwarf, unit, entry, frame_base)? else {
+                    continue;
+                };
+                if entry.tag() == gimli::DW_TAG_formal_parameter {
+                    info.params.push(variable);
+                } else {
+                    info.locals.push(variable);
+                }
+            }
+            gimli::DW_TAG_lexical_block => {
+                function_children(dwarf, unit, child, frame_base, depth, info)?;
+            }
+            gimli::DW_TAG_inlined_subroutine => {
+                let name = origin_attr(unit, entry, |e| entry_name(dwarf, unit, e))?
+                    .unwrap_or_else(|| "<inlined>".to_string());
+                let call_line = entry
+                    .attr_value(gimli::DW_AT_call_line)?
+                    .and_then(|v| v.udata_value())
+                    .map(|line| line as u32);
+                let mut ranges = dwarf.die_ranges(unit, entry)?;
+                while let Some(range) = ranges.next()? {
+                    info.inlined.push(ObjInlinedRange {
+                        name: name.clone(),
+                        start: range.begin,
+                        end: range.end,
+                        depth,
+                        call_line,
+                    });
+                }
+                function_children(dwarf, unit, child, frame_base, depth + 1, info)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn variable_info(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<'_>,
+    entry: &Entry<'_, '_>,
+    frame_base: Option<FrameBase>,
+) -> Result<Option<ObjVariable>> {
+    let Some(name) = origin_attr(unit, entry, |e| entry_name(dwarf, unit, e))? else {
+        return Ok(None);
+    };
+    let ty = match origin_attr(unit, entry, |e| Ok(e.attr_value(gimli::DW_AT_type)?))? {
+        Some(AttributeValue::UnitRef(offset)) => Some(type_name(dwarf, unit, offset, 0)?),
+        _ => None,
+    };
+    // Location lists and complex expressions are not supported
+    let location = match entry.attr_value(gimli::DW_AT_location)?.and_then(|v| v.exprloc_value()) {
+        Some(mut expression) => match single_operation(unit, &mut expression.0)? {
+            Some(Operation::FrameOffset { offset }) => match frame_base {
+                Some(FrameBase::RegisterOffset(register, base)) => {
+                    Some(ObjVariableLocation::RegisterOffset { register, offset: base + offset })
+                }
+                Some(FrameBase::Cfa) => Some(ObjVariableLocation::FrameOffset(offset)),
+                None => None,
+            },
+            Some(Operation::RegisterOffset { register, offset, .. }) => {
+                Some(ObjVariableLocation::RegisterOffset { register: register.0, offset })
+            }
+            Some(Operation::Register { register }) => {
+                Some(ObjVariableLocation::Register(register.0))
+            }
+            _ => None,
+        },
+        None => None,
+    };
+    Ok(Some(ObjVariable { name, ty, location }))
+}
+
+/// Parses an expression consisting of exactly one operation.
+fn single_operation<'a>(
+    unit: &Unit<'a>,
+    expression: &mut Reader<'a>,
+) -> Result<Option<Operation<Reader<'a>>>> {
+    let operation = Operation::parse(expression, unit.encoding())?;
+    Ok(if gimli::Reader::is_empty(expression) { Some(operation) } else { None })
+}
+
+/// Reads an attribute from an entry, falling back to its abstract origin or specification.
+fn origin_attr<'a, T>(
+    unit: &'a Unit<'a>,
+    entry: &Entry<'a, 'a>,
+    mut f: impl FnMut(&Entry<'a, 'a>) -> Result<Option<T>>,
+) -> Result<Option<T>> {
+    if let Some(value) = f(entry)? {
+        return Ok(Some(value));
+    }
+    let mut current = entry.clone();
+    for _ in 0..MAX_TYPE_DEPTH {
+        let offset = match current.attr_value(gimli::DW_AT_abstract_origin)? {
+            Some(AttributeValue::UnitRef(offset)) => offset,
+            _ => match current.attr_value(gimli::DW_AT_specification)? {
+                Some(AttributeValue::UnitRef(offset)) => offset,
+                _ => return Ok(None),
+            },
+        };
+        current = unit.entry(offset)?;
+        if let Some(value) = f(&current)? {
+            return Ok(Some(value));
+        }
+    }
+    Ok(None)
+}
+
+/// Formats a type reference as a C-like type name.
+fn type_name(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<'_>,
+    offset: UnitOffset,
+    depth: usize,
+) -> Result<String> {
+    let entry = unit.entry(offset)?;
+    let name = entry_name(dwarf, unit, &entry)?;
+    if depth > MAX_TYPE_DEPTH {
+        return Ok(name.unwrap_or_else(|| "?".to_string()));
+    }
+    let inner = |default: &str| -> Result<String> {
+        match entry.attr_value(gimli::DW_AT_type)? {
+            Some(AttributeValue::UnitRef(offset)) => type_name(dwarf, unit, offset, depth + 1),
+            _ => Ok(default.to_string()),
+        }
+    };
+    Ok(match entry.tag() {
+        gimli::DW_TAG_pointer_type => format!("{}*", inner("void")?),
+        gimli::DW_TAG_reference_type => format!("{}&", inner("void")?),
+        gimli::DW_TAG_rvalue_reference_type => format!("{}&&", inner("void")?),
+        gimli::DW_TAG_const_type => format!("const {}", inner("void")?),
+        gimli::DW_TAG_volatile_type => format!("volatile {}", inner("void")?),
+        gimli::DW_TAG_restrict_type | gimli::DW_TAG_atomic_type => inner("void")?,
+        gimli::DW_TAG_subroutine_type => "<function>".to_string(),
+        gimli::DW_TAG_array_type => {
+            let mut result = inner("?")?;
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                if child.tag() != gimli::DW_TAG_subrange_type {
+                    continue;
+                }
+                let count = match child.attr_value(gimli::DW_AT_count)? {
+                    Some(value) => value.udata_value(),
+                    None => child
+                        .attr_value(gimli::DW_AT_upper_bound)?
+                        .and_then(|v| v.udata_value())
+                        .map(|upper| upper + 1),
+                };
+                match count {
+                    Some(count) => result.push_str(&format!("[{count}]")),
+                    None => result.push_str("[]"),
+                }
+            }
+            result
+        }
+        gimli::DW_TAG_unspecified_type => name.unwrap_or_else(|| "void".to_string()),
+        _ => name.unwrap_or_else(|| "<anonymous>".to_string()),
+    })
+}
+
+fn attr_string(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<'_>,
+    value: AttributeValue<Reader<'_>>,
+) -> Result<String> {
+    let string = dwarf.attr_string(unit, value)?;
+    Ok(gimli::Reader::to_string_lossy(&string)?.into_owned())
+}
+
+/// Returns the linkage name of a variable if present, otherwise its plain name.
+fn variable_name(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<'_>,
+    entry: &Entry<'_, '_>,
+) -> Result<Option<String>> {
+    for attr in [gimli::DW_AT_linkage_name, gimli::DW_AT_MIPS_linkage_name, gimli::DW_AT_name] {
+        if let Some(value) = entry.attr_value(attr)? {
+            return Ok(Some(attr_string(dwarf, unit, value)?));
+        }
+    }
+    Ok(None)
+}
+
+fn entry_name(dwarf: &Dwarf<'_>, unit: &Unit<'_>, entry: &Entry<'_, '_>) -> Result<Option<String>> {
+    match entry.attr_value(gimli::DW_AT_name)? {
+        Some(value) => Ok(Some(attr_string(dwarf, unit, value)?)),
+        None => Ok(None),
+    }
+}
+
+fn byte_size(entry: &Entry<'_, '_>) -> Result<Option<u64>> {
+    Ok(entry.attr_value(gimli::DW_AT_byte_size)?.and_then(|v| v.udata_value()))
+}
+
+fn resolve_type(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<'_>,
+    offset: UnitOffset,
+    types: &mut HashMap<UnitOffset, Arc<ObjDataType>>,
+    depth: usize,
+) -> Result<Arc<ObjDataType>> {
+    if let Some(ty) = types.get(&offset) {
+        return Ok(ty.clone());
+    }
+    let entry = unit.entry(offset)?;
+    let name = entry_name(dwarf, unit, &entry)?;
+    let size = byte_size(&entry)?;
+    if depth > MAX_TYPE_DEPTH {
+        log::warn!("Type nesting too deep at {:?}", offset);
+        return Ok(Arc::new(ObjDataType::Opaque { name, size: size.unwrap_or(0) }));
+    }
+    let inner_type = |types: &mut HashMap<UnitOffset, Arc<ObjDataType>>| -> Result<_> {
+        match entry.attr_value(gimli::DW_AT_type)? {
+            Some(AttributeValue::UnitRef(offset)) => {
+                resolve_type(dwarf, unit, offset, types, depth + 1).map(Some)
+            }
+            _ => Ok(None),
+        }
+    };
+    let ty = match entry.tag() {
+        gimli::DW_TAG_base_type => {
+            let size = size.unwrap_or(0);
+            let encoding = match entry.attr_value(gimli::DW_AT_encoding)? {
+                Some(AttributeValue::Encoding(encoding)) => Some(encoding),
+                _ => None,
+            };
+            let kind = match encoding {
+                Some(gimli::DW_ATE_boolean) => Some(ObjBaseTypeKind::Bool),
+                Some(gimli::DW_ATE_signed_char | gimli::DW_ATE_unsigned_char) if size == 1 => {
+                    Some(ObjBaseTypeKind::Char)
+                }
+                Some(gimli::DW_ATE_signed | gimli::DW_ATE_signed_char) => {
+                    Some(ObjBaseTypeKind::Signed)
+                }
+                Some(gimli::DW_ATE_unsigned | gimli::DW_ATE_unsigned_char) => {
+                    Some(ObjBaseTypeKind::Unsigned)
+                }
+                Some(gimli::DW_ATE_float) if size == 4 || size == 8 => Some(ObjBaseTypeKind::Float),
+                _ => None,
+            };
+            match kind {
+                Some(kind) if size <= 8 => {
+                    ObjDataType::Base { name: name.unwrap_or_default(), kind, size }
+                }
+                _ => ObjDataType::Opaque { name, size },
+            }
+        }
+        gimli::DW_TAG_pointer_type
+        | gimli::DW_TAG_reference_type
+        | gimli::DW_TAG_rvalue_reference_type
+        | gimli::DW_TAG_ptr_to_member_type => {
+            ObjDataType::Pointer { size: size.unwrap_or(unit.encoding().address_size as u64) }
+        }
+        gimli::DW_TAG_typedef
+        | gimli::DW_TAG_const_type
+        | gimli::DW_TAG_volatile_type
+        | gimli::DW_TAG_restrict_type
+        | gimli::DW_TAG_atomic_type => match inner_type(types)? {
+            Some(ty) => {
+                types.insert(offset, ty.clone());
+                return Ok(ty);
+            }
+            None => ObjDataType::Opaque { name, size: 0 },
+        },
+        gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type => {
+            let mut members = Vec::new();
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                if !matches!(child.tag(), gimli::DW_TAG_member | gimli::DW_TAG_inheritance)
+                    || is_declaration(child)?
+                {
+                    // C++ static members are declarations without storage in the struct
+                    continue;
+                }
+                let Some(AttributeValue::UnitRef(type_offset)) =
+                    child.attr_value(gimli::DW_AT_type)?
+                else {
+                    continue;
+                };
+                let mut ty = resolve_type(dwarf, unit, type_offset, types, depth + 1)?;
+                let mut member_offset = member_location(unit, child)?;
+                if child.attr_value(gimli::DW_AT_bit_size)?.is_some() {
+                    // Bitfields share their storage unit with neighbouring fields
+                    let size = byte_size(child)?.unwrap_or_else(|| ty.size());
+                    // DWARF 4+ describes bitfields with a bit offset from the start of the struct
+                    if let Some(bit_offset) = child
+                        .attr_value(gimli::DW_AT_data_bit_offset)?
+                        .and_then(|v| v.udata_value())
+                    {
+                        member_offset = Some(bit_offset / 8 / size.max(1) * size);
+                    }
+                    ty = Arc::new(ObjDataType::Opaque { name: None, size });
+                }
+                // Skip members whose location can't be evaluated
+                let Some(member_offset) = member_offset else {
+                    continue;
+                };
+                let member_name = match entry_name(dwarf, unit, child)? {
+                    Some(name) => name,
+                    // Base classes are named after their type
+                    None => match ty.as_ref() {
+                        ObjDataType::Struct { name: Some(name), .. } => name.clone(),
+                        _ => format!("<{member_offset:#x}>"),
+                    },
+                };
+                // Merge bitfields sharing the same storage unit
+                if let (Some(prev), ObjDataType::Opaque { name: None, .. }) =
+                    (members.last_mut(), ty.as_ref())
+                {
+                    let prev: &mut ObjDataMember = prev;
+                    if prev.offset == member_offset
+                        && matches!(prev.ty.as_ref(), ObjDataType::Opaque { name: None, .. })
+                    {
+                        prev.name = format!("{}|{}", prev.name, member_name);
+                        continue;
+                    }
+                }
+                members.push(ObjDataMember { name: member_name, offset: member_offset, ty });
+            }
+            ObjDataType::Struct { name, size: size.unwrap_or(0), members }
+        }
+        gimli::DW_TAG_enumeration_type => {
+            let mut values = Vec::new();
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                if child.tag() != gimli::DW_TAG_enumerator {
+                    continue;
+                }
+                let (Some(name), Some(value)) = (
+                    entry_name(dwarf, unit, child)?,
+                    child.attr_value(gimli::DW_AT_const_value)?.and_then(|v| v.sdata_value()),
+                ) else {
+                    continue;
+                };
+                values.push((name, value));
+            }
+            let size = match size {
+                Some(size) => size,
+                None => inner_type(types)?.map(|ty| ty.size()).unwrap_or(4),
+            };
+            ObjDataType::Enum { name, size, values }
+        }
+        gimli::DW_TAG_array_type => {
+            let Some(mut element) = inner_type(types)? else {
+                return Ok(Arc::new(ObjDataType::Opaque { name, size: size.unwrap_or(0) }));
+            };
+            let mut counts = Vec::new();
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                if child.tag() != gimli::DW_TAG_subrange_type {
+                    continue;
+                }
+                let count = match child.attr_value(gimli::DW_AT_count)? {
+                    Some(value) => value.udata_value(),
+                    None => child
+                        .attr_value(gimli::DW_AT_upper_bound)?
+                        .and_then(|v| v.udata_value())
+                        .map(|upper| upper + 1),
+                };
+                // Flexible array members have no bounds
+                counts.push(count.unwrap_or(0));
+            }
+            if counts.is_empty() {
+                counts.push(0);
+            }
+            // Multidimensional arrays are nested from the innermost dimension outwards
+            for count in counts.into_iter().rev() {
+                element = Arc::new(ObjDataType::Array { element, count });
+            }
+            types.insert(offset, element.clone());
+            return Ok(element);
+        }
+        _ => ObjDataType::Opaque { name, size: size.unwrap_or(0) },
+    };
+    let ty = Arc::new(ty);
+    types.insert(offset, ty.clone());
+    Ok(ty)
+}
+
+fn is_declaration(entry: &Entry<'_, '_>) -> Result<bool> {
+    Ok(entry.attr_value(gimli::DW_AT_declaration)?.is_some())
+}
+
+/// Reads `DW_AT_data_member_location`, which is either a constant or
+/// (in DWARF 2 and 3) a `DW_OP_plus_uconst` expression.
+fn member_location(unit: &Unit<'_>, entry: &Entry<'_, '_>) -> Result<Option<u64>> {
+    let Some(value) = entry.attr_value(gimli::DW_AT_data_member_location)? else {
+        // Union members have no location, and neither do static members
+        return Ok(if entry.tag() == gimli::DW_TAG_member && !is_declaration(entry)? {
+            Some(0)
+        } else {
+            None
+        });
+    };
+    if let Some(offset) = value.udata_value() {
+        return Ok(Some(offset));
+    }
+    let Some(mut expression) = value.exprloc_value() else {
+        return Ok(None);
+    };
+    match Operation::parse(&mut expression.0, unit.encoding())? {
+        Operation::PlusConstant { value } => Ok(Some(value)),
+        Operation::UnsignedConstant { value } => Ok(Some(value)),
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,559 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    config::{ProjectConfig, ProjectObject, TargetRegion},
+    obj::{ObjInfo, ObjSymbolKind},
+};
+
+/// The format of a linker map, detected from its contents.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MapFormat {
+    /// `nm --print-size` output
+    Nm,
+    /// CodeWarrior (mwld) `.MAP`
+    CodeWarrior,
+    /// GNU ld `-Map`
+    Gnu,
+    /// MSVC link `/MAP`
+    Msvc,
+}
+
+/// A symbol read from a linker map.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MapSymbol {
+    pub name: String,
+    pub address: u64,
+    /// Symbol size, or 0 if unknown
+    pub size: u64,
+    pub kind: ObjSymbolKind,
+    /// Output section containing the symbol
+    pub section: Option<String>,
+    /// Object that defined the symbol, as written in the map (e.g. `os.a __start.c`)
+    pub unit: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LinkerMap {
+    pub format: MapFormat,
+    /// File the map was read from, if any
+    pub path: Option<PathBuf>,
+    /// Symbols sorted by address
+    pub symbols: Vec<MapSymbol>,
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+impl LinkerMap {
+    fn new(format: MapFormat, mut symbols: Vec<MapSymbol>) -> Self {
+        symbols.sort_by_key(|s| s.address);
+        let mut by_name = HashMap::<String, Vec<usize>>::new();
+        for (i, symbol) in symbols.iter().enumerate() {
+            by_name.entry(symbol.name.clone()).or_default().push(i);
+        }
+        Self { format, path: None, symbols, by_name }
+    }
+
+    /// Returns the units in the map, in order of their first symbol.
+    pub fn units(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.symbols
+            .iter()
+            .filter_map(|s| s.unit.as_deref())
+            .filter(|unit| seen.insert(*unit))
+            .collect()
+    }
+
+    /// Finds a symbol by name. Names defined by more than one unit (e.g. local statics) only
+    /// resolve within the given unit.
+    pub fn find_symbol(&self, name: &str, unit: Option<&str>) -> Option<&MapSymbol> {
+        let indices = self.by_name.get(name)?;
+        if let Some(unit) = unit {
+            if let Some(&i) =
+                indices.iter().find(|&&i| self.symbols[i].unit.as_deref() == Some(unit))
+            {
+                return Some(&self.symbols[i]);
+            }
+        }
+        match indices.as_slice() {
+            [i] => Some(&self.symbols[*i]),
+            _ => None,
+        }
+    }
+
+    /// Finds the symbol starting at an address.
+    pub fn symbol_at(&self, address: u64) -> Option<&MapSymbol> {
+        let idx = self.symbols.partition_point(|s| s.address < address);
+        self.symbols.get(idx).filter(|s| s.address == address)
+    }
+
+    /// Renames the symbols of a stripped target object, matching them to the map by virtual
+    /// address. Symbols whose name already appears in the map are kept.
+    pub fn rename_stripped(&self, obj: &mut ObjInfo) {
+        let mut renamed = HashMap::<String, String>::new();
+        for symbol in obj.sections.iter_mut().flat_map(|s| s.symbols.iter_mut()) {
+            let Some(address) = symbol.virtual_address else {
+                continue;
+            };
+            if self.by_name.contains_key(&symbol.name) {
+                continue;
+            }
+            let Some(map_symbol) = self.symbol_at(address) else {
+                continue;
+            };
+            let old_name = std::mem::replace(&mut symbol.name, map_symbol.name.clone());
+            symbol.demangled_name = obj.arch.demangle(&symbol.name);
+            renamed.insert(old_name, map_symbol.name.clone());
+        }
+        if renamed.is_empty() {
+            return;
+        }
+        // Relocations hold a copy of their target symbol
+        for reloc in obj.sections.iter_mut().flat_map(|s| s.relocations.iter_mut()) {
+            if let Some(name) = renamed.get(&reloc.target.name) {
+                reloc.target.name = name.clone();
+                reloc.target.demangled_name = obj.arch.demangle(name);
+            }
+        }
+    }
+
+    /// Fills in the virtual address of each symbol the object shares with the map.
+    pub fn annotate(&self, obj: &mut ObjInfo, unit: Option<&str>) {
+        for symbol in obj.sections.iter_mut().flat_map(|s| s.symbols.iter_mut()) {
+            if symbol.virtual_address.is_none() {
+                symbol.virtual_address = self.find_symbol(&symbol.name, unit).map(|s| s.address);
+            }
+        }
+    }
+
+    /// Applies the map to a unit's objects: stripped target symbols are renamed, and virtual
+    /// addresses are filled in for both objects.
+    pub fn apply(
+        &self,
+        target: Option<&mut ObjInfo>,
+        base: Option<&mut ObjInfo>,
+        unit: Option<&str>,
+    ) {
+        if let Some(target) = target {
+            self.rename_stripped(target);
+            self.annotate(target, unit);
+        }
+        if let Some(base) = base {
+            self.annotate(base, unit);
+        }
+    }
+}
+
+/// Reads a linker map or symbol map, detecting its format.
+pub fn read_linker_map(path: &Path) -> Result<LinkerMap> {
+    let data =
+        fs::read(path).with_context(|| format!("Failed to read linker map {}", path.display()))?;
+    // MSVC and CodeWarrior maps aren't necessarily UTF-8
+    let text = String::from_utf8_lossy(&data);
+    let mut map = parse_linker_map(&text)
+        .with_context(|| format!("Failed to parse linker map {}", path.display()))?;
+    map.path = Some(path.to_path_buf());
+    Ok(map)
+}
+
+/// Parses a linker map, detecting its format.
+pub fn parse_linker_map(text: &str) -> Result<LinkerMap> {
+    let format = if text.contains("Linker script and memory map") {
+        MapFormat::Gnu
+    } else if text.contains("Publics by Value") {
+        MapFormat::Msvc
+    } else if text.lines().any(|l| l.trim_end().ends_with(" section layout")) {
+        MapFormat::CodeWarrior
+    } else {
+        MapFormat::Nm
+    };
+    let symbols = match format {
+        MapFormat::Nm => parse_nm(text)?,
+        MapFormat::CodeWarrior => parse_codewarrior(text),
+        MapFormat::Gnu => parse_gnu(text),
+        MapFormat::Msvc => parse_msvc(text),
+    };
+    Ok(LinkerMap::new(format, symbols))
+}
+
+/// Converts a unit as written in a linker map into a project unit name. Archive members are
+/// prefixed with the archive name, so `os.a __start.c` (CodeWarrior), `os.a(__start.o)`
+/// (GNU ld) and `os:__start.obj` (MSVC) become `os/__start`.
+pub fn unit_name(unit: &str) -> String {
+    let unit = unit.replace('\\', "/");
+    let (archive, member) =
+        if let Some((archive, member)) = unit.strip_suffix(')').and_then(|u| u.split_once('(')) {
+            (Some(archive), member)
+        } else if let Some((archive, member)) = unit.split_once(' ') {
+            (Some(archive), member)
+        } else if let Some((archive, member)) = unit.split_once(':').filter(|(a, _)| a.len() > 1) {
+            // Skip drive letters
+            (Some(archive), member)
+        } else {
+            (None, unit.as_str())
+        };
+    let member = member.trim().trim_start_matches("./");
+    let member = Path::new(member).with_extension("");
+    let member = member.to_string_lossy();
+    match archive.and_then(|a| Path::new(a.trim()).file_stem()) {
+        Some(archive) => format!("{}/{}", archive.to_string_lossy(), member),
+        None => member.into_owned(),
+    }
+}
+
+/// Reads the project's linker map, if one is configured.
+pub fn read_project_map(config: &ProjectConfig, project_dir: &Path) -> Result<Option<LinkerMap>> {
+    config.linker_map.as_ref().map(|m| read_linker_map(&project_dir.join(&m.path))).transpose()
+}
+
+/// Returns a unit for each object in the project's linker map that isn't already configured.
+/// Each one diffs its region of the linked executable against a base object named after the
+/// unit in `base_dir`. Returns nothing if the map has no linked executable configured.
+pub fn discover_units(map: &LinkerMap, config: &ProjectConfig) -> Vec<ProjectObject> {
+    let Some(map_config) = &config.linker_map else {
+        return Vec::new();
+    };
+    let Some(target_path) = &map_config.target_path else {
+        return Vec::new();
+    };
+    let mut result = Vec::new();
+    for unit in map.units() {
+        let name = unit_name(unit);
+        if config.units().iter().any(|u| {
+            u.name() == name
+                || u.target_region.as_ref().and_then(|r| r.unit.as_deref()) == Some(unit)
+        }) {
+            continue;
+        }
+        result.push(ProjectObject {
+            base_path: config.base_dir.as_ref().map(|dir| dir.join(format!("{name}.o"))),
+            name: Some(name),
+            target_path: Some(target_path.clone()),
+            target_region: Some(TargetRegion {
+                symbols: Some(map_config.path.clone()),
+                unit: Some(unit.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
+    result
+}
+
+fn parse_hex(s: &str) -> Option<u64> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.is_empty() {
+        return None;
+    }
+    u64::from_str_radix(s, 16).ok()
+}
+
+fn section_symbol_kind(section: &str) -> ObjSymbolKind {
+    let lower = section.to_ascii_lowercase();
+    if lower.contains("text") || lower == ".init" || lower == ".fini" || lower == "code" {
+        ObjSymbolKind::Function
+    } else {
+        ObjSymbolKind::Object
+    }
+}
+
+/// Parses `nm --print-size` output (`address size type name`). The type column is optional,
+/// and addresses and sizes are hexadecimal. Lines starting with `#` are ignored.
+fn parse_nm(text: &str) -> Result<Vec<MapSymbol>> {
+    let mut symbols = Vec::new();
+    for (line_num, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let (address, size, kind, name) = match fields.as_slice() {
+            [address, size, name] => (address, size, ObjSymbolKind::Unknown, name),
+            [address, size, kind, name] => (address, size, nm_symbol_kind(kind), name),
+            _ => bail!("Line {}: expected `address size [type] name`", line_num + 1),
+        };
+        let address = parse_hex(address)
+            .with_context(|| format!("Line {}: invalid address {address}", line_num + 1))?;
+        let size = parse_hex(size)
+            .with_context(|| format!("Line {}: invalid size {size}", line_num + 1))?;
+        symbols.push(MapSymbol {
+            name: name.to_string(),
+            address,
+            size,
+            kind,
+            section: None,
+            unit: None,
+        });
+    }
+    Ok(symbols)
+}
+
+fn nm_symbol_kind(kind: &str) -> ObjSymbolKind {
+    match kind {
+        "T" | "t" | "W" | "w" => ObjSymbolKind::Function,
+        "D" | "d" | "B" | "b" | "R" | "r" | "V" | "v" | "G" | "g" | "S" | "s" => {
+            ObjSymbolKind::Object
+        }
+        _ => ObjSymbolKind::Unknown,
+    }
+}
+
+/// Parses the section layouts of a CodeWarrior map. Entries are either
+/// `offset size address alignment name unit` or, since CodeWarrior 2.7,
+/// `offset size address file_offset alignment name unit`.
+fn parse_codewarrior(text: &str) -> Vec<MapSymbol> {
+    let mut symbols = Vec::new();
+    let mut section = None;
+    for line in text.lines() {
+        if let Some(name) = line.trim().strip_suffix(" section layout") {
+            section = Some(name);
+            continue;
+        }
+        let Some(section) = section else {
+            continue;
+        };
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        // Skips headers, `UNUSED` entries and the memory map
+        let (Some(_), Some(size), Some(address)) = (
+            tokens.first().and_then(|s| parse_hex(s)),
+            tokens.get(1).and_then(|s| parse_hex(s)),
+            tokens.get(2).and_then(|s| parse_hex(s)),
+        ) else {
+            continue;
+        };
+        let name_idx = if tokens.len() >= 7
+            && tokens[3].len() == 8
+            && parse_hex(tokens[3]).is_some()
+            && tokens[4].parse::<u32>().is_ok()
+        {
+            5
+        } else {
+            4
+        };
+        let (Some(name), Some(unit)) = (tokens.get(name_idx), tokens.get(name_idx + 1..)) else {
+            continue;
+        };
+        // Skip the section entry for each unit and linker fill
+        if unit.is_empty() || *name == section || name.starts_with('*') {
+            continue;
+        }
+        symbols.push(MapSymbol {
+            name: name.to_string(),
+            address,
+            size,
+            kind: section_symbol_kind(section),
+            section: Some(section.to_string()),
+            unit: Some(unit.join(" ")),
+        });
+    }
+    symbols
+}
+
+/// An input section of a GNU ld map, with the symbols it defines.
+struct GnuInputSection<'a> {
+    section: &'a str,
+    address: u64,
+    size: u64,
+    unit: String,
+    symbols: Vec<(u64, &'a str)>,
+}
+
+impl GnuInputSection<'_> {
+    /// Symbols sizes extend to the next symbol or the end of the input section.
+    fn flush(mut self, out: &mut Vec<MapSymbol>) {
+        self.symbols.sort_by_key(|&(address, _)| address);
+        let end = self.address + self.size;
+        for (i, &(address, name)) in self.symbols.iter().enumerate() {
+            let next = self.symbols.get(i + 1).map_or(end, |&(next, _)| next);
+            out.push(MapSymbol {
+                name: name.to_string(),
+                address,
+                size: next - address,
+                kind: section_symbol_kind(self.section),
+                section: Some(self.section.to_string()),
+                unit: Some(self.unit.clone()),
+            });
+        }
+    }
+}
+
+/// Parses the memory map of a GNU ld map. Input sections are listed as
+/// ` .text 0xaddress 0xsize unit`, with long section names wrapped onto the next line, and
+/// followed by their symbols as `0xaddress name`.
+fn parse_gnu(text: &str) -> Vec<MapSymbol> {
+    let mut symbols = Vec::new();
+    let mut output_section = None;
+    let mut input: Option<GnuInputSection> = None;
+    let mut wrapped = false;
+    for line in text.lines().skip_while(|l| !l.starts_with("Linker script and memory map")) {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let Some(&first) = tokens.first() else {
+            continue;
+        };
+        if !line.starts_with(' ') {
+            // Output section
+            if let Some(input) = input.take() {
+                input.flush(&mut symbols);
+            }
+            output_section = Some(first);
+            wrapped = false;
+            continue;
+        }
+        let Some(section) = output_section else {
+            continue;
+        };
+        let is_input = !line.starts_with("  ") && !first.starts_with('*');
+        let input_fields = if is_input {
+            wrapped = tokens.len() == 1;
+            tokens.get(1..)
+        } else if wrapped {
+            wrapped = false;
+            Some(&tokens[..])
+        } else {
+            None
+        };
+        if let Some(fields) = input_fields {
+            if let [address, size, unit @ ..] = fields {
+                if let (Some(address), Some(size), false) =
+                    (parse_hex(address), parse_hex(size), unit.is_empty())
+                {
+                    if let Some(input) = input.take() {
+                        input.flush(&mut symbols);
+                    }
+                    if size != 0 {
+                        input = Some(GnuInputSection {
+                            section,
+                            address,
+                            size,
+                            unit: unit.join(" "),
+                            symbols: Vec::new(),
+                        });
+                    }
+                }
+            }
+            continue;
+        }
+        // Symbols have exactly two fields; assignments and fill have more
+        if let ([address, name], Some(input)) = (&tokens[..], input.as_mut()) {
+            if let Some(address) = parse_hex(address) {
+                if address >= input.address && address < input.address + input.size {
+                    input.symbols.push((address, name));
+                }
+            }
+        }
+    }
+    if let Some(input) = input {
+        input.flush(&mut symbols);
+    }
+    symbols
+}
+
+/// Parses the public and static symbols of an MSVC map, as
+/// `section:offset name address [f] [i] lib:object`. Symbol sizes extend to the next symbol in
+/// the section, or the end of its section contribution.
+fn parse_msvc(text: &str) -> Vec<MapSymbol> {
+    struct Contribution<'a> {
+        section: u32,
+        start: u64,
+        end: u64,
+        name: &'a str,
+        class: &'a str,
+    }
+    struct Public<'a> {
+        section: u32,
+        offset: u64,
+        name: &'a str,
+        address: u64,
+        function: bool,
+        unit: &'a str,
+    }
+    fn parse_section_offset(s: &str) -> Option<(u32, u64)> {
+        let (section, offset) = s.split_once(':')?;
+        Some((u32::from_str_radix(section, 16).ok()?, parse_hex(offset)?))
+    }
+
+    let mut contributions = Vec::new();
+    let mut publics = Vec::new();
+    let mut in_sections = false;
+    let mut in_publics = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("Start") && trimmed.ends_with("Class") {
+            in_sections = true;
+            continue;
+        }
+        if trimmed.contains("Publics by Value") || trimmed == "Static symbols" {
+            in_sections = false;
+            in_publics = true;
+            continue;
+        }
+        let tokens = trimmed.split_whitespace().collect::<Vec<_>>();
+        let Some((section, offset)) = tokens.first().and_then(|s| parse_section_offset(s)) else {
+            continue;
+        };
+        if in_sections {
+            if let [_, length, name, class] = tokens[..] {
+                if let Some(length) = length.strip_suffix('H').and_then(parse_hex) {
+                    contributions.push(Contribution {
+                        section,
+                        start: offset,
+                        end: offset + length,
+                        name,
+                        class,
+                    });
+                }
+            }
+        } else if in_publics && section != 0 {
+            let [_, name, address, flags @ .., unit] = &tokens[..] else {
+                continue;
+            };
+            let Some(address) = parse_hex(address) else {
+                continue;
+            };
+            if unit.starts_with('<') {
+                continue;
+            }
+            publics.push(Public {
+                section,
+                offset,
+                name,
+                address,
+                function: flags.contains(&"f"),
+                unit,
+            });
+        }
+    }
+
+    publics.sort_by_key(|p| (p.section, p.offset));
+    let mut symbols = Vec::with_capacity(publics.len());
+    for (i, public) in publics.iter().enumerate() {
+        let contribution = contributions.iter().find(|c| {
+            c.section == public.section && c.start <= public.offset && public.offset < c.end
+        });
+        let end = contribution.map(|c| c.end);
+        let next = publics
+            .get(i + 1)
+            .filter(|next| next.section == public.section)
+            .map(|next| next.offset)
+            .filter(|&next| end.map_or(true, |end| next <= end));
+        let size = next.or(end).map_or(0, |end| end - public.offset);
+        let kind = if public.function {
+            ObjSymbolKind::Function
+        } else {
+            contribution.map_or(ObjSymbolKind::Unknown, |c| section_symbol_kind(c.class))
+        };
+        symbols.push(MapSymbol {
+            name: public.name.to_string(),
+            address: public.address,
+            size,
+            kind,
+            section: contribution.map(|c| c.name.to_string()),
+            unit: Some(public.unit.to_string()),
+        });
+    }
+    symbols
+}
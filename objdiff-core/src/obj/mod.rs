@@ -1,11 +1,14 @@
+#[cfg(feature = "dwarf")]
+mod dwarf;
+pub mod map;
 pub mod read;
 pub mod split_meta;
 
-use std::{borrow::Cow, collections::BTreeMap, fmt, path::PathBuf};
+use std::{borrow::Cow, collections::BTreeMap, fmt, path::PathBuf, sync::Arc};
 
 use filetime::FileTime;
 use flagset::{flags, FlagSet};
-use object::RelocationFlags;
+use object::{Endianness, RelocationFlags};
 use split_meta::SplitMeta;
 
 use crate::{arch::ObjArch, util::ReallySigned};
@@ -148,10 +151,154 @@ pub struct ObjSymbol {
     /// Original index in object symbol table
     pub original_index: Option<usize>,
     pub bytes: Vec<u8>,
+    /// Data type (from DWARF debug info)
+    pub data_type: Option<Arc<ObjDataType>>,
+    /// Function metadata (from DWARF debug info)
+    pub function_info: Option<Arc<ObjFunctionInfo>>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ObjBaseTypeKind {
+    Bool,
+    Char,
+    Signed,
+    Unsigned,
+    Float,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjDataType {
+    Base {
+        name: String,
+        kind: ObjBaseTypeKind,
+        size: u64,
+    },
+    Pointer {
+        size: u64,
+    },
+    Enum {
+        name: Option<String>,
+        size: u64,
+        values: Vec<(String, i64)>,
+    },
+    Struct {
+        name: Option<String>,
+        size: u64,
+        members: Vec<ObjDataMember>,
+    },
+    Array {
+        element: Arc<ObjDataType>,
+        count: u64,
+    },
+    /// Unions, bitfields and anything else that can't be broken down further
+    Opaque {
+        name: Option<String>,
+        size: u64,
+    },
+}
+
+impl ObjDataType {
+    pub fn size(&self) -> u64 {
+        match self {
+            ObjDataType::Base { size, .. }
+            | ObjDataType::Pointer { size }
+            | ObjDataType::Enum { size, .. }
+            | ObjDataType::Struct { size, .. }
+            | ObjDataType::Opaque { size, .. } => *size,
+            ObjDataType::Array { element, count } => element.size().saturating_mul(*count),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjDataMember {
+    pub name: String,
+    /// Offset from the start of the parent type
+    pub offset: u64,
+    pub ty: Arc<ObjDataType>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ObjFunctionInfo {
+    /// Return type name, or `None` for `void`
+    pub return_type: Option<String>,
+    pub params: Vec<ObjVariable>,
+    pub locals: Vec<ObjVariable>,
+    /// Inlined call sites, outermost first
+    pub inlined: Vec<ObjInlinedRange>,
+    /// Canonical frame address rules (from .debug_frame), used to resolve
+    /// [`ObjVariableLocation::FrameOffset`]
+    pub cfa: Vec<ObjCfaRange>,
+}
+
+impl ObjFunctionInfo {
+    /// Resolves a variable location to a register and optional offset at the given address.
+    pub fn resolve_location(
+        &self,
+        location: &ObjVariableLocation,
+        address: u64,
+    ) -> Option<(u16, Option<i64>)> {
+        match *location {
+            ObjVariableLocation::Register(register) => Some((register, None)),
+            ObjVariableLocation::RegisterOffset { register, offset } => {
+                Some((register, Some(offset)))
+            }
+            ObjVariableLocation::FrameOffset(offset) => self
+                .cfa
+                .iter()
+                .find(|cfa| address >= cfa.start && address < cfa.end)
+                .map(|cfa| (cfa.register, Some(cfa.offset + offset))),
+        }
+    }
+
+    /// Returns the inlined call sites containing the given address, outermost first.
+    pub fn inlined_at(&self, address: u64) -> impl Iterator<Item = &ObjInlinedRange> {
+        self.inlined.iter().filter(move |range| address >= range.start && address < range.end)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjVariable {
+    pub name: String,
+    pub ty: Option<String>,
+    pub location: Option<ObjVariableLocation>,
+}
+
+/// Variable location, using DWARF register numbers
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ObjVariableLocation {
+    /// Held in a register
+    Register(u16),
+    /// In memory, at an offset from a register
+    RegisterOffset { register: u16, offset: i64 },
+    /// In memory, at an offset from the canonical frame address
+    FrameOffset(i64),
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjInlinedRange {
+    /// Name of the inlined function
+    pub name: String,
+    pub start: u64,
+    pub end: u64,
+    /// Nesting depth, 0 for call sites in the function itself
+    pub depth: u32,
+    /// Line of the call site
+    pub call_line: Option<u32>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ObjCfaRange {
+    pub start: u64,
+    pub end: u64,
+    /// DWARF register number
+    pub register: u16,
+    pub offset: i64,
 }
 
 pub struct ObjInfo {
     pub arch: Box<dyn ObjArch>,
+    pub endianness: Endianness,
     pub path: Option<PathBuf>,
     pub timestamp: Option<FileTime>,
     pub sections: Vec<ObjSection>,
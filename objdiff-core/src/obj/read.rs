@@ -1,9 +1,10 @@
 use std::{
+    borrow::Cow,
     collections::{HashMap, HashSet},
     fs,
     io::Cursor,
     mem::size_of,
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
@@ -12,15 +13,21 @@ use flagset::Flags;
 use object::{
     endian::LittleEndian as LE,
     pe::{ImageAuxSymbolFunctionBeginEnd, ImageLinenumber},
-    read::coff::{CoffFile, CoffHeader, ImageSymbol},
-    BinaryFormat, File, Object, ObjectSection, ObjectSymbol, RelocationTarget, Section,
-    SectionIndex, SectionKind, Symbol, SymbolIndex, SymbolKind, SymbolScope,
+    read::{
+        archive::ArchiveFile,
+        coff::{CoffFile, CoffHeader, ImageSymbol},
+    },
+    BinaryFormat, File, FileKind, Object, ObjectKind, ObjectSection, ObjectSymbol, RelocationFlags,
+    RelocationTarget, Section, SectionIndex, SectionKind, Symbol, SymbolIndex, SymbolKind,
+    SymbolScope,
 };
 
 use crate::{
     arch::{new_arch, ObjArch},
+    config::TargetRegion,
     diff::DiffObjConfig,
     obj::{
+        map::{read_linker_map, LinkerMap},
         split_meta::{SplitMeta, SPLITMETA_SECTION},
         ObjInfo, ObjReloc, ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags,
         ObjSymbolKind,
@@ -31,8 +38,11 @@ use crate::{
 fn to_obj_section_kind(kind: SectionKind) -> Option<ObjSectionKind> {
     match kind {
         SectionKind::Text => Some(ObjSectionKind::Code),
-        SectionKind::Data | SectionKind::ReadOnlyData => Some(ObjSectionKind::Data),
-        SectionKind::UninitializedData => Some(ObjSectionKind::Bss),
+        SectionKind::Data
+        | SectionKind::ReadOnlyData
+        | SectionKind::ReadOnlyDataWithRel
+        | SectionKind::ReadOnlyString => Some(ObjSectionKind::Data),
+        SectionKind::UninitializedData | SectionKind::Common => Some(ObjSectionKind::Bss),
         _ => None,
     }
 }
@@ -77,7 +87,12 @@ fn to_obj_symbol(
     } else {
         address
     };
-    let demangled_name = arch.demangle(name);
+    // Mach-O prefixes C symbols with an underscore, including mangled C++ names
+    let demangled_name = if obj_file.format() == BinaryFormat::MachO {
+        arch.demangle(name.strip_prefix('_').unwrap_or(name))
+    } else {
+        arch.demangle(name)
+    };
     // Find the virtual address for the symbol if available
     let virtual_address = split_meta
         .and_then(|m| m.virtual_addresses.as_ref())
@@ -112,6 +127,8 @@ fn to_obj_symbol(
         virtual_address,
         original_index: Some(symbol.index().0),
         bytes: bytes.to_vec(),
+        data_type: None,
+        function_info: None,
     })
 }
 
@@ -124,7 +141,7 @@ fn filter_sections(obj_file: &File<'_>, split_meta: Option<&SplitMeta>) -> Resul
         let Some(kind) = to_obj_section_kind(section.kind()) else {
             continue;
         };
-        let name = section.name().context("Failed to process section name")?;
+        let name = section_name(&section)?;
         let data = section.uncompressed_data().context("Failed to read section data")?;
 
         // Find the virtual address for the section symbol if available
@@ -138,7 +155,7 @@ fn filter_sections(obj_file: &File<'_>, split_meta: Option<&SplitMeta>) -> Resul
         });
 
         result.push(ObjSection {
-            name: name.to_string(),
+            name,
             kind,
             address: section.address(),
             size: section.size(),
@@ -154,6 +171,15 @@ fn filter_sections(obj_file: &File<'_>, split_meta: Option<&SplitMeta>) -> Resul
     Ok(result)
 }
 
+/// Returns the name of a section, qualified by its segment for Mach-O (e.g. `__TEXT,__text`).
+fn section_name(section: &Section<'_, '_>) -> Result<String> {
+    let name = section.name().context("Failed to process section name")?;
+    Ok(match section.segment_name().context("Failed to process segment name")? {
+        Some(segment) => format!("{segment},{name}"),
+        None => name.to_string(),
+    })
+}
+
 fn symbols_by_section(
     arch: &dyn ObjArch,
     obj_file: &File<'_>,
@@ -167,7 +193,13 @@ fn symbols_by_section(
         if symbol.kind() == SymbolKind::Section {
             continue;
         }
-        if symbol.is_local() && section.kind == ObjSectionKind::Code {
+        if obj_file.format() == BinaryFormat::MachO {
+            // Mach-O symbols have no size, so only skip assembler temporaries
+            let name = symbol.name().context("Failed to process symbol name")?;
+            if name.starts_with("ltmp") || name.starts_with('L') {
+                continue;
+            }
+        } else if symbol.is_local() && section.kind == ObjSectionKind::Code {
             // TODO strip local syms in diff?
             let name = symbol.name().context("Failed to process symbol name")?;
             if symbol.size() == 0 || name.starts_with("lbl_") {
@@ -176,6 +208,17 @@ fn symbols_by_section(
         }
         result.push(to_obj_symbol(arch, obj_file, symbol, split_meta)?);
     }
+    finish_section_symbols(section, &mut result, name_counts);
+    Ok(result)
+}
+
+/// Sorts a section's symbols, infers sizes for symbols without one, and adds a dummy symbol
+/// covering the section if it has no symbols.
+fn finish_section_symbols(
+    section: &ObjSection,
+    result: &mut Vec<ObjSymbol>,
+    name_counts: &mut HashMap<String, u32>,
+) {
     result.sort_by(|a, b| a.address.cmp(&b.address).then(a.size.cmp(&b.size)));
     let mut iter = result.iter_mut().peekable();
     while let Some(symbol) = iter.next() {
@@ -218,9 +261,10 @@ fn symbols_by_section(
             virtual_address: None,
             original_index: None,
             bytes: Vec::new(),
+            data_type: None,
+            function_info: None,
         });
     }
-    Ok(result)
 }
 
 fn common_symbols(
@@ -291,7 +335,7 @@ fn find_section_symbol(
     }
     // Fallback to section symbol
     Ok(ObjSymbol {
-        name: section.name()?.to_string(),
+        name: section_name(section)?,
         demangled_name: None,
         address: section.address(),
         section_address: 0,
@@ -303,6 +347,8 @@ fn find_section_symbol(
         virtual_address: None,
         original_index: None,
         bytes: Vec::new(),
+        data_type: None,
+        function_info: None,
     })
 }
 
@@ -315,7 +361,18 @@ fn relocations_by_section(
 ) -> Result<Vec<ObjReloc>> {
     let obj_section = obj_file.section_by_index(SectionIndex(section.orig_index))?;
     let mut relocations = Vec::<ObjReloc>::new();
-    for (address, reloc) in obj_section.relocations() {
+    for (offset, reloc) in obj_section.relocations() {
+        let flags = reloc.flags(); // TODO validate reloc here?
+        let mut addend = if reloc.has_implicit_addend() {
+            arch.implcit_addend(obj_file, section, offset, &reloc)?
+        } else {
+            reloc.addend()
+        };
+        // Mach-O relocation offsets are relative to the section, unlike its symbol addresses
+        let address = match obj_file.format() {
+            BinaryFormat::MachO => obj_section.address() + offset,
+            _ => offset,
+        };
         let symbol = match reloc.target() {
             RelocationTarget::Symbol(idx) => {
                 if idx.0 == u32::MAX as usize {
@@ -332,18 +389,42 @@ fn relocations_by_section(
                 };
                 symbol
             }
+            RelocationTarget::Section(section_index) => {
+                // Mach-O local relocations target a section, and the implicit addend holds
+                // the target address (or the offset from the next instruction if PC-relative)
+                let Ok(target_section) = obj_file.section_by_index(section_index) else {
+                    log::warn!(
+                        "Failed to locate relocation {:#x} target section {}",
+                        address,
+                        section_index.0
+                    );
+                    continue;
+                };
+                let target_address = match flags {
+                    RelocationFlags::MachO { r_pcrel: true, .. } => {
+                        address as i64 + addend - reloc.addend()
+                    }
+                    _ => addend,
+                };
+                ensure!(target_address >= 0, "Negative target address in reloc: {target_address}");
+                let target = find_section_symbol(
+                    arch,
+                    obj_file,
+                    &target_section,
+                    &section_symbols[section_index.0],
+                    target_address as u64,
+                    split_meta,
+                )?;
+                addend = target_address - target.address as i64;
+                relocations.push(ObjReloc { flags, address, target, addend });
+                continue;
+            }
             RelocationTarget::Absolute => {
                 log::warn!("Ignoring absolute relocation @ {}:{:#x}", section.name, address);
                 continue;
             }
             _ => bail!("Unhandled relocation target: {:?}", reloc.target()),
         };
-        let flags = reloc.flags(); // TODO validate reloc here?
-        let mut addend = if reloc.has_implicit_addend() {
-            arch.implcit_addend(obj_file, section, address, &reloc)?
-        } else {
-            reloc.addend()
-        };
         let target = match symbol.kind() {
             SymbolKind::Text | SymbolKind::Data | SymbolKind::Label | SymbolKind::Unknown => {
                 to_obj_symbol(arch, obj_file, &symbol, split_meta)?
@@ -594,6 +675,8 @@ fn update_combined_symbol(symbol: ObjSymbol, address_change: i64) -> Result<ObjS
         },
         original_index: symbol.original_index,
         bytes: symbol.bytes,
+        data_type: symbol.data_type,
+        function_info: symbol.function_info,
     })
 }
 
@@ -677,18 +760,317 @@ fn combine_data_sections(sections: &mut Vec<ObjSection>) -> Result<()> {
     Ok(())
 }
 
+/// Splits an archive member selector (`libfoo.a(bar.o)`) into the archive path and member name.
+/// Paths without a selector, or that exist as-is, are returned unchanged.
+pub fn split_member_path(path: &Path) -> (&Path, Option<&str>) {
+    let Some(s) = path.to_str() else {
+        return (path, None);
+    };
+    if let Some((archive, member)) = s.strip_suffix(')').and_then(|s| s.rsplit_once('(')) {
+        if !archive.is_empty() && !member.is_empty() && !path.exists() {
+            return (Path::new(archive), Some(member));
+        }
+    }
+    (path, None)
+}
+
+/// Creates an object path selecting a member of an archive.
+pub fn member_path(archive_path: &Path, member: &str) -> PathBuf {
+    PathBuf::from(format!("{}({})", archive_path.display(), member))
+}
+
+/// Lists the members of an archive, or returns `None` if the file isn't an archive.
+pub fn archive_members(path: &Path) -> Result<Option<Vec<String>>> {
+    let file = fs::File::open(path)?;
+    let data = unsafe { memmap2::Mmap::map(&file) }?;
+    if FileKind::parse(&*data)? != FileKind::Archive {
+        return Ok(None);
+    }
+    let archive = ArchiveFile::parse(&*data)?;
+    let mut members = vec![];
+    for member in archive.members() {
+        members.push(String::from_utf8_lossy(member?.name()).into_owned());
+    }
+    Ok(Some(members))
+}
+
+/// Returns the object data to parse, selecting a member if the file is an archive.
+/// Without a member name, an archive must contain exactly one member.
+fn select_member<'a>(data: &'a [u8], member_name: Option<&str>, path: &Path) -> Result<&'a [u8]> {
+    if FileKind::parse(data)? != FileKind::Archive {
+        ensure!(member_name.is_none(), "{} is not an archive", path.display());
+        return Ok(data);
+    }
+    let archive = ArchiveFile::parse(data)?;
+    let members = archive.members().collect::<object::Result<Vec<_>>>()?;
+    if let Some(member_name) = member_name {
+        let member = members
+            .iter()
+            .find(|member| {
+                let name = String::from_utf8_lossy(member.name());
+                // Archives built by MSVC store the full path of each member
+                name == member_name || name.rsplit(['/', '\\']).next() == Some(member_name)
+            })
+            .ok_or_else(|| {
+                anyhow!("Member {} not found in archive {}", member_name, path.display())
+            })?;
+        return Ok(member.data(data)?);
+    }
+    match members.as_slice() {
+        [member] => Ok(member.data(data)?),
+        [] => bail!("Archive {} is empty", path.display()),
+        members => bail!(
+            "Archive {} contains multiple members, select one with {}: {}",
+            path.display(),
+            member_path(path, "<member>").display(),
+            members
+                .iter()
+                .map(|m| String::from_utf8_lossy(m.name()).into_owned())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
 pub fn read(obj_path: &Path, config: &DiffObjConfig) -> Result<ObjInfo> {
+    let (file_path, member_name) = split_member_path(obj_path);
     let (data, timestamp) = {
-        let file = fs::File::open(obj_path)?;
+        let file = fs::File::open(file_path)?;
         let timestamp = FileTime::from_last_modification_time(&file.metadata()?);
         (unsafe { memmap2::Mmap::map(&file) }?, timestamp)
     };
-    let mut obj = parse(&data, config)?;
+    let mut obj = parse(select_member(&data, member_name, file_path)?, config)?;
     obj.path = Some(obj_path.to_owned());
     obj.timestamp = Some(timestamp);
     Ok(obj)
 }
 
+/// Reads the target object, either from an object file or from a region of a linked executable.
+/// See [`read_region`] for `linker_map`.
+pub fn read_target(
+    obj_path: &Path,
+    region: Option<&TargetRegion>,
+    linker_map: Option<&LinkerMap>,
+    config: &DiffObjConfig,
+) -> Result<ObjInfo> {
+    match region {
+        Some(region) => read_region(obj_path, region, linker_map, config),
+        None => read(obj_path, config),
+    }
+}
+
+/// Reads a region of a linked executable as if it were a relocatable object.
+///
+/// Sections are clipped to the address range, and symbols are taken from the symbol map if
+/// provided, otherwise from the executable's symbol table. With only a symbol map, each section
+/// is clipped to the symbols it contains. Executables have no relocations, so references to
+/// other symbols only match the base object with `relax_reloc_diffs` enabled.
+///
+/// `linker_map` is an already parsed map, such as the project's, which is used instead of reading
+/// the symbol map again if it was read from the same file.
+pub fn read_region(
+    exe_path: &Path,
+    region: &TargetRegion,
+    linker_map: Option<&LinkerMap>,
+    config: &DiffObjConfig,
+) -> Result<ObjInfo> {
+    ensure!(
+        region.start.is_some() || region.end.is_some() || region.symbols.is_some(),
+        "Target region requires an address range or a symbol map"
+    );
+    let start = region.start.unwrap_or(0);
+    let end = region.end.unwrap_or(u64::MAX);
+    ensure!(start < end, "Invalid target region {start:#x}-{end:#x}");
+    let map_symbols = match &region.symbols {
+        Some(path) => {
+            let map = match linker_map {
+                Some(map) if map.path.as_deref() == Some(path.as_path()) => Cow::Borrowed(map),
+                _ => Cow::Owned(read_linker_map(path)?),
+            };
+            let symbols = map
+                .symbols
+                .iter()
+                .filter(|s| region.unit.is_none() || s.unit == region.unit)
+                .cloned()
+                .collect::<Vec<_>>();
+            if let Some(unit) = &region.unit {
+                ensure!(!symbols.is_empty(), "Unit {unit} not found in {}", path.display());
+            }
+            Some(symbols)
+        }
+        None => None,
+    };
+
+    let (data, timestamp) = {
+        let file = fs::File::open(exe_path)?;
+        let timestamp = FileTime::from_last_modification_time(&file.metadata()?);
+        (unsafe { memmap2::Mmap::map(&file) }?, timestamp)
+    };
+    let obj_file = File::parse(&*data)?;
+    ensure!(
+        obj_file.kind() != ObjectKind::Relocatable,
+        "{} is not a linked executable",
+        exe_path.display()
+    );
+    let arch = new_arch(&obj_file)?;
+
+    let mut sections = Vec::<ObjSection>::new();
+    let mut section_name_counts: HashMap<String, u32> = HashMap::new();
+    for section in obj_file.sections() {
+        if section.size() == 0 {
+            continue;
+        }
+        let Some(kind) = to_obj_section_kind(section.kind()) else {
+            continue;
+        };
+        let mut lo = start.max(section.address());
+        let mut hi = end.min(section.address() + section.size());
+        if let Some(map_symbols) = &map_symbols {
+            let (section_lo, section_hi) = (lo, hi);
+            let mut contained =
+                map_symbols.iter().filter(|s| s.address >= section_lo && s.address < section_hi);
+            let Some(first) = contained.next() else {
+                continue;
+            };
+            let last = contained.next_back().unwrap_or(first);
+            lo = first.address;
+            if last.size != 0 {
+                hi = hi.min(last.address + last.size);
+            }
+        }
+        if lo >= hi {
+            continue;
+        }
+        let data = match kind {
+            ObjSectionKind::Bss => Vec::new(),
+            _ => {
+                let data = section.uncompressed_data().context("Failed to read section data")?;
+                let offset = (lo - section.address()) as usize;
+                data.get(offset..offset + (hi - lo) as usize).unwrap_or_default().to_vec()
+            }
+        };
+        let mut obj_section = ObjSection {
+            name: section_name(&section)?,
+            kind,
+            address: lo,
+            size: hi - lo,
+            data,
+            orig_index: section.index().0,
+            symbols: Vec::new(),
+            relocations: Vec::new(),
+            virtual_address: Some(lo),
+            line_info: Default::default(),
+        };
+        let mut symbols = if let Some(map_symbols) = &map_symbols {
+            map_symbols
+                .iter()
+                .filter(|s| s.address >= lo && s.address < hi)
+                .map(|s| ObjSymbol {
+                    name: s.name.clone(),
+                    demangled_name: arch.demangle(&s.name),
+                    address: s.address,
+                    section_address: 0,
+                    size: s.size,
+                    size_known: s.size != 0,
+                    kind: s.kind,
+                    flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+                    orig_section_index: Some(section.index().0),
+                    virtual_address: None,
+                    original_index: None,
+                    bytes: Vec::new(),
+                    data_type: None,
+                    function_info: None,
+                })
+                .collect()
+        } else {
+            let mut section_symbols = obj_file
+                .symbols()
+                .filter(|s| {
+                    s.section_index() == Some(section.index())
+                        && s.kind() != SymbolKind::Section
+                        && (lo..hi).contains(&s.address())
+                        && !s.name().unwrap_or_default().is_empty()
+                        && !(s.is_local() && kind == ObjSectionKind::Code && s.size() == 0)
+                })
+                .collect::<Vec<_>>();
+            section_symbols.sort_by_key(|s| s.address());
+            section_symbols
+                .iter()
+                .map(|s| to_obj_symbol(arch.as_ref(), &obj_file, s, None))
+                .collect::<Result<Vec<_>>>()?
+        };
+        let has_symbols = !symbols.is_empty();
+        finish_section_symbols(&obj_section, &mut symbols, &mut section_name_counts);
+        if !has_symbols {
+            // The dummy symbol is created at address 0, move it to the start of the region
+            symbols[0].address = lo;
+        }
+        for symbol in &mut symbols {
+            symbol.size = symbol.size.min(hi - symbol.address);
+            symbol.section_address = symbol.address - lo;
+            symbol.virtual_address = Some(symbol.address);
+            symbol.bytes = obj_section
+                .data
+                .get(
+                    symbol.section_address as usize
+                        ..(symbol.section_address + symbol.size) as usize,
+                )
+                .unwrap_or_default()
+                .to_vec();
+        }
+        obj_section.symbols = symbols;
+        sections.push(obj_section);
+    }
+    ensure!(
+        !sections.is_empty(),
+        "No sections of {} overlap the target region",
+        exe_path.display()
+    );
+    sections.sort_by(|a, b| a.name.cmp(&b.name));
+    if config.combine_data_sections {
+        combine_data_sections(&mut sections)?;
+    }
+    #[cfg(feature = "dwarf")]
+    debug_info(&obj_file, &mut sections);
+    Ok(ObjInfo {
+        arch,
+        endianness: obj_file.endianness(),
+        path: Some(exe_path.to_owned()),
+        timestamp: Some(timestamp),
+        sections,
+        common: Vec::new(),
+        split_meta: None,
+    })
+}
+
+/// Assigns DWARF variable types to data symbols and function metadata to code symbols.
+/// Debug info is optional, so errors are logged rather than failing the whole object.
+#[cfg(feature = "dwarf")]
+fn debug_info(obj_file: &File<'_>, sections: &mut [ObjSection]) {
+    let info = match super::dwarf::debug_info(obj_file) {
+        Ok(info) => info,
+        Err(e) => {
+            log::warn!("Failed to read DWARF debug info: {e:?}");
+            return;
+        }
+    };
+    for section in sections {
+        match section.kind {
+            ObjSectionKind::Code if !info.functions.is_empty() => {
+                for symbol in &mut section.symbols {
+                    symbol.function_info = info.functions.get(&symbol.name).cloned();
+                }
+            }
+            ObjSectionKind::Data | ObjSectionKind::Bss if !info.data_types.is_empty() => {
+                for symbol in &mut section.symbols {
+                    symbol.data_type = info.data_types.get(&symbol.name).cloned();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 pub fn parse(data: &[u8], config: &DiffObjConfig) -> Result<ObjInfo> {
     let obj_file = File::parse(data)?;
     let arch = new_arch(&obj_file)?;
@@ -732,19 +1114,45 @@ pub fn parse(data: &[u8], config: &DiffObjConfig) -> Result<ObjInfo> {
         combine_data_sections(&mut sections)?;
     }
     line_info(&obj_file, &mut sections, data)?;
+    #[cfg(feature = "dwarf")]
+    debug_info(&obj_file, &mut sections);
     let common = common_symbols(arch.as_ref(), &obj_file, split_meta.as_ref())?;
-    Ok(ObjInfo { arch, path: None, timestamp: None, sections, common, split_meta })
+    Ok(ObjInfo {
+        arch,
+        endianness: obj_file.endianness(),
+        path: None,
+        timestamp: None,
+        sections,
+        common,
+        split_meta,
+    })
 }
 
+/// Checks whether the object defines a function with the given name.
 pub fn has_function(obj_path: &Path, symbol_name: &str) -> Result<bool> {
+    has_symbol_kind(obj_path, symbol_name, |kind| kind == SymbolKind::Text)
+}
+
+/// Checks whether the object defines a function or data symbol with the given name.
+pub fn has_symbol(obj_path: &Path, symbol_name: &str) -> Result<bool> {
+    has_symbol_kind(obj_path, symbol_name, |kind| {
+        matches!(kind, SymbolKind::Text | SymbolKind::Data)
+    })
+}
+
+fn has_symbol_kind(
+    obj_path: &Path,
+    symbol_name: &str,
+    filter: impl Fn(SymbolKind) -> bool,
+) -> Result<bool> {
+    let (file_path, member_name) = split_member_path(obj_path);
     let data = {
-        let file = fs::File::open(obj_path)?;
+        let file = fs::File::open(file_path)?;
         unsafe { memmap2::Mmap::map(&file) }?
     };
-    Ok(File::parse(&*data)?
+    Ok(File::parse(select_member(&data, member_name, file_path)?)?
         .symbol_by_name(symbol_name)
-        .filter(|o| o.kind() == SymbolKind::Text)
-        .is_some())
+        .is_some_and(|o| filter(o.kind())))
 }
 
 fn split_meta(obj_file: &File<'_>) -> Result<Option<SplitMeta>> {
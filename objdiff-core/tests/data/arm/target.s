@@ -0,0 +1,34 @@
+	.syntax unified
+	.arch armv5te
+	.eabi_attribute Tag_CPU_arch, 4
+	.arm
+	.text
+	.globl add_one
+	.type add_one, %function
+add_one:
+	add r0, r0, #1
+	bx lr
+	.size add_one, .-add_one
+
+	.globl accumulate
+	.type accumulate, %function
+accumulate:
+	push {r4, lr}
+	mov r4, r0
+	bl add_one
+	add r0, r0, r4
+
+	ldr r1, .Lpool
+	str r0, [r1]
+	pop {r4, pc}
+.Lpool:
+	.word counter
+	.size accumulate, .-accumulate
+
+	.data
+	.globl counter
+	.type counter, %object
+counter:
+	.word 0x00000010
+	.word 0x00000020
+	.size counter, .-counter
@@ -0,0 +1,32 @@
+	.text
+	.globl add_one
+	.type add_one, %function
+add_one:
+	add w0, w0, #1
+	ret
+	.size add_one, .-add_one
+
+	.globl accumulate
+	.type accumulate, %function
+accumulate:
+	stp x29, x30, [sp, #-32]!
+	mov x29, sp
+	str x19, [sp, #16]
+	mov w19, w0
+	bl add_one
+	add w0, w0, w19
+
+	adrp x8, counter
+	str w0, [x8, :lo12:counter]
+	ldr x19, [sp, #16]
+	ldp x29, x30, [sp], #32
+	ret
+	.size accumulate, .-accumulate
+
+	.data
+	.globl counter
+	.type counter, %object
+counter:
+	.word 0x00000010
+	.word 0x00000020
+	.size counter, .-counter
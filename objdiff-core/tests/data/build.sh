@@ -0,0 +1,53 @@
+#!/bin/sh
+# Regenerates the test fixtures from their sources.
+# Requires llvm-mc (LLVM 14 or newer), an x86-64 gcc and g++, and GNU ld.
+set -e
+cd "$(dirname "$0")"
+
+assemble() {
+    dir=$1
+    triple=$2
+    shift 2
+    for name in target base; do
+        llvm-mc -triple="$triple" "$@" -filetype=obj "$dir/$name.s" -o "$dir/$name.o"
+    done
+}
+
+assemble ppc powerpc-unknown-eabi
+assemble mips mips-unknown-linux-gnu -mcpu=mips2
+assemble x86 i386-pc-windows-msvc
+rm -f x86/lib.a
+llvm-ar rc --format=gnu x86/lib.a x86/target.o x86/base.o
+# Base object with renamed symbols, diffed against x86/target.o
+llvm-mc -triple=i386-pc-windows-msvc -filetype=obj pairing/base.s -o pairing/base.o
+assemble macho x86_64-apple-macos11
+assemble arm armv5te-none-eabi
+assemble arm64 aarch64-none-elf
+# Data relocations at a non-zero section address
+assemble macho_arm64 arm64-apple-macos11
+# Registers renamed in some instructions but not others
+assemble renaming i386-pc-windows-msvc
+# Data symbols with relocations to different targets
+assemble relocs i386-pc-windows-msvc
+# Jump table referenced through a label symbol, as MSVC emits
+assemble jumptable i386-pc-windows-msvc
+
+# Linked executable containing x86/target.o at 0x401018-0x40106c (see linked/target.map)
+llvm-mc -triple=i386-pc-windows-msvc -filetype=obj linked/start.s -o linked/start.o
+ld -m i386pe --no-insert-timestamp -nostdlib -e _start -Map linked/target.ld.map \
+    -o linked/target.exe linked/start.o x86/target.o
+rm linked/start.o
+
+# DWARF type information
+for name in target base; do
+    gcc -std=c99 -g -O0 -fno-ident -fdebug-prefix-map="$PWD"=. -c "dwarf/$name.c" -o "dwarf/$name.o"
+done
+
+# C++ static members, which DWARF 4 describes as member declarations
+g++ -gdwarf-4 -O0 -fno-ident -fdebug-prefix-map="$PWD"=. -c dwarf/members.cpp -o dwarf/members.o
+
+# DWARF function info, with .debug_frame for CFA-relative locals
+for opt in O0 O1; do
+    gcc -std=c99 -g -$opt -fno-asynchronous-unwind-tables -fno-ident -fdebug-prefix-map="$PWD"=. \
+        -c dwarf/func.c -o "dwarf/func-$opt.o"
+done
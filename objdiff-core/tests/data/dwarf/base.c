@@ -0,0 +1,21 @@
+typedef enum { MODE_OFF, MODE_ON = 5 } Mode;
+
+struct Camera {
+    float fov;
+    float aspect;
+    double near;
+    int pos[3];
+};
+
+struct Scene {
+    struct Camera camera;
+    char name[8];
+    Mode mode;
+    const char *title;
+    unsigned short flags : 3;
+    unsigned short layer : 5;
+    _Bool enabled;
+};
+
+struct Scene gScene = { { 0.5f, 1.5f, 0.25, { 1, -2, 3 } }, "main", MODE_OFF, "title", 1, 2, 1 };
+short gTable[2][2] = { { 1, 2 }, { 3, 5 } };
@@ -0,0 +1,16 @@
+static inline __attribute__((always_inline)) int square(int x) { return x * x; }
+
+int sum_squares(int count, int scale)
+{
+    int total = 0;
+    for (int i = 0; i < count; i++) {
+        total += square(i) * scale;
+    }
+    return total;
+}
+
+long mix(long a, long b)
+{
+    long result = a ^ b;
+    return result + (a << 3);
+}
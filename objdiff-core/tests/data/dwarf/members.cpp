@@ -0,0 +1,8 @@
+struct Counter {
+    static int sInstances;
+    int value;
+    short step;
+};
+
+int Counter::sInstances = 0;
+Counter gCounter = { 7, 2 };
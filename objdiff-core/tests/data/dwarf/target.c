@@ -0,0 +1,20 @@
+typedef enum { MODE_OFF, MODE_ON = 5 } Mode;
+
+struct Camera {
+    float fov;
+    double near;
+    int pos[3];
+};
+
+struct Scene {
+    struct Camera camera;
+    char name[8];
+    Mode mode;
+    const char *title;
+    unsigned short flags : 3;
+    unsigned short layer : 5;
+    _Bool enabled;
+};
+
+struct Scene gScene = { { 1.0f, 0.25, { 1, -2, 3 } }, "main", MODE_ON, "title", 1, 2, 1 };
+short gTable[2][2] = { { 1, 2 }, { 3, 4 } };
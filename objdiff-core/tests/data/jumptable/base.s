@@ -0,0 +1,35 @@
+	.intel_syntax noprefix
+	.text
+	.globl _clamp
+	.def _clamp; .scl 2; .type 32; .endef
+_clamp:
+	mov eax, dword ptr [esp + 4]
+	test eax, eax
+	jns "$LN2@clamp"
+	xor eax, eax
+"$LN2@clamp":
+	ret
+
+	.globl _dispatch
+	.def _dispatch; .scl 2; .type 32; .endef
+_dispatch:
+	mov ecx, dword ptr [esp + 4]
+	xor eax, eax
+	cmp ecx, 2
+	ja "$LN6@dispatch"
+	jmp dword ptr ["$LN8@dispatch" + 4*ecx]
+"$LN5@dispatch":
+	add eax, 1
+	ret
+"$LN4@dispatch":
+	add eax, 2
+	ret
+"$LN3@dispatch":
+	add eax, 4
+"$LN6@dispatch":
+	ret
+	.p2align 2, 0xcc
+"$LN8@dispatch":
+	.long "$LN5@dispatch"
+	.long "$LN4@dispatch"
+	.long "$LN3@dispatch"
@@ -0,0 +1,18 @@
+	.intel_syntax noprefix
+	.text
+	.globl _start
+	.def _start; .scl 2; .type 32; .endef
+_start:
+	push 2
+	push 1
+	call _select
+	add esp, 8
+	push eax
+	call _accumulate
+	add esp, 4
+	ret
+
+	.data
+	.globl _flags
+_flags:
+	.long 0
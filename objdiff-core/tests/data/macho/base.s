@@ -0,0 +1,64 @@
+	.section __TEXT,__text,regular,pure_instructions
+	.p2align 4, 0x90
+_add_one:
+	leal 1(%rdi), %eax
+	retq
+
+	.globl _greet
+	.p2align 4, 0x90
+_greet:
+	pushq %rbx
+	movl %edi, %ebx
+	callq _add_one
+	addl _counter(%rip), %eax
+	nop
+	movl %eax, _counter(%rip)
+	leaq L_.str(%rip), %rdi
+	movl %ebx, %esi
+	popq %rbx
+	jmp _printf
+
+	.globl _dispatch
+	.p2align 4, 0x90
+_dispatch:
+	leaq LJTI0_0(%rip), %rcx
+	movslq (%rcx,%rdi,4), %rax
+	addq %rcx, %rax
+	jmpq *%rax
+LBB0_1:
+	movl $1, %eax
+	retq
+LBB0_2:
+	movl $3, %eax
+	retq
+	.p2align 2, 0x90
+LJTI0_0:
+	.long LBB0_1-LJTI0_0
+	.long LBB0_2-LJTI0_0
+
+	.globl _entry
+	.p2align 4, 0x90
+_entry:
+	leaq LBB1_1(%rip), %rax
+	movq %rax, _counter(%rip)
+LBB1_1:
+	xorl %eax, %eax
+	retq
+
+	.section __TEXT,__cstring,cstring_literals
+L_.str:
+	.asciz "hi %d\n"
+
+	.section __DATA,__data
+	.globl _counter
+	.p2align 2
+_counter:
+	.long 32
+
+	.globl _table
+	.p2align 3
+_table:
+	.quad _greet
+	.quad _add_one
+
+.subsections_via_symbols
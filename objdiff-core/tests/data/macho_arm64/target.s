@@ -0,0 +1,47 @@
+	.section __TEXT,__text,regular,pure_instructions
+	.globl _add_one
+	.p2align 2
+_add_one:
+	add w0, w0, #1
+	ret
+
+	.globl _greet
+	.p2align 2
+_greet:
+	stp x29, x30, [sp, #-16]!
+	mov x29, sp
+	bl _add_one
+	adrp x8, _counter@PAGE
+	ldr w9, [x8, _counter@PAGEOFF]
+	add w0, w0, w9
+	str w0, [x8, _counter@PAGEOFF]
+	adrp x0, L_.str@PAGE
+	add x0, x0, L_.str@PAGEOFF
+	ldp x29, x30, [sp], #16
+	b _printf
+
+	.globl _select
+	.p2align 2
+_select:
+	adrp x8, _table@PAGE
+	add x8, x8, _table@PAGEOFF
+	ldr x0, [x8, w0, uxtw #3]
+	ret
+
+	.section __TEXT,__cstring,cstring_literals
+L_.str:
+	.asciz "hello %d\n"
+
+	.section __DATA,__data
+	.globl _counter
+	.p2align 2
+_counter:
+	.long 16
+
+	.globl _table
+	.p2align 3
+_table:
+	.quad _greet
+	.quad _add_one
+
+.subsections_via_symbols
@@ -0,0 +1,35 @@
+	.set noreorder
+	.set noat
+	.text
+	.globl add_one
+	.type add_one, @function
+add_one:
+	jr $ra
+	addiu $v0, $a0, 1
+	.size add_one, .-add_one
+
+	.globl accumulate
+	.type accumulate, @function
+accumulate:
+	addiu $sp, $sp, -24
+	sw $ra, 20($sp)
+	sw $s1, 16($sp)
+	jal add_one
+	move $s1, $a0
+	addu $v0, $v0, $s1
+	nop
+	lui $t0, %hi(counter)
+	sw $v0, %lo(counter)($t0)
+	lw $ra, 20($sp)
+	lw $s1, 16($sp)
+	jr $ra
+	addiu $sp, $sp, 24
+	.size accumulate, .-accumulate
+
+	.data
+	.globl counter
+	.type counter, @object
+counter:
+	.word 0x00000010
+	.word 0x00000030
+	.size counter, .-counter
@@ -0,0 +1,57 @@
+	.intel_syntax noprefix
+	.text
+	.globl _func_00401000
+	.def _func_00401000; .scl 2; .type 32; .endef
+_func_00401000:
+	mov eax, dword ptr [esp + 4]
+	add eax, 1
+	ret
+
+	.globl _Accumulate
+	.def _Accumulate; .scl 2; .type 32; .endef
+_Accumulate:
+	push esi
+	mov esi, dword ptr [esp + 8]
+	push esi
+	call _func_00401000
+	add esp, 4
+	add eax, esi
+	nop
+	mov dword ptr [_data_00402000], eax
+	pop esi
+	ret
+
+	.globl _pick
+	.def _pick; .scl 2; .type 32; .endef
+_pick:
+	mov ecx, dword ptr [esp + 4]
+	xor eax, eax
+LBB2_1:
+	add eax, ecx
+	dec ecx
+	jg LBB2_1
+	mov ecx, dword ptr [esp + 8]
+	cmp ecx, 2
+	ja LBB2_5
+	jmp dword ptr [4*ecx + LJTI2_0]
+LBB2_2:
+	add eax, 1
+	ret
+LBB2_3:
+	add eax, 2
+	ret
+LBB2_4:
+	add eax, 4
+LBB2_5:
+	ret
+	.p2align 2, 0xcc
+LJTI2_0:
+	.long LBB2_2
+	.long LBB2_4
+	.long LBB2_3
+
+	.data
+	.globl _data_00402000
+_data_00402000:
+	.long 0x00000010
+	.long 0x00000030
@@ -0,0 +1,35 @@
+	.text
+	.globl add_one
+	.type add_one, @function
+add_one:
+	addi 3, 3, 1
+	blr
+	.size add_one, .-add_one
+
+	.globl accumulate
+	.type accumulate, @function
+accumulate:
+	stwu 1, -16(1)
+	mflr 0
+	stw 0, 20(1)
+	stw 30, 12(1)
+	mr 30, 3
+	bl add_one
+	add 3, 3, 30
+	nop
+	lis 4, counter@ha
+	stw 3, counter@l(4)
+	lwz 0, 20(1)
+	lwz 30, 12(1)
+	mtlr 0
+	addi 1, 1, 16
+	blr
+	.size accumulate, .-accumulate
+
+	.data
+	.globl counter
+	.type counter, @object
+counter:
+	.long 0x00000010
+	.long 0x00000030
+	.size counter, .-counter
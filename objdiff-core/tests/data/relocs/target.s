@@ -0,0 +1,26 @@
+	.intel_syntax noprefix
+	.text
+	.globl _func_a
+	.def _func_a; .scl 2; .type 32; .endef
+_func_a:
+	mov eax, 1
+	ret
+
+	.globl _func_b
+	.def _func_b; .scl 2; .type 32; .endef
+_func_b:
+	mov eax, 2
+	ret
+
+	.data
+	.globl _table
+	.p2align 2
+_table:
+	.long _func_a
+	.long _func_b
+	.long 0
+
+	.globl _value
+	.p2align 2
+_value:
+	.long 42
@@ -0,0 +1,10 @@
+	.intel_syntax noprefix
+	.text
+	.globl _triple
+	.def _triple; .scl 2; .type 32; .endef
+_triple:
+	mov esi, ecx
+	add esi, ecx
+	add esi, ecx
+	mov eax, ebx
+	ret
@@ -0,0 +1,1009 @@
+//! Golden-file tests for the diff pipeline.
+//!
+//! Each directory under `tests/data` contains a `target.o` and a `base.o` built from the
+//! accompanying `.s` or `.c` sources (see `tests/data/build.sh`). The fixtures are parsed, diffed and
+//! rendered to text, which is compared against the snapshots in `tests/snapshots`.
+//!
+//! When a change in output is intended, regenerate the snapshots with:
+//! ```sh
+//! cargo insta test -p objdiff-core --all-features --accept
+//! ```
+//! or, without `cargo-insta` installed:
+//! ```sh
+//! INSTA_UPDATE=always cargo test -p objdiff-core --all-features
+//! ```
+#![cfg(feature = "any-arch")]
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use objdiff_core::{
+    config::{
+        save_unit_symbol_mappings, try_project_config, ProjectConfig, ProjectLinkerMap,
+        ProjectObject, SymbolMappings, TargetRegion,
+    },
+    diff::{
+        diff_objs, diff_objs_incremental,
+        display::{display_data_reloc, display_diff, DiffText},
+        fingerprint::MIN_SIMILARITY,
+        incremental::CachedDiff,
+        three_way::{align_prev_rows, ins_progress, ObjInsProgress},
+        DiffAlgorithm, DiffObjConfig, DiffObjsResult, DiffScoreWeights, ObjDiff, ObjInsDiffKind,
+        ObjSymbolDiff, SymbolPairingMode,
+    },
+    index::{index_unit, ProjectIndex},
+    obj::{
+        map::{discover_units, read_linker_map, unit_name},
+        read::{has_function, has_symbol, member_path, parse, read, read_target},
+        ObjInfo, ObjSectionKind, SymbolRef,
+    },
+};
+
+fn fixture_path(arch: &str, name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join(arch).join(name)
+}
+
+fn load_fixture(arch: &str, name: &str, config: &DiffObjConfig) -> ObjInfo {
+    let path = fixture_path(arch, name);
+    let data = std::fs::read(&path)
+        .unwrap_or_else(|e| panic!("Failed to read fixture {}: {e}", path.display()));
+    parse(&data, config)
+        .unwrap_or_else(|e| panic!("Failed to parse fixture {}: {e:?}", path.display()))
+}
+
+fn format_percent(percent: Option<f32>) -> String {
+    match percent {
+        Some(percent) => format!("{percent:.2}%"),
+        None => "-".to_string(),
+    }
+}
+
+/// Renders an object and its diff into a stable, human-readable form.
+fn render(out: &mut String, obj: &ObjInfo, diff: &ObjDiff) {
+    for (section, section_diff) in obj.sections.iter().zip(&diff.sections) {
+        writeln!(
+            out,
+            "section {} ({:?}) match: {}",
+            section.name,
+            section.kind,
+            format_percent(section_diff.match_percent)
+        )
+        .unwrap();
+        for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+            let target = symbol_diff
+                .target_symbol
+                .map(|r| format!("{}:{}", r.section_idx, r.symbol_idx))
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                out,
+                "  symbol {} size: {:#x} match: {} target: {}",
+                symbol.name,
+                symbol.size,
+                format_percent(symbol_diff.match_percent),
+                target
+            )
+            .unwrap();
+            for mapping in &symbol_diff.register_mapping {
+                writeln!(out, "    register {} -> {}", mapping.left, mapping.right).unwrap();
+            }
+            for ins_diff in &symbol_diff.instructions {
+                let mut line = String::new();
+                display_diff(ins_diff, symbol.address, |text| {
+                    match text {
+                        DiffText::Basic(s) | DiffText::BasicColor(s, _) => line.push_str(s),
+                        DiffText::Line(num) => write!(line, "{num} ").unwrap(),
+                        DiffText::Address(addr) => write!(line, "{addr:x}:").unwrap(),
+                        DiffText::Opcode(mnemonic, _) => line.push_str(mnemonic),
+                        DiffText::Argument(arg, _) => write!(line, "{arg}").unwrap(),
+                        DiffText::BranchDest(addr, _) => write!(line, "{addr:x}").unwrap(),
+                        DiffText::Symbol(sym, _) => {
+                            line.push_str(sym.demangled_name.as_ref().unwrap_or(&sym.name))
+                        }
+                        DiffText::Spacing(n) => line.push_str(&" ".repeat(n)),
+                        DiffText::Eol => {}
+                    }
+                    Ok::<_, ()>(())
+                })
+                .unwrap();
+                let arg_diff = ins_diff
+                    .arg_diff
+                    .iter()
+                    .map(|d| d.map(|d| d.idx.to_string()).unwrap_or_else(|| "-".to_string()))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let kind = format!("{:?}", ins_diff.kind);
+                let row = format!("{kind:<11} [{arg_diff}] {line}");
+                writeln!(out, "    {}", row.trim_end()).unwrap();
+            }
+        }
+        if section.kind == ObjSectionKind::Data {
+            for data_diff in &section_diff.data_diff {
+                write!(
+                    out,
+                    "  data {:?} len: {:#x} {:02x?}",
+                    data_diff.kind, data_diff.len, data_diff.data
+                )
+                .unwrap();
+                if let Some(reloc) = &data_diff.reloc {
+                    write!(out, " reloc: {}", display_data_reloc(reloc)).unwrap();
+                }
+                out.push('\n');
+            }
+        }
+    }
+}
+
+/// Diffs the `target.o` and `base.o` fixtures for an architecture and renders both sides.
+fn diff_fixture(arch: &str) -> String { diff_fixture_with_config(arch, &DiffObjConfig::default()) }
+
+fn diff_fixture_with_config(arch: &str, config: &DiffObjConfig) -> String {
+    let target = load_fixture(arch, "target.o", config);
+    let base = load_fixture(arch, "base.o", config);
+    let result = diff_objs(config, Some(&target), Some(&base), None).unwrap();
+    let mut out = String::new();
+    out.push_str("# target\n");
+    render(&mut out, &target, result.left.as_ref().unwrap());
+    out.push_str("\n# base\n");
+    render(&mut out, &base, result.right.as_ref().unwrap());
+    out
+}
+
+/// An object diffed against itself must match completely, regardless of backend output.
+fn assert_self_diff_matches(arch: &str) {
+    let config = DiffObjConfig::default();
+    let obj = load_fixture(arch, "target.o", &config);
+    let result = diff_objs(&config, Some(&obj), Some(&obj), None).unwrap();
+    for diff in [result.left.as_ref().unwrap(), result.right.as_ref().unwrap()] {
+        for (section, section_diff) in obj.sections.iter().zip(&diff.sections) {
+            if let Some(percent) = section_diff.match_percent {
+                assert_eq!(percent, 100.0, "{arch}: section {} self-diff mismatch", section.name);
+            }
+            for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+                if let Some(percent) = symbol_diff.match_percent {
+                    assert_eq!(percent, 100.0, "{arch}: symbol {} self-diff mismatch", symbol.name);
+                }
+                for ins_diff in &symbol_diff.instructions {
+                    assert_eq!(ins_diff.kind, ObjInsDiffKind::None, "{arch}: {}", symbol.name);
+                    assert!(ins_diff.arg_diff.iter().all(|d| d.is_none()));
+                }
+            }
+        }
+    }
+}
+
+/// Renders the typed fields of each data symbol.
+#[cfg(feature = "dwarf")]
+fn render_data_fields(out: &mut String, obj: &ObjInfo, diff: &ObjDiff) {
+    for (section, section_diff) in obj.sections.iter().zip(&diff.sections) {
+        for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+            for field_diff in &symbol_diff.data_fields {
+                let kind = format!("{:?}", field_diff.kind);
+                match &field_diff.field {
+                    Some(field) => writeln!(
+                        out,
+                        "{kind:<7} {:#06x} {}{}: {}",
+                        field.offset,
+                        symbol.name,
+                        field.path,
+                        field.value.as_deref().unwrap_or("-")
+                    )
+                    .unwrap(),
+                    None => writeln!(out, "{kind}").unwrap(),
+                }
+            }
+        }
+    }
+}
+
+/// Renders the DWARF function info of each code symbol, followed by the
+/// instructions that have annotations.
+#[cfg(feature = "dwarf")]
+fn render_function_info(out: &mut String, obj: &ObjInfo, diff: &ObjDiff) {
+    use objdiff_core::diff::display::{display_variable_location, ins_annotations};
+
+    let arch = obj.arch.as_ref();
+    for (section, section_diff) in obj.sections.iter().zip(&diff.sections) {
+        for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+            let Some(info) = &symbol.function_info else {
+                continue;
+            };
+            writeln!(out, "{} -> {}", symbol.name, info.return_type.as_deref().unwrap_or("void"))
+                .unwrap();
+            for (kind, variables) in [("param", &info.params), ("local", &info.locals)] {
+                for variable in variables {
+                    let location = variable
+                        .location
+                        .as_ref()
+                        .map(|location| display_variable_location(arch, location))
+                        .unwrap_or_else(|| "-".to_string());
+                    writeln!(
+                        out,
+                        "  {kind} {}: {} @ {location}",
+                        variable.name,
+                        variable.ty.as_deref().unwrap_or("?")
+                    )
+                    .unwrap();
+                }
+            }
+            for range in &info.inlined {
+                writeln!(
+                    out,
+                    "  inlined {} {:#x}..{:#x} depth: {} line: {:?}",
+                    range.name,
+                    range.start - symbol.address,
+                    range.end - symbol.address,
+                    range.depth,
+                    range.call_line
+                )
+                .unwrap();
+            }
+            for ins in symbol_diff.instructions.iter().filter_map(|d| d.ins.as_ref()) {
+                let annotations = ins_annotations(arch, symbol, ins);
+                if !annotations.is_empty() {
+                    writeln!(
+                        out,
+                        "    {:x}: {} ; {}",
+                        ins.address - symbol.address,
+                        ins.formatted,
+                        annotations.join(", ")
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+}
+
+/// Renders the basic blocks of each code symbol.
+fn render_blocks(out: &mut String, obj: &ObjInfo, diff: &ObjDiff) {
+    for (section, section_diff) in obj.sections.iter().zip(&diff.sections) {
+        for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+            if symbol_diff.blocks.is_empty() {
+                continue;
+            }
+            writeln!(out, "symbol {}", symbol.name).unwrap();
+            for (idx, block) in symbol_diff.blocks.iter().enumerate() {
+                let successors =
+                    block.successors.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(",");
+                let target =
+                    block.target_block.map(|t| t.to_string()).unwrap_or_else(|| "-".to_string());
+                writeln!(
+                    out,
+                    "  block {idx} rows: {}..{} successors: [{successors}] target: {target} {:?}",
+                    block.start, block.end, block.kind
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+fn diff_fixture_blocks(arch: &str) -> String {
+    let config = DiffObjConfig::default();
+    let target = load_fixture(arch, "target.o", &config);
+    let base = load_fixture(arch, "base.o", &config);
+    let result = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+    let mut out = String::new();
+    out.push_str("# target\n");
+    render_blocks(&mut out, &target, result.left.as_ref().unwrap());
+    out.push_str("\n# base\n");
+    render_blocks(&mut out, &base, result.right.as_ref().unwrap());
+    out
+}
+
+fn check_fixture(arch: &str) {
+    assert_self_diff_matches(arch);
+    insta::assert_snapshot!(arch, diff_fixture(arch));
+}
+
+#[test]
+#[cfg(feature = "ppc")]
+fn ppc_elf() { check_fixture("ppc"); }
+
+#[test]
+#[cfg(feature = "mips")]
+fn mips_elf() { check_fixture("mips"); }
+
+#[test]
+#[cfg(feature = "x86")]
+fn x86_coff() { check_fixture("x86"); }
+
+#[test]
+#[cfg(feature = "x86")]
+fn x86_register_renaming() {
+    let config = DiffObjConfig { register_renaming: true, ..Default::default() };
+    insta::assert_snapshot!("x86_register_renaming", diff_fixture_with_config("x86", &config));
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn x86_register_mapping() {
+    let config = DiffObjConfig { register_renaming: true, ..Default::default() };
+    insta::assert_snapshot!("x86_register_mapping", diff_fixture_with_config("renaming", &config));
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn x86_score_weights() {
+    let mut out = String::new();
+    let weights = [
+        ("default", DiffScoreWeights::default()),
+        ("arg mismatches", DiffScoreWeights { arg_mismatch: 10, ..Default::default() }),
+        ("unweighted", DiffScoreWeights {
+            op_mismatch: 1,
+            arg_mismatch: 1,
+            register_rename: 1,
+            replace: 1,
+            delete: 1,
+            insert: 1,
+        }),
+    ];
+    for (name, score_weights) in weights {
+        let config = DiffObjConfig { score_weights, ..Default::default() };
+        let target = load_fixture("x86", "target.o", &config);
+        let base = load_fixture("x86", "base.o", &config);
+        let result = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+        let diff = result.left.as_ref().unwrap();
+        writeln!(out, "# {name}").unwrap();
+        for (section, section_diff) in target.sections.iter().zip(&diff.sections) {
+            if section.kind != ObjSectionKind::Code {
+                continue;
+            }
+            for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+                let score = symbol_diff.score.map_or_else(|| "-".to_string(), |s| s.to_string());
+                writeln!(
+                    out,
+                    "{} score: {} match: {}",
+                    symbol.name,
+                    score,
+                    format_percent(symbol_diff.match_percent)
+                )
+                .unwrap();
+            }
+        }
+    }
+    insta::assert_snapshot!("x86_score_weights", out);
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn x86_operand_alignment() {
+    let config = DiffObjConfig { diff_algorithm: DiffAlgorithm::Operand, ..Default::default() };
+    insta::assert_snapshot!("x86_operand_alignment", diff_fixture_with_config("x86", &config));
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn alignment_time_budget() {
+    use strum::VariantArray;
+
+    for &diff_algorithm in DiffAlgorithm::VARIANTS {
+        for (diff_time_budget_ms, approximate) in [(None, false), (Some(0), true)] {
+            let config =
+                DiffObjConfig { diff_algorithm, diff_time_budget_ms, ..Default::default() };
+            let target = load_fixture("x86", "target.o", &config);
+            let base = load_fixture("x86", "base.o", &config);
+            let result = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+            let diff = result.left.as_ref().unwrap();
+            let (section_idx, section) = target
+                .sections
+                .iter()
+                .enumerate()
+                .find(|(_, s)| s.kind == ObjSectionKind::Code)
+                .unwrap();
+            let symbol_idx = section.symbols.iter().position(|s| s.name == "_accumulate").unwrap();
+            let symbol_diff = &diff.sections[section_idx].symbols[symbol_idx];
+            assert_eq!(symbol_diff.approximate, approximate, "{diff_algorithm:?}");
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn incremental_diff() {
+    let config = DiffObjConfig::default();
+    let target = load_fixture("x86", "target.o", &config);
+    let base = load_fixture("x86", "base.o", &config);
+    let render_both = |left: &ObjDiff, right: &ObjDiff| {
+        let mut out = String::new();
+        render(&mut out, &target, left);
+        render(&mut out, &base, right);
+        out
+    };
+    let full = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+    let expected = render_both(full.left.as_ref().unwrap(), full.right.as_ref().unwrap());
+
+    // Start from a diff of the target against itself, marking its scores to tell which
+    // functions are reused
+    let mut cached = diff_objs(&config, Some(&target), Some(&target), None).unwrap();
+    for diff in [cached.left.as_mut().unwrap(), cached.right.as_mut().unwrap()] {
+        for symbol_diff in diff.sections.iter_mut().flat_map(|s| &mut s.symbols) {
+            symbol_diff.score = symbol_diff.score.map(|_| u64::MAX);
+        }
+    }
+    let cached_diff = |config| CachedDiff {
+        config,
+        left: Some((&target, cached.left.as_ref().unwrap())),
+        right: Some((&target, cached.right.as_ref().unwrap())),
+    };
+
+    let result = diff_objs_incremental(
+        &config,
+        Some(&target),
+        Some(&base),
+        None,
+        Some(cached_diff(&config)),
+    )
+    .unwrap();
+    let left = result.left.as_ref().unwrap();
+    assert_eq!(render_both(left, result.right.as_ref().unwrap()), expected);
+    let (reused, recomputed): (Vec<_>, Vec<_>) = left
+        .sections
+        .iter()
+        .flat_map(|s| &s.symbols)
+        .filter(|s| s.score.is_some())
+        .partition(|s| s.score == Some(u64::MAX));
+    assert!(!reused.is_empty() && !recomputed.is_empty());
+    assert!(reused.iter().all(|s| s.match_percent == Some(100.0)));
+
+    // Nothing is reused once the config changes
+    let config = DiffObjConfig { relax_reloc_diffs: true, ..Default::default() };
+    let result = diff_objs_incremental(
+        &config,
+        Some(&target),
+        Some(&base),
+        None,
+        Some(cached_diff(&DiffObjConfig::default())),
+    )
+    .unwrap();
+    let left = result.left.as_ref().unwrap();
+    assert!(left.sections.iter().flat_map(|s| &s.symbols).all(|s| s.score != Some(u64::MAX)));
+}
+
+/// Returns both sides of the diff of a left symbol.
+fn symbol_diffs(
+    result: &DiffObjsResult,
+    symbol_ref: SymbolRef,
+) -> (&ObjSymbolDiff, &ObjSymbolDiff) {
+    let left = result.left.as_ref().unwrap().symbol_diff(symbol_ref);
+    let right = result.right.as_ref().unwrap().symbol_diff(left.target_symbol.unwrap());
+    (left, right)
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn three_way_progress() {
+    let config = DiffObjConfig::default();
+    let target = load_fixture("x86", "target.o", &config);
+    let base = load_fixture("x86", "base.o", &config);
+    let current = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+    // The target itself stands in for a build that matched completely
+    let matched = diff_objs(&config, Some(&target), Some(&target), None).unwrap();
+    let mut progress = Vec::new();
+    for (section_idx, section) in target.sections.iter().enumerate() {
+        if section.kind != ObjSectionKind::Code {
+            continue;
+        }
+        for symbol_idx in 0..section.symbols.len() {
+            let symbol_ref = SymbolRef { section_idx, symbol_idx };
+            let (current_left, current_right) = symbol_diffs(&current, symbol_ref);
+            let (matched_left, matched_right) = symbol_diffs(&matched, symbol_ref);
+
+            // Compared to the matching build, mismatched instructions got worse
+            let rows = align_prev_rows(current_left, matched_left);
+            for (idx, row) in rows.iter().enumerate() {
+                let ins_diff = &current_right.instructions[idx];
+                let Some(row) = row else {
+                    assert!(current_left.instructions[idx].ins.is_none());
+                    continue;
+                };
+                let expected =
+                    (ins_diff.kind != ObjInsDiffKind::None).then_some(ObjInsProgress::Worse);
+                assert_eq!(ins_progress(ins_diff, &matched_right.instructions[*row]), expected);
+                progress.extend(expected);
+            }
+
+            // And the other way around, they got better
+            let rows = align_prev_rows(matched_left, current_left);
+            for (idx, row) in rows.iter().enumerate() {
+                let prev = &current_right.instructions[row.unwrap()];
+                let expected =
+                    (prev.kind != ObjInsDiffKind::None).then_some(ObjInsProgress::Better);
+                assert_eq!(ins_progress(&matched_right.instructions[idx], prev), expected);
+            }
+        }
+    }
+    assert!(!progress.is_empty());
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn x86_basic_blocks() {
+    insta::assert_snapshot!("x86_basic_blocks", diff_fixture_blocks("x86"));
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn x86_named_jump_table() {
+    // MSVC-style jump table referenced through a label symbol in the middle of `.text`
+    insta::assert_snapshot!("x86_named_jump_table", diff_fixture_blocks("jumptable"));
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn x86_data_relocs() { check_fixture("relocs"); }
+
+#[test]
+#[cfg(feature = "x86")]
+fn x86_64_macho() { check_fixture("macho"); }
+
+#[test]
+#[cfg(feature = "x86")]
+fn symbol_pairing() {
+    let mut config =
+        DiffObjConfig { symbol_pairing: SymbolPairingMode::Suggest, ..Default::default() };
+    let target = load_fixture("x86", "target.o", &config);
+    let base = load_fixture("pairing", "base.o", &config);
+    let result = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+    let mut out = String::new();
+    for pairing in &result.pairings {
+        let (_, left) = target.section_symbol(pairing.left);
+        let (_, right) = base.section_symbol(pairing.right);
+        writeln!(
+            out,
+            "{} -> {} confidence: {:.2} applied: {}",
+            left.name, right.name, pairing.confidence, pairing.applied
+        )
+        .unwrap();
+    }
+    // Suggestions are not used for the diff
+    let left = result.left.as_ref().unwrap();
+    assert!(left.sections.iter().flat_map(|s| &s.symbols).all(|s| s.target_symbol.is_none()));
+
+    config.symbol_pairing = SymbolPairingMode::Apply;
+    let result = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+    out.push_str("\n# target\n");
+    render(&mut out, &target, result.left.as_ref().unwrap());
+    out.push_str("\n# base\n");
+    render(&mut out, &base, result.right.as_ref().unwrap());
+    insta::assert_snapshot!("symbol_pairing", out);
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn archive_member() {
+    let config = DiffObjConfig::default();
+    let archive = fixture_path("x86", "lib.a");
+    let member = read(&member_path(&archive, "base.o"), &config).unwrap();
+    let base = load_fixture("x86", "base.o", &config);
+    let render_obj = |obj: &ObjInfo| {
+        let result = diff_objs(&config, Some(obj), None, None).unwrap();
+        let mut out = String::new();
+        render(&mut out, obj, result.left.as_ref().unwrap());
+        out
+    };
+    assert_eq!(render_obj(&member), render_obj(&base));
+    // Archives with more than one member require a selector
+    assert!(read(&archive, &config).is_err());
+    assert!(read(&member_path(&archive, "missing.o"), &config).is_err());
+    // Symbol lookups resolve the member too
+    let member = member_path(&archive, "base.o");
+    assert!(has_function(&member, "_accumulate").unwrap());
+    assert!(!has_function(&member, "_counter").unwrap());
+    assert!(has_symbol(&member, "_counter").unwrap());
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn linked_region() {
+    let config = DiffObjConfig { relax_reloc_diffs: true, ..Default::default() };
+    let exe = fixture_path("linked", "target.exe");
+    let region = TargetRegion { start: Some(0x401018), end: Some(0x40106c), ..Default::default() };
+    let target = read_target(&exe, Some(&region), None, &config).unwrap();
+    let base = load_fixture("x86", "base.o", &config);
+    let result = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+    let mut out = String::new();
+    out.push_str("# target\n");
+    render(&mut out, &target, result.left.as_ref().unwrap());
+    out.push_str("\n# base\n");
+    render(&mut out, &base, result.right.as_ref().unwrap());
+    insta::assert_snapshot!("linked", out);
+
+    // A symbol map alone selects the sections and symbols it covers
+    let region =
+        TargetRegion { symbols: Some(fixture_path("linked", "target.map")), ..Default::default() };
+    let mapped = read_target(&exe, Some(&region), None, &config).unwrap();
+    let symbols = mapped
+        .sections
+        .iter()
+        .flat_map(|s| {
+            s.symbols.iter().map(move |sym| (s.name.as_str(), sym.name.as_str(), sym.address))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(symbols, [
+        (".data", "_counter", 0x402004),
+        (".text", "_add_one", 0x401018),
+        (".text", "_accumulate", 0x401020),
+        (".text", "_select", 0x401037),
+    ]);
+    assert!(read_target(&exe, Some(&TargetRegion::default()), None, &config).is_err());
+
+    // A linker map selects the symbols of a single unit
+    let region = TargetRegion {
+        symbols: Some(fixture_path("linked", "target.ld.map")),
+        unit: Some("x86/target.o".to_string()),
+        ..Default::default()
+    };
+    let mapped = read_target(&exe, Some(&region), None, &config).unwrap();
+    let symbols = mapped
+        .sections
+        .iter()
+        .flat_map(|s| {
+            s.symbols.iter().map(move |sym| (s.name.as_str(), sym.name.as_str(), sym.size))
+        })
+        .collect::<Vec<_>>();
+    // GNU ld omits the leading underscore of i386 PE symbols
+    assert_eq!(symbols, [
+        (".data", "counter", 0x8),
+        (".text", "add_one", 0x8),
+        (".text", "accumulate", 0x17),
+        (".text", "select", 0x35),
+    ]);
+
+    // A map that was already read from the same file is used instead of reading it again
+    let mut map = read_linker_map(region.symbols.as_ref().unwrap()).unwrap();
+    map.symbols.retain(|s| s.name != "select");
+    let mapped = read_target(&exe, Some(&region), Some(&map), &config).unwrap();
+    assert!(mapped.sections.iter().flat_map(|s| &s.symbols).all(|s| s.name != "select"));
+}
+
+#[test]
+#[cfg(feature = "x86")]
+fn project_index() {
+    let config = DiffObjConfig { relax_reloc_diffs: true, ..Default::default() };
+    let units = [
+        ProjectObject {
+            name: Some("x86".to_string()),
+            target_path: Some(fixture_path("x86", "target.o")),
+            base_path: Some(fixture_path("x86", "base.o")),
+            ..Default::default()
+        },
+        ProjectObject {
+            name: Some("linked".to_string()),
+            target_path: Some(fixture_path("linked", "target.exe")),
+            base_path: Some(fixture_path("x86", "base.o")),
+            target_region: Some(TargetRegion {
+                start: Some(0x401018),
+                end: Some(0x40106c),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        // Objects that haven't been built are skipped
+        ProjectObject {
+            name: Some("missing".to_string()),
+            target_path: Some(fixture_path("x86", "missing.o")),
+            ..Default::default()
+        },
+    ];
+    let mut index = ProjectIndex::default();
+    for unit in &units {
+        index_unit(&mut index, unit, None, &config).unwrap();
+    }
+    let search = |query: &str| {
+        index
+            .search(query)
+            .into_iter()
+            .map(|s| (s.unit.as_str(), s.name(), s.match_percent.map(|p| p.round() as u32)))
+            .collect::<Vec<_>>()
+    };
+    // Exact matches are listed first, searches are case-insensitive
+    assert_eq!(search("_ACCUMULATE"), [
+        ("linked", "_accumulate", Some(40)),
+        ("x86", "_accumulate", Some(40)),
+    ]);
+    assert_eq!(search("0x401020"), [("linked", "_accumulate", Some(40))]);
+    assert!(search("").is_empty());
+    assert!(index.symbols.iter().all(|s| s.unit != "missing"));
+
+    // The renamed copy of the function is found by its fingerprint
+    let renamed = ProjectObject {
+        name: Some("pairing".to_string()),
+        base_path: Some(fixture_path("pairing", "base.o")),
+        ..Default::default()
+    };
+    index_unit(&mut index, &renamed, None, &config).unwrap();
+    let fingerprint = index.find_symbol("x86", "_accumulate").unwrap().fingerprint.clone().unwrap();
+    let similar = index
+        .find_similar(&fingerprint, MIN_SIMILARITY)
+        .into_iter()
+        .map(|s| (s.symbol.unit.as_str(), s.symbol.name(), (s.similarity * 100.0).round() as u32))
+        .collect::<Vec<_>>();
+    insta::assert_debug_snapshot!("similar_functions", similar);
+
+    // Units are diffed with their own symbol mappings
+    let mut mappings = SymbolMappings::new();
+    mappings.insert("_accumulate".to_string(), "_Accumulate".to_string());
+    let mapped = ProjectObject {
+        name: Some("mapped".to_string()),
+        target_path: Some(fixture_path("x86", "target.o")),
+        base_path: Some(fixture_path("pairing", "base.o")),
+        symbol_mappings: Some(mappings),
+        ..Default::default()
+    };
+    let mut index = ProjectIndex::default();
+    index_unit(&mut index, &mapped, None, &config).unwrap();
+    let symbol = index.find_symbol("mapped", "_accumulate").unwrap();
+    assert_eq!(symbol.base_name.as_deref(), Some("_Accumulate"));
+    assert!(symbol.match_percent.is_some());
+}
+
+#[test]
+fn linker_maps() {
+    let mut out = String::new();
+    for path in [
+        fixture_path("maps", "codewarrior.map"),
+        fixture_path("maps", "msvc.map"),
+        fixture_path("linked", "target.ld.map"),
+    ] {
+        let map = read_linker_map(&path).unwrap();
+        writeln!(out, "# {} ({:?})", path.file_name().unwrap().to_string_lossy(), map.format)
+            .unwrap();
+        for symbol in &map.symbols {
+            writeln!(
+                out,
+                "{:#x} size: {:#x} {:?} {} {} unit: {}",
+                symbol.address,
+                symbol.size,
+                symbol.kind,
+                symbol.section.as_deref().unwrap_or("-"),
+                symbol.name,
+                symbol.unit.as_deref().unwrap_or("-")
+            )
+            .unwrap();
+        }
+        let units = map.units().into_iter().map(unit_name).collect::<Vec<_>>();
+        writeln!(out, "units: {}\n", units.join(", ")).unwrap();
+    }
+    insta::assert_snapshot!("linker_maps", out);
+
+    // Units already in the project aren't discovered again
+    let map = read_linker_map(&fixture_path("linked", "target.ld.map")).unwrap();
+    let config = ProjectConfig {
+        base_dir: Some(PathBuf::from("build")),
+        units: Some(vec![ProjectObject {
+            name: Some("linked/start".to_string()),
+            ..Default::default()
+        }]),
+        linker_map: Some(ProjectLinkerMap {
+            path: PathBuf::from("target.ld.map"),
+            target_path: Some(PathBuf::from("target.exe")),
+        }),
+        ..Default::default()
+    };
+    let units = discover_units(&map, &config);
+    assert_eq!(units.len(), 1);
+    assert_eq!(units[0].name(), "x86/target");
+    assert_eq!(units[0].base_path.as_deref(), Some(Path::new("build/x86/target.o")));
+    assert_eq!(
+        units[0].target_region.as_ref().and_then(|r| r.unit.as_deref()),
+        Some("x86/target.o")
+    );
+}
+
+#[test]
+fn symbol_mapping_config() {
+    let dir = std::env::temp_dir().join(format!("objdiff-mappings-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut mappings = SymbolMappings::new();
+    mappings.insert("func_80001000".to_string(), "Player_Update".to_string());
+
+    // Formatting and keys unknown to the config are kept
+    let json = "{\n    \"custom_key\": 1,\n    \"units\": [\n        {\n            \"name\": \"main/player\"\n        }\n    ]\n}\n";
+    std::fs::write(dir.join("objdiff.json"), json).unwrap();
+    let (_, info) = try_project_config(&dir).unwrap();
+    let info = save_unit_symbol_mappings(&info, "main/player", &mappings).unwrap();
+    let saved = std::fs::read_to_string(&info.path).unwrap();
+    assert_eq!(
+        saved,
+        "{\n    \"custom_key\": 1,\n    \"units\": [\n        {\n            \"name\": \"main/player\",\n            \"symbol_mappings\": {\n                \"func_80001000\": \"Player_Update\"\n            }\n        }\n    ]\n}\n"
+    );
+    let info = save_unit_symbol_mappings(&info, "main/player", &SymbolMappings::new()).unwrap();
+    assert_eq!(std::fs::read_to_string(&info.path).unwrap(), json);
+    assert!(save_unit_symbol_mappings(&info, "main/missing", &mappings).is_err());
+
+    // Changes made since the config was read are not overwritten
+    filetime::set_file_mtime(&info.path, filetime::FileTime::from_unix_time(0, 0)).unwrap();
+    assert!(save_unit_symbol_mappings(&info, "main/player", &mappings).is_err());
+    std::fs::remove_file(&info.path).unwrap();
+
+    let yaml = "units:\n- path: main/player.o\n  custom_key: 1\n";
+    std::fs::write(dir.join("objdiff.yml"), yaml).unwrap();
+    let (_, info) = try_project_config(&dir).unwrap();
+    let info = save_unit_symbol_mappings(&info, "main/player.o", &mappings).unwrap();
+    let (config, _) = try_project_config(&dir).unwrap();
+    assert_eq!(config.unwrap().units()[0].symbol_mappings.as_ref(), Some(&mappings));
+    assert_eq!(
+        std::fs::read_to_string(&info.path).unwrap(),
+        "units:\n- path: main/player.o\n  custom_key: 1\n  symbol_mappings:\n    func_80001000: Player_Update\n"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn symbol_mapping_formatting() {
+    let dir = std::env::temp_dir().join(format!("objdiff-formatting-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let save = |file_name: &str, text: &str, unit: &str, mappings: &[(&str, &str)]| {
+        let path = dir.join(file_name);
+        std::fs::write(&path, text).unwrap();
+        let (_, info) = try_project_config(&dir).unwrap();
+        let mappings = mappings.iter().map(|&(l, r)| (l.to_string(), r.to_string())).collect();
+        save_unit_symbol_mappings(&info, unit, &mappings).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        saved
+    };
+
+    // Hand-formatted JSON only changes where the mappings are
+    let json = r#"{
+  "custom_args": ["-j", "8"],
+  "units": [
+    {"name": "main/other", "complete": true},
+    {
+      "name": "main/player",
+      "metadata": {"complete": false}
+    }
+  ]
+}"#;
+    let added = save("objdiff.json", json, "main/player", &[("func_80001000", "Player_Update")]);
+    assert_eq!(
+        added,
+        r#"{
+  "custom_args": ["-j", "8"],
+  "units": [
+    {"name": "main/other", "complete": true},
+    {
+      "name": "main/player",
+      "metadata": {"complete": false},
+      "symbol_mappings": {
+        "func_80001000": "Player_Update"
+      }
+    }
+  ]
+}"#
+    );
+    let replaced = save("objdiff.json", &added, "main/player", &[("a", "b"), ("c", "d")]);
+    assert_eq!(
+        replaced,
+        added.replace(r#""func_80001000": "Player_Update""#, "\"a\": \"b\",\n        \"c\": \"d\"")
+    );
+    assert_eq!(save("objdiff.json", &replaced, "main/player", &[]), json);
+    let inline = save("objdiff.json", json, "main/other", &[("a", "b")]);
+    assert_eq!(
+        inline,
+        json.replace(r#""complete": true}"#, r#""complete": true, "symbol_mappings": {"a":"b"}}"#)
+    );
+    assert_eq!(save("objdiff.json", &inline, "main/other", &[]), json);
+
+    // Comments in YAML are kept, including those next to the mappings
+    let yaml = "# Project config
+units:
+  # Already matched
+  - name: main/other # Other
+    complete: true
+
+  - name: main/player
+    # Mappings from the map
+    symbol_mappings:
+      old_a: new_a # Stale
+    metadata:
+      complete: false
+# Trailing comment
+";
+    let replaced = save("objdiff.yml", yaml, "main/player", &[("func_80001000", "Player_Update")]);
+    assert_eq!(replaced, yaml.replace("old_a: new_a # Stale", "func_80001000: Player_Update"));
+    let removed = save("objdiff.yml", &replaced, "main/player", &[]);
+    assert_eq!(removed, yaml.replace("    symbol_mappings:\n      old_a: new_a # Stale\n", ""));
+    let added = save("objdiff.yml", &removed, "main/player", &[("a", "b")]);
+    assert_eq!(
+        added,
+        removed.replace(
+            "      complete: false\n",
+            "      complete: false\n    symbol_mappings:\n      a: b\n"
+        )
+    );
+    let added = save("objdiff.yml", yaml, "main/other", &[("a", "b")]);
+    assert_eq!(
+        added,
+        yaml.replace(
+            "    complete: true\n",
+            "    complete: true\n    symbol_mappings:\n      a: b\n"
+        )
+    );
+
+    // Mappings on the first line of a unit are replaced by the next key
+    let yaml = "units:\n- symbol_mappings:\n    a: b\n  name: main/player\n";
+    assert_eq!(save("objdiff.yml", yaml, "main/player", &[]), "units:\n- name: main/player\n");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "arm")]
+fn arm_elf() { check_fixture("arm"); }
+
+#[test]
+#[cfg(feature = "arm64")]
+fn arm64_elf() { check_fixture("arm64"); }
+
+#[test]
+#[cfg(feature = "arm64")]
+fn arm64_macho() { check_fixture("macho_arm64"); }
+
+#[test]
+#[cfg(all(feature = "x86", feature = "dwarf"))]
+fn dwarf_data_fields() {
+    let config = DiffObjConfig::default();
+    let target = load_fixture("dwarf", "target.o", &config);
+    let base = load_fixture("dwarf", "base.o", &config);
+    let result = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+    let mut out = String::new();
+    out.push_str("# target\n");
+    render_data_fields(&mut out, &target, result.left.as_ref().unwrap());
+    out.push_str("\n# base\n");
+    render_data_fields(&mut out, &base, result.right.as_ref().unwrap());
+    insta::assert_snapshot!("dwarf", out);
+}
+
+#[test]
+#[cfg(all(feature = "x86", feature = "dwarf"))]
+fn dwarf_static_members() {
+    let config = DiffObjConfig::default();
+    let obj = load_fixture("dwarf", "members.o", &config);
+    let result = diff_objs(&config, Some(&obj), Some(&obj), None).unwrap();
+    let mut out = String::new();
+    render_data_fields(&mut out, &obj, result.left.as_ref().unwrap());
+    insta::assert_snapshot!("dwarf_static_members", out);
+}
+
+#[test]
+#[cfg(all(feature = "x86", feature = "dwarf"))]
+fn dwarf_function_info() {
+    let config = DiffObjConfig::default();
+    let mut out = String::new();
+    for name in ["func-O0.o", "func-O1.o"] {
+        let obj = load_fixture("dwarf", name, &config);
+        let result = diff_objs(&config, Some(&obj), None, None).unwrap();
+        writeln!(out, "# {name}").unwrap();
+        render_function_info(&mut out, &obj, result.left.as_ref().unwrap());
+    }
+    insta::assert_snapshot!("dwarf_function_info", out);
+}
+
+#[test]
+#[cfg(all(feature = "x86", feature = "dwarf"))]
+fn dwarf_source_lines() {
+    use objdiff_core::diff::display::source_line_diffs;
+
+    let config = DiffObjConfig::default();
+    let target = load_fixture("dwarf", "func-O1.o", &config);
+    let base = load_fixture("dwarf", "func-O0.o", &config);
+    let result = diff_objs(&config, Some(&target), Some(&base), None).unwrap();
+    let diff = result.right.as_ref().unwrap();
+    let mut out = String::new();
+    for (section, section_diff) in base.sections.iter().zip(&diff.sections) {
+        if section.kind != ObjSectionKind::Code {
+            continue;
+        }
+        for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+            writeln!(out, "{}", symbol.name).unwrap();
+            for (line, mismatched) in source_line_diffs(symbol_diff) {
+                writeln!(out, "  {line}: {}", if mismatched { "mismatch" } else { "match" })
+                    .unwrap();
+            }
+        }
+    }
+    insta::assert_snapshot!("dwarf_source_lines", out);
+}
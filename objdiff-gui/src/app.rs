@@ -15,12 +15,15 @@ use globset::Glob;
 use objdiff_core::{
     build::watcher::{create_watcher, Watcher},
     config::{
-        build_globset, default_watch_patterns, save_project_config, ProjectConfig,
-        ProjectConfigInfo, ProjectObject, ScratchConfig, SymbolMappings, DEFAULT_WATCH_PATTERNS,
+        build_globset, default_watch_patterns, save_unit_symbol_mappings, ProjectConfig,
+        ProjectConfigInfo, ProjectObject, ScratchConfig, SymbolMappings, TargetRegion,
+        DEFAULT_WATCH_PATTERNS,
     },
-    diff::DiffObjConfig,
+    diff::{DiffAlgorithm, DiffObjConfig, SymbolPairingMode},
     jobs::{Job, JobQueue, JobResult},
+    obj::map::LinkerMap,
 };
+use strum::{EnumMessage, VariantArray};
 use time::UtcOffset;
 
 use crate::{
@@ -42,6 +45,7 @@ use crate::{
         jobs::{jobs_menu_ui, jobs_window},
         rlwinm::{rlwinm_decode_window, RlwinmDecodeViewState},
         symbol_diff::{symbol_diff_ui, DiffViewAction, DiffViewNavigation, DiffViewState, View},
+        symbol_search::{symbol_search_window, SymbolSearchViewState},
     },
 };
 
@@ -51,11 +55,13 @@ pub struct ViewState {
     pub demangle_state: DemangleViewState,
     pub rlwinm_decode_state: RlwinmDecodeViewState,
     pub diff_state: DiffViewState,
+    pub symbol_search_state: SymbolSearchViewState,
     pub graphics_state: GraphicsViewState,
     pub frame_history: FrameHistory,
     pub show_appearance_config: bool,
     pub show_demangle: bool,
     pub show_rlwinm_decode: bool,
+    pub show_symbol_search: bool,
     pub show_project_config: bool,
     pub show_arch_config: bool,
     pub show_debug: bool,
@@ -72,11 +78,13 @@ impl Default for ViewState {
             demangle_state: Default::default(),
             rlwinm_decode_state: Default::default(),
             diff_state: Default::default(),
+            symbol_search_state: Default::default(),
             graphics_state: Default::default(),
             frame_history: Default::default(),
             show_appearance_config: false,
             show_demangle: false,
             show_rlwinm_decode: false,
+            show_symbol_search: false,
             show_project_config: false,
             show_arch_config: false,
             show_debug: false,
@@ -93,6 +101,7 @@ pub struct ObjectConfig {
     pub name: String,
     pub target_path: Option<PathBuf>,
     pub base_path: Option<PathBuf>,
+    pub target_region: Option<TargetRegion>,
     pub reverse_fn_order: Option<bool>,
     pub complete: Option<bool>,
     pub scratch: Option<ScratchConfig>,
@@ -107,6 +116,7 @@ impl From<&ProjectObject> for ObjectConfig {
             name: object.name().to_string(),
             target_path: object.target_path.clone(),
             base_path: object.base_path.clone(),
+            target_region: object.target_region.clone(),
             reverse_fn_order: object.reverse_fn_order(),
             complete: object.complete(),
             scratch: object.scratch.clone(),
@@ -130,6 +140,8 @@ pub struct AppState {
     pub queue_reload: bool,
     pub current_project_config: Option<ProjectConfig>,
     pub project_config_info: Option<ProjectConfigInfo>,
+    /// The project's linker map, if configured
+    pub linker_map: Option<Arc<LinkerMap>>,
     pub last_mod_check: Instant,
     /// The right object symbol name that we're selecting a left symbol for
     pub selecting_left: Option<String>,
@@ -151,6 +163,7 @@ impl Default for AppState {
             queue_reload: false,
             current_project_config: None,
             project_config_info: None,
+            linker_map: None,
             last_mod_check: Instant::now(),
             selecting_left: None,
             selecting_right: None,
@@ -372,13 +385,13 @@ impl AppState {
                     Some(object.symbol_mappings.clone())
                 };
             }
-        }
-        // Save the updated project config
-        match save_project_config(config, info) {
-            Ok(new_info) => *info = new_info,
-            Err(e) => {
-                log::error!("Failed to save project config: {e}");
-                self.config_error = Some(format!("Failed to save project config: {e}"));
+            // Save the updated mappings, leaving the rest of the project config as written
+            match save_unit_symbol_mappings(info, &object.name, &object.symbol_mappings) {
+                Ok(new_info) => *info = new_info,
+                Err(e) => {
+                    log::error!("Failed to save project config: {e}");
+                    self.config_error = Some(format!("Failed to save project config: {e}"));
+                }
             }
         }
     }
@@ -465,7 +478,8 @@ impl App {
     fn pre_update(&mut self, ctx: &egui::Context) {
         self.appearance.pre_update(ctx);
 
-        let ViewState { jobs, diff_state, config_state, .. } = &mut self.view_state;
+        let ViewState { jobs, diff_state, config_state, symbol_search_state, .. } =
+            &mut self.view_state;
 
         jobs.collect_results();
         jobs.results.retain(|result| match result {
@@ -480,6 +494,7 @@ impl App {
         });
         diff_state.pre_update(jobs, &self.state);
         config_state.pre_update(jobs, &self.state);
+        symbol_search_state.pre_update(jobs);
         debug_assert!(jobs.results.is_empty());
     }
 
@@ -490,7 +505,26 @@ impl App {
 
         self.appearance.post_update(ctx);
 
-        let ViewState { jobs, diff_state, config_state, graphics_state, .. } = &mut self.view_state;
+        let ViewState {
+            jobs,
+            diff_state,
+            config_state,
+            graphics_state,
+            symbol_search_state,
+            show_symbol_search,
+            ..
+        } = &mut self.view_state;
+        if let Some(DiffViewAction::FindSimilar(column, symbol_ref)) = &action {
+            symbol_search_state.find_similar(
+                ctx,
+                jobs,
+                &self.state,
+                diff_state,
+                *column,
+                *symbol_ref,
+            );
+            *show_symbol_search = true;
+        }
         config_state.post_update(ctx, jobs, &self.state);
         diff_state.post_update(action, ctx, jobs, &self.state);
 
@@ -544,7 +578,10 @@ impl App {
         }
 
         if state.obj_change {
+            // Keep any pending navigation into the new object, e.g. from the symbol search
+            let post_build_nav = diff_state.post_build_nav.take();
             *diff_state = Default::default();
+            diff_state.post_build_nav = post_build_nav;
             if state.config.selected_obj.is_some() {
                 state.queue_build = true;
             }
@@ -580,11 +617,11 @@ impl App {
             && state.config.selected_obj.is_some()
             && !jobs.is_running(Job::ObjDiff)
         {
-            start_build(ctx, jobs, create_objdiff_config(state));
+            start_build(ctx, jobs, create_objdiff_config(state, diff_state));
             state.queue_build = false;
             state.queue_reload = false;
         } else if state.queue_reload && !jobs.is_running(Job::ObjDiff) {
-            let mut diff_config = create_objdiff_config(state);
+            let mut diff_config = create_objdiff_config(state, diff_state);
             // Don't build, just reload the current files
             diff_config.build_base = false;
             diff_config.build_target = false;
@@ -621,11 +658,13 @@ impl eframe::App for App {
             demangle_state,
             rlwinm_decode_state,
             diff_state,
+            symbol_search_state,
             graphics_state,
             frame_history,
             show_appearance_config,
             show_demangle,
             show_rlwinm_decode,
+            show_symbol_search,
             show_project_config,
             show_arch_config,
             show_debug,
@@ -703,6 +742,10 @@ impl eframe::App for App {
                         *show_rlwinm_decode = !*show_rlwinm_decode;
                         ui.close_menu();
                     }
+                    if ui.button("Symbol Search…").clicked() {
+                        *show_symbol_search = !*show_symbol_search;
+                        ui.close_menu();
+                    }
                 });
                 ui.menu_button("Diff Options", |ui| {
                     if ui.button("Arch Settings…").clicked() {
@@ -740,6 +783,19 @@ impl eframe::App for App {
                     {
                         state.queue_reload = true;
                     }
+                    if ui
+                        .checkbox(
+                            &mut state.config.diff_obj_config.register_renaming,
+                            "Tolerate register renaming",
+                        )
+                        .on_hover_text(
+                            "Instructions that only differ by a consistent register renaming \
+                            are shown as near-matches.",
+                        )
+                        .changed()
+                    {
+                        state.queue_reload = true;
+                    }
                     if ui
                         .checkbox(
                             &mut state.config.diff_obj_config.space_between_args,
@@ -759,6 +815,71 @@ impl eframe::App for App {
                     {
                         state.queue_reload = true;
                     }
+                    egui::ComboBox::new("symbol_pairing", "Symbol pairing")
+                        .selected_text(
+                            state.config.diff_obj_config.symbol_pairing.get_message().unwrap(),
+                        )
+                        .show_ui(ui, |ui| {
+                            for &mode in SymbolPairingMode::VARIANTS {
+                                if ui
+                                    .selectable_label(
+                                        state.config.diff_obj_config.symbol_pairing == mode,
+                                        mode.get_message().unwrap(),
+                                    )
+                                    .on_hover_text(mode.get_detailed_message().unwrap())
+                                    .clicked()
+                                {
+                                    state.config.diff_obj_config.symbol_pairing = mode;
+                                    state.queue_reload = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "Pairs symbols that couldn't be matched by name by their similarity.",
+                        );
+                    egui::ComboBox::new("diff_algorithm", "Alignment")
+                        .selected_text(
+                            state.config.diff_obj_config.diff_algorithm.get_message().unwrap(),
+                        )
+                        .show_ui(ui, |ui| {
+                            for &algorithm in DiffAlgorithm::VARIANTS {
+                                if ui
+                                    .selectable_label(
+                                        state.config.diff_obj_config.diff_algorithm == algorithm,
+                                        algorithm.get_message().unwrap(),
+                                    )
+                                    .on_hover_text(algorithm.get_detailed_message().unwrap())
+                                    .clicked()
+                                {
+                                    state.config.diff_obj_config.diff_algorithm = algorithm;
+                                    state.queue_reload = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Algorithm used to align the instructions of functions.");
+                    ui.horizontal(|ui| {
+                        let mut budget = state.config.diff_obj_config.diff_time_budget_ms;
+                        let mut limited = budget.is_some();
+                        if ui
+                            .checkbox(&mut limited, "Alignment time limit")
+                            .on_hover_text(
+                                "Stops searching for the best alignment of a function's \
+                                instructions after this long, and marks the diff as approximate.",
+                            )
+                            .changed()
+                        {
+                            budget = limited.then_some(1000);
+                        }
+                        if let Some(ms) = &mut budget {
+                            ui.add(egui::DragValue::new(ms).range(1..=60000).suffix(" ms"));
+                        }
+                        if budget != state.config.diff_obj_config.diff_time_budget_ms {
+                            state.config.diff_obj_config.diff_time_budget_ms = budget;
+                            state.queue_reload = true;
+                        }
+                    });
                     if ui.button("Clear custom symbol mappings").clicked() {
                         state.clear_mappings();
                         diff_state.post_build_nav = Some(DiffViewNavigation::symbol_diff());
@@ -798,6 +919,15 @@ impl eframe::App for App {
         appearance_window(ctx, show_appearance_config, appearance);
         demangle_window(ctx, show_demangle, demangle_state, appearance);
         rlwinm_decode_window(ctx, show_rlwinm_decode, rlwinm_decode_state, appearance);
+        symbol_search_window(
+            ctx,
+            show_symbol_search,
+            state,
+            symbol_search_state,
+            diff_state,
+            jobs,
+            appearance,
+        );
         arch_config_window(ctx, state, show_arch_config, appearance);
         debug_window(ctx, show_debug, frame_history, appearance);
         graphics_window(ctx, show_graphics, frame_history, graphics_state, appearance);
@@ -817,6 +947,7 @@ impl eframe::App for App {
 
 #[inline]
 fn file_modified(path: &Path, last_ts: FileTime) -> bool {
+    let (path, _) = objdiff_core::obj::read::split_member_path(path);
     if let Ok(metadata) = fs::metadata(path) {
         FileTime::from_last_modification_time(&metadata) != last_ts
     } else {
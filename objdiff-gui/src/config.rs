@@ -1,8 +1,14 @@
-use std::path::{Component, Path};
+use std::{
+    path::{Component, Path},
+    sync::Arc,
+};
 
 use anyhow::Result;
 use globset::Glob;
-use objdiff_core::config::{try_project_config, ProjectObject, DEFAULT_WATCH_PATTERNS};
+use objdiff_core::{
+    config::{try_project_config, ProjectObject, DEFAULT_WATCH_PATTERNS},
+    obj::map::{discover_units, read_project_map},
+};
 
 use crate::app::{AppState, ObjectConfig};
 
@@ -102,8 +108,21 @@ pub fn load_project_config(state: &mut AppState) -> Result<()> {
         state.config.watch_patterns = project_config.watch_patterns.clone().unwrap_or_else(|| {
             DEFAULT_WATCH_PATTERNS.iter().map(|s| Glob::new(s).unwrap()).collect()
         });
+        state.config.diff_obj_config.score_weights =
+            project_config.score_weights.unwrap_or_default();
         state.watcher_change = true;
         state.objects = project_config.units.clone().unwrap_or_default();
+        // Units discovered from the linker map are not written back to the project config
+        state.linker_map = match read_project_map(&project_config, project_dir) {
+            Ok(linker_map) => linker_map.map(Arc::new),
+            Err(e) => {
+                log::error!("Failed to load linker map: {e}");
+                None
+            }
+        };
+        if let Some(linker_map) = &state.linker_map {
+            state.objects.extend(discover_units(linker_map, &project_config));
+        }
         state.object_nodes = build_nodes(
             &mut state.objects,
             project_dir,
@@ -8,12 +8,15 @@ use jobs::create_scratch;
 use objdiff_core::{
     build::BuildConfig,
     jobs,
-    jobs::{check_update::CheckUpdateConfig, objdiff, update::UpdateConfig, Job, JobQueue},
+    jobs::{
+        check_update::CheckUpdateConfig, objdiff, symbol_index, update::UpdateConfig, Job, JobQueue,
+    },
 };
 
 use crate::{
     app::{AppConfig, AppState},
     update::{build_updater, BIN_NAME_NEW, BIN_NAME_OLD},
+    views::symbol_diff::DiffViewState,
 };
 
 struct EguiWaker(egui::Context);
@@ -88,7 +91,10 @@ impl From<&AppConfig> for BuildConfig {
     }
 }
 
-pub fn create_objdiff_config(state: &AppState) -> objdiff::ObjDiffConfig {
+pub fn create_objdiff_config(
+    state: &AppState,
+    diff_state: &DiffViewState,
+) -> objdiff::ObjDiffConfig {
     objdiff::ObjDiffConfig {
         build_config: BuildConfig::from(&state.config),
         build_base: state.config.build_base,
@@ -105,6 +111,13 @@ pub fn create_objdiff_config(state: &AppState) -> objdiff::ObjDiffConfig {
             .as_ref()
             .and_then(|obj| obj.base_path.as_ref())
             .cloned(),
+        target_region: state
+            .config
+            .selected_obj
+            .as_ref()
+            .and_then(|obj| obj.target_region.as_ref())
+            .cloned(),
+        linker_map: state.linker_map.clone(),
         diff_obj_config: state.config.diff_obj_config.clone(),
         symbol_mappings: state
             .config
@@ -115,6 +128,8 @@ pub fn create_objdiff_config(state: &AppState) -> objdiff::ObjDiffConfig {
             .unwrap_or_default(),
         selecting_left: state.selecting_left.clone(),
         selecting_right: state.selecting_right.clone(),
+        last_build: diff_state.build.clone(),
+        compare_base: diff_state.compare_base.clone(),
     }
 }
 
@@ -122,6 +137,15 @@ pub fn start_build(ctx: &egui::Context, jobs: &mut JobQueue, config: objdiff::Ob
     jobs.push_once(Job::ObjDiff, || objdiff::start_build(egui_waker(ctx), config));
 }
 
+pub fn start_symbol_index(ctx: &egui::Context, jobs: &mut JobQueue, state: &AppState) {
+    let config = symbol_index::SymbolIndexConfig {
+        units: state.objects.clone(),
+        linker_map: state.linker_map.clone(),
+        diff_obj_config: state.config.diff_obj_config.clone(),
+    };
+    jobs.push_once(Job::SymbolIndex, || symbol_index::start_symbol_index(egui_waker(ctx), config));
+}
+
 pub fn start_check_update(ctx: &egui::Context, jobs: &mut JobQueue) {
     jobs.push_once(Job::Update, || {
         jobs::check_update::start_check_update(egui_waker(ctx), CheckUpdateConfig {
@@ -16,6 +16,7 @@ use objdiff_core::{
     config::{ProjectObject, DEFAULT_WATCH_PATTERNS},
     diff::{ArmArchVersion, ArmR9Usage, MipsAbi, MipsInstrCategory, X86Formatter},
     jobs::{check_update::CheckUpdateResult, Job, JobQueue, JobResult},
+    obj::read,
 };
 use strum::{EnumMessage, VariantArray};
 
@@ -48,6 +49,10 @@ pub struct ConfigViewState {
     #[cfg(all(windows, feature = "wsl"))]
     pub available_wsl_distros: Option<Vec<String>>,
     pub file_dialog_state: FileDialogState,
+    /// Members of an archive picked with the object file dialog, awaiting selection
+    pub archive_members: Option<(PathBuf, Vec<String>)>,
+    /// Object (or archive member) path to select on the next update
+    pub queue_object: Option<PathBuf>,
 }
 
 impl ConfigViewState {
@@ -79,28 +84,39 @@ impl ConfigViewState {
                 let mut guard = state.write().unwrap();
                 guard.set_base_obj_dir(path.to_path_buf());
             }
-            FileDialogResult::Object(path) => {
-                let mut guard = state.write().unwrap();
-                if let (Some(base_dir), Some(target_dir)) =
-                    (&guard.config.base_obj_dir, &guard.config.target_obj_dir)
-                {
-                    if let Ok(obj_path) = path.strip_prefix(base_dir) {
-                        let target_path = target_dir.join(obj_path);
-                        guard.set_selected_obj(ObjectConfig {
-                            name: obj_path.display().to_string(),
-                            target_path: Some(target_path),
-                            base_path: Some(path),
-                            ..Default::default()
-                        });
-                    } else if let Ok(obj_path) = path.strip_prefix(target_dir) {
-                        let base_path = base_dir.join(obj_path);
-                        guard.set_selected_obj(ObjectConfig {
-                            name: obj_path.display().to_string(),
-                            target_path: Some(path),
-                            base_path: Some(base_path),
-                            ..Default::default()
-                        });
-                    }
+            FileDialogResult::Object(path) => match read::archive_members(&path) {
+                Ok(Some(members)) if members.len() > 1 => {
+                    self.archive_members = Some((path, members));
+                }
+                Ok(Some(members)) if members.len() == 1 => {
+                    self.queue_object = Some(read::member_path(&path, &members[0]));
+                }
+                Ok(_) => self.queue_object = Some(path),
+                Err(e) => log::error!("Failed to read {}: {e:?}", path.display()),
+            },
+        }
+
+        if let Some(path) = self.queue_object.take() {
+            let mut guard = state.write().unwrap();
+            if let (Some(base_dir), Some(target_dir)) =
+                (&guard.config.base_obj_dir, &guard.config.target_obj_dir)
+            {
+                if let Ok(obj_path) = path.strip_prefix(base_dir) {
+                    let target_path = target_dir.join(obj_path);
+                    guard.set_selected_obj(ObjectConfig {
+                        name: obj_path.display().to_string(),
+                        target_path: Some(target_path),
+                        base_path: Some(path),
+                        ..Default::default()
+                    });
+                } else if let Ok(obj_path) = path.strip_prefix(target_dir) {
+                    let base_path = base_dir.join(obj_path);
+                    guard.set_selected_obj(ObjectConfig {
+                        name: obj_path.display().to_string(),
+                        target_path: Some(path),
+                        base_path: Some(base_path),
+                        ..Default::default()
+                    });
                 }
             }
         }
@@ -233,13 +249,29 @@ pub fn config_ui(
                         Box::pin(
                             rfd::AsyncFileDialog::new()
                                 .set_directory(target_dir)
-                                .add_filter("Object file", &["o", "elf", "obj"])
+                                .add_filter("Object file", &["o", "elf", "obj", "a", "lib"])
                                 .pick_file(),
                         )
                     },
                     FileDialogResult::Object,
                 );
             }
+            if let Some((archive_path, members)) = &config_state.archive_members {
+                let mut selected = None;
+                egui::ComboBox::from_label("Archive member")
+                    .selected_text("Select member")
+                    .show_ui(ui, |ui| {
+                        for member in members {
+                            if ui.selectable_label(false, member).clicked() {
+                                selected = Some(read::member_path(archive_path, member));
+                            }
+                        }
+                    });
+                if selected.is_some() {
+                    config_state.queue_object = selected;
+                    config_state.archive_members = None;
+                }
+            }
             if let Some(obj) = selected_obj {
                 ui.label(
                     RichText::new(&obj.name)
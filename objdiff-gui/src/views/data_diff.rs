@@ -2,8 +2,8 @@ use std::{cmp::min, default::Default, mem::take};
 
 use egui::{text::LayoutJob, Id, Label, RichText, Sense, Widget};
 use objdiff_core::{
-    diff::{ObjDataDiff, ObjDataDiffKind, ObjDiff},
-    obj::ObjInfo,
+    diff::{display::display_data_reloc, ObjDataDiff, ObjDataDiffKind, ObjDataFieldDiff, ObjDiff},
+    obj::{ObjInfo, SymbolRef},
 };
 use time::format_description;
 
@@ -12,7 +12,7 @@ use crate::{
     views::{
         appearance::Appearance,
         column_layout::{render_header, render_table},
-        symbol_diff::{DiffViewAction, DiffViewNavigation, DiffViewState},
+        symbol_diff::{DiffViewAction, DiffViewNavigation, DiffViewState, SymbolRefByName},
         write_text,
     },
 };
@@ -23,6 +23,27 @@ fn find_section(obj: &ObjInfo, section_name: &str) -> Option<usize> {
     obj.sections.iter().position(|section| section.name == section_name)
 }
 
+fn find_symbol(
+    obj: &ObjInfo,
+    section_idx: usize,
+    selected_symbol: &SymbolRefByName,
+) -> Option<SymbolRef> {
+    let symbol_idx = obj.sections[section_idx]
+        .symbols
+        .iter()
+        .position(|symbol| symbol.name == selected_symbol.symbol_name)?;
+    Some(SymbolRef { section_idx, symbol_idx })
+}
+
+fn data_kind_color(kind: ObjDataDiffKind, appearance: &Appearance) -> egui::Color32 {
+    match kind {
+        ObjDataDiffKind::None => appearance.text_color,
+        ObjDataDiffKind::Replace => appearance.replace_color,
+        ObjDataDiffKind::Delete => appearance.delete_color,
+        ObjDataDiffKind::Insert => appearance.insert_color,
+    }
+}
+
 fn data_row_ui(ui: &mut egui::Ui, address: usize, diffs: &[ObjDataDiff], appearance: &Appearance) {
     if diffs.iter().any(|d| d.kind != ObjDataDiffKind::None) {
         ui.painter().rect_filled(ui.available_rect_before_wrap(), 0.0, ui.visuals().faint_bg_color);
@@ -36,12 +57,7 @@ fn data_row_ui(ui: &mut egui::Ui, address: usize, diffs: &[ObjDataDiff], appeara
     );
     let mut cur_addr = 0usize;
     for diff in diffs {
-        let base_color = match diff.kind {
-            ObjDataDiffKind::None => appearance.text_color,
-            ObjDataDiffKind::Replace => appearance.replace_color,
-            ObjDataDiffKind::Delete => appearance.delete_color,
-            ObjDataDiffKind::Insert => appearance.insert_color,
-        };
+        let base_color = data_kind_color(diff.kind, appearance);
         if diff.data.is_empty() {
             let mut str = "   ".repeat(diff.len);
             str.push_str(" ".repeat(diff.len / 8).as_str());
@@ -68,12 +84,7 @@ fn data_row_ui(ui: &mut egui::Ui, address: usize, diffs: &[ObjDataDiff], appeara
     }
     write_text(" ", appearance.text_color, &mut job, appearance.code_font.clone());
     for diff in diffs {
-        let base_color = match diff.kind {
-            ObjDataDiffKind::None => appearance.text_color,
-            ObjDataDiffKind::Replace => appearance.replace_color,
-            ObjDataDiffKind::Delete => appearance.delete_color,
-            ObjDataDiffKind::Insert => appearance.insert_color,
-        };
+        let base_color = data_kind_color(diff.kind, appearance);
         if diff.data.is_empty() {
             write_text(
                 " ".repeat(diff.len).as_str(),
@@ -94,6 +105,16 @@ fn data_row_ui(ui: &mut egui::Ui, address: usize, diffs: &[ObjDataDiff], appeara
             write_text(text.as_str(), base_color, &mut job, appearance.code_font.clone());
         }
     }
+    for diff in diffs {
+        if let Some(reloc) = &diff.reloc {
+            write_text(
+                format!(" {}", display_data_reloc(reloc)).as_str(),
+                data_kind_color(diff.kind, appearance),
+                &mut job,
+                appearance.code_font.clone(),
+            );
+        }
+    }
     Label::new(job).sense(Sense::click()).ui(ui);
     //     .on_hover_ui_at_pointer(|ui| ins_hover_ui(ui, ins))
     //     .context_menu(|ui| ins_context_menu(ui, ins));
@@ -119,6 +140,8 @@ fn split_diffs(diffs: &[ObjDataDiff]) -> Vec<Vec<ObjDataDiff>> {
                 len,
                 // TODO
                 symbol: String::new(),
+                // Only show the relocation on the row it starts in
+                reloc: if cur_len == 0 { diff.reloc.clone() } else { None },
             });
             remaining_in_row -= len;
             cur_len += len;
@@ -134,24 +157,98 @@ fn split_diffs(diffs: &[ObjDataDiff]) -> Vec<Vec<ObjDataDiff>> {
     split_diffs
 }
 
+fn data_field_row_ui(
+    ui: &mut egui::Ui,
+    symbol_name: &str,
+    diff: &ObjDataFieldDiff,
+    appearance: &Appearance,
+) {
+    let Some(field) = &diff.field else {
+        return;
+    };
+    if diff.kind != ObjDataDiffKind::None {
+        ui.painter().rect_filled(ui.available_rect_before_wrap(), 0.0, ui.visuals().faint_bg_color);
+    }
+    let mut job = LayoutJob::default();
+    write_text(
+        format!("{:04x}: ", field.offset).as_str(),
+        appearance.text_color,
+        &mut job,
+        appearance.code_font.clone(),
+    );
+    let color = data_kind_color(diff.kind, appearance);
+    write_text(symbol_name, color, &mut job, appearance.code_font.clone());
+    write_text(&field.path, color, &mut job, appearance.code_font.clone());
+    if let Some(value) = &field.value {
+        write_text(": ", appearance.text_color, &mut job, appearance.code_font.clone());
+        write_text(value, color, &mut job, appearance.code_font.clone());
+    }
+    Label::new(job).sense(Sense::click()).ui(ui);
+}
+
 #[derive(Clone, Copy)]
 struct SectionDiffContext<'a> {
     obj: &'a ObjInfo,
     diff: &'a ObjDiff,
     section_index: Option<usize>,
+    symbol_ref: Option<SymbolRef>,
 }
 
 impl<'a> SectionDiffContext<'a> {
-    pub fn new(obj: Option<&'a (ObjInfo, ObjDiff)>, section_name: Option<&str>) -> Option<Self> {
-        obj.map(|(obj, diff)| Self {
-            obj,
-            diff,
-            section_index: section_name.and_then(|section_name| find_section(obj, section_name)),
+    pub fn new(
+        obj: Option<&'a (ObjInfo, ObjDiff)>,
+        section_name: Option<&str>,
+        selected_symbol: Option<&SymbolRefByName>,
+    ) -> Option<Self> {
+        obj.map(|(obj, diff)| {
+            let section_index =
+                section_name.and_then(|section_name| find_section(obj, section_name));
+            Self {
+                obj,
+                diff,
+                section_index,
+                symbol_ref: section_index.zip(selected_symbol).and_then(
+                    |(section_idx, selected_symbol)| find_symbol(obj, section_idx, selected_symbol),
+                ),
+            }
         })
     }
 
     #[inline]
     pub fn has_section(&self) -> bool { self.section_index.is_some() }
+
+    /// Field diffs of the selected symbol, if it has type information.
+    pub fn data_fields(&self) -> Option<(&'a str, &'a [ObjDataFieldDiff])> {
+        let symbol_ref = self.symbol_ref?;
+        let (_, symbol) = self.obj.section_symbol(symbol_ref);
+        let fields = &self.diff.symbol_diff(symbol_ref).data_fields;
+        (!fields.is_empty()).then_some((symbol.name.as_str(), fields.as_slice()))
+    }
+}
+
+fn data_fields_table_ui(
+    ui: &mut egui::Ui,
+    available_width: f32,
+    left_ctx: Option<SectionDiffContext<'_>>,
+    right_ctx: Option<SectionDiffContext<'_>>,
+    config: &Appearance,
+) -> Option<()> {
+    let left_fields = left_ctx.and_then(|ctx| ctx.data_fields());
+    let right_fields = right_ctx.and_then(|ctx| ctx.data_fields());
+    let total_rows = left_fields.or(right_fields)?.1.len();
+
+    hotkeys::check_scroll_hotkeys(ui, true);
+
+    render_table(ui, available_width, 2, config.code_font.size, total_rows, |row, column| {
+        let i = row.index();
+        row.col(|ui| {
+            let fields = if column == 0 { left_fields } else { right_fields };
+            if let Some((symbol_name, diff)) = fields.and_then(|(n, f)| Some((n, f.get(i)?))) {
+                data_field_row_ui(ui, symbol_name, diff, config);
+            }
+        });
+    });
+    Some(())
 }
 
 fn data_table_ui(
@@ -214,8 +311,18 @@ pub fn data_diff_ui(
         state.symbol_state.left_symbol.as_ref().and_then(|s| s.section_name.as_deref()).or_else(
             || state.symbol_state.right_symbol.as_ref().and_then(|s| s.section_name.as_deref()),
         );
-    let left_ctx = SectionDiffContext::new(result.first_obj.as_ref(), section_name);
-    let right_ctx = SectionDiffContext::new(result.second_obj.as_ref(), section_name);
+    let left_ctx = SectionDiffContext::new(
+        result.first_obj.as_ref(),
+        section_name,
+        state.symbol_state.left_symbol.as_ref(),
+    );
+    let right_ctx = SectionDiffContext::new(
+        result.second_obj.as_ref(),
+        section_name,
+        state.symbol_state.right_symbol.as_ref(),
+    );
+    let has_fields = left_ctx.and_then(|ctx| ctx.data_fields()).is_some()
+        || right_ctx.and_then(|ctx| ctx.data_fields()).is_some();
 
     // If both sides are missing a symbol, switch to symbol diff view
     if !right_ctx.is_some_and(|ctx| ctx.has_section())
@@ -229,9 +336,21 @@ pub fn data_diff_ui(
     render_header(ui, available_width, 2, |ui, column| {
         if column == 0 {
             // Left column
-            if ui.button("⏴ Back").clicked() || hotkeys::back_pressed(ui.ctx()) {
-                ret = Some(DiffViewAction::Navigate(DiffViewNavigation::symbol_diff()));
-            }
+            ui.horizontal(|ui| {
+                if ui.button("⏴ Back").clicked() || hotkeys::back_pressed(ui.ctx()) {
+                    ret = Some(DiffViewAction::Navigate(DiffViewNavigation::symbol_diff()));
+                }
+                if has_fields {
+                    let mut show_data_bytes = state.show_data_bytes;
+                    if ui
+                        .checkbox(&mut show_data_bytes, "Show bytes")
+                        .on_hover_text("Show the raw section bytes instead of the symbol's fields")
+                        .changed()
+                    {
+                        ret = Some(DiffViewAction::SetShowDataBytes(show_data_bytes));
+                    }
+                }
+            });
 
             if let Some(section) =
                 left_ctx.and_then(|ctx| ctx.section_index.map(|i| &ctx.obj.sections[i]))
@@ -291,7 +410,11 @@ pub fn data_diff_ui(
         Id::new(state.symbol_state.left_symbol.as_ref().and_then(|s| s.section_name.as_deref()))
             .with(state.symbol_state.right_symbol.as_ref().and_then(|s| s.section_name.as_deref()));
     ui.push_id(id, |ui| {
-        data_table_ui(ui, available_width, left_ctx, right_ctx, appearance);
+        if has_fields && !state.show_data_bytes {
+            data_fields_table_ui(ui, available_width, left_ctx, right_ctx, appearance);
+        } else {
+            data_table_ui(ui, available_width, left_ctx, right_ctx, appearance);
+        }
     });
     ret
 }
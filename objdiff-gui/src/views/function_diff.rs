@@ -1,12 +1,15 @@
-use std::{cmp::Ordering, default::Default};
+use std::{cmp::Ordering, default::Default, path::PathBuf, sync::Arc};
 
-use egui::{text::LayoutJob, Id, Label, Response, RichText, Sense, Widget};
-use egui_extras::TableRow;
+use egui::{text::LayoutJob, Id, Label, Response, RichText, ScrollArea, Sense, Widget};
+use egui_extras::{Size, StripBuilder, TableRow};
 use objdiff_core::{
+    arch::ObjArch,
     diff::{
-        display::{display_diff, DiffText, HighlightKind},
+        display::{display_diff, ins_annotations, source_line_diffs, DiffText, HighlightKind},
+        three_way::{align_prev_rows, ins_progress, ObjInsProgress},
         ObjDiff, ObjInsDiff, ObjInsDiffKind,
     },
+    jobs::objdiff::BaseBuild,
     obj::{
         ObjInfo, ObjIns, ObjInsArg, ObjInsArgValue, ObjSection, ObjSectionKind, ObjSymbol,
         SymbolRef,
@@ -19,6 +22,7 @@ use crate::{
     views::{
         appearance::Appearance,
         column_layout::{render_header, render_strips, render_table},
+        function_graph::function_graph_ui,
         symbol_diff::{
             match_color_for_symbol, symbol_list_ui, DiffViewAction, DiffViewNavigation,
             DiffViewState, SymbolDiffContext, SymbolFilter, SymbolRefByName, SymbolViewState, View,
@@ -30,6 +34,9 @@ use crate::{
 pub struct FunctionViewState {
     left_highlight: HighlightKind,
     right_highlight: HighlightKind,
+    selected_row: Option<usize>,
+    /// Scroll the source view to the selected instruction's line
+    pub autoscroll_to_selected_line: bool,
 }
 
 impl FunctionViewState {
@@ -75,6 +82,34 @@ impl FunctionViewState {
         self.left_highlight = HighlightKind::None;
         self.right_highlight = HighlightKind::None;
     }
+
+    #[inline]
+    pub fn selected_row(&self) -> Option<usize> { self.selected_row }
+
+    pub fn select_row(&mut self, row: usize) {
+        self.selected_row = Some(row);
+        self.autoscroll_to_selected_line = true;
+    }
+
+    pub fn clear_selection(&mut self) { self.selected_row = None; }
+}
+
+/// Fraction of the function diff view's width used by the source view.
+const SOURCE_VIEW_WIDTH: f32 = 0.35;
+
+/// Contents of the selected object's source file, for the source view.
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub lines: Result<Vec<String>, String>,
+}
+
+impl SourceFile {
+    pub fn load(path: PathBuf) -> Self {
+        let lines = std::fs::read_to_string(&path)
+            .map(|text| text.lines().map(|line| line.replace('\t', "    ")).collect())
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()));
+        Self { path, lines }
+    }
 }
 
 fn ins_hover_ui(
@@ -164,6 +199,17 @@ fn ins_hover_ui(
         if let Some(decoded) = rlwinmdec::decode(&ins.formatted) {
             ui.colored_label(appearance.highlight_color, decoded.trim());
         }
+
+        if let Some(info) = &symbol.function_info {
+            for range in info.inlined_at(ins.address) {
+                let indent = "  ".repeat(range.depth as usize);
+                let mut text = format!("{indent}Inlined: {}", range.name);
+                if let Some(line) = range.call_line {
+                    text.push_str(&format!(" (line {line})"));
+                }
+                ui.colored_label(appearance.highlight_color, text);
+            }
+        }
     });
 }
 
@@ -263,9 +309,10 @@ fn diff_text_ui(
     let mut ret = None;
     let label_text;
     let mut base_color = match ins_diff.kind {
-        ObjInsDiffKind::None | ObjInsDiffKind::OpMismatch | ObjInsDiffKind::ArgMismatch => {
-            appearance.text_color
-        }
+        ObjInsDiffKind::None
+        | ObjInsDiffKind::OpMismatch
+        | ObjInsDiffKind::ArgMismatch
+        | ObjInsDiffKind::RegisterRename => appearance.text_color,
         ObjInsDiffKind::Replace => appearance.replace_color,
         ObjInsDiffKind::Delete => appearance.delete_color,
         ObjInsDiffKind::Insert => appearance.insert_color,
@@ -344,8 +391,10 @@ fn diff_text_ui(
 }
 
 #[must_use]
+#[expect(clippy::too_many_arguments)]
 fn asm_row_ui(
     ui: &mut egui::Ui,
+    arch: &dyn ObjArch,
     ins_diff: &ObjInsDiff,
     symbol: &ObjSymbol,
     appearance: &Appearance,
@@ -360,7 +409,28 @@ fn asm_row_ui(
         ui.painter().rect_filled(ui.available_rect_before_wrap(), 0.0, ui.visuals().faint_bg_color);
     }
     let space_width = ui.fonts(|f| f.glyph_width(&appearance.code_font, ' '));
+    // Mark instructions belonging to inlined functions with a bar for each nesting level
+    if let (Some(info), Some(ins)) = (&symbol.function_info, &ins_diff.ins) {
+        let rect = ui.available_rect_before_wrap();
+        for range in info.inlined_at(ins.address) {
+            let x = rect.left() + range.depth as f32 * 3.0;
+            let color = appearance.diff_colors[range.depth as usize % appearance.diff_colors.len()];
+            ui.painter().vline(x + 1.0, rect.y_range(), egui::Stroke::new(2.0, color));
+        }
+    }
+    let annotations = match &ins_diff.ins {
+        Some(ins) => ins_annotations(arch, symbol, ins),
+        None => Vec::new(),
+    };
     display_diff(ins_diff, symbol.address, |text| {
+        if matches!(text, DiffText::Eol) && !annotations.is_empty() {
+            ui.add_space(space_width * 2.0);
+            Label::new(LayoutJob::single_section(
+                annotations.join(", "),
+                appearance.code_text_format(appearance.deemphasized_text_color, false),
+            ))
+            .ui(ui);
+        }
         if let Some(action) = diff_text_ui(
             ui,
             text,
@@ -392,6 +462,7 @@ fn asm_col_ui(
     let (section, symbol) = ctx.obj.section_symbol(symbol_ref);
     let section = section?;
     let ins_diff = &ctx.diff.symbol_diff(symbol_ref).instructions[row.index()];
+    row.set_selected(ins_view_state.selected_row() == Some(row.index()));
     let response_cb = |response: Response| {
         if let Some(ins) = &ins_diff.ins {
             response.context_menu(|ui| ins_context_menu(ui, section, ins, symbol));
@@ -403,9 +474,16 @@ fn asm_col_ui(
         }
     };
     let (_, response) = row.col(|ui| {
-        if let Some(action) =
-            asm_row_ui(ui, ins_diff, symbol, appearance, ins_view_state, column, response_cb)
-        {
+        if let Some(action) = asm_row_ui(
+            ui,
+            ctx.obj.arch.as_ref(),
+            ins_diff,
+            symbol,
+            appearance,
+            ins_view_state,
+            column,
+            response_cb,
+        ) {
             ret = Some(action);
         }
     });
@@ -413,12 +491,84 @@ fn asm_col_ui(
     ret
 }
 
+/// An earlier base build of the function, with the row of its diff aligned to each current row.
+#[derive(Clone, Copy)]
+struct PrevColumn<'a> {
+    ctx: FunctionDiffContext<'a>,
+    rows: &'a [Option<usize>],
+}
+
 #[must_use]
+fn prev_col_ui(
+    row: &mut TableRow<'_, '_>,
+    current_ctx: FunctionDiffContext<'_>,
+    prev: PrevColumn<'_>,
+    appearance: &Appearance,
+    ins_view_state: &FunctionViewState,
+    column: usize,
+) -> Option<DiffViewAction> {
+    let mut ret = None;
+    let symbol_ref = prev.ctx.symbol_ref?;
+    let (section, symbol) = prev.ctx.obj.section_symbol(symbol_ref);
+    let section = section?;
+    let current = &current_ctx.diff.symbol_diff(current_ctx.symbol_ref?).instructions[row.index()];
+    let prev_diff = prev.ctx.diff.symbol_diff(symbol_ref);
+    let ins_diff = prev.rows[row.index()].map(|idx| &prev_diff.instructions[idx]);
+    row.set_selected(ins_view_state.selected_row() == Some(row.index()));
+    row.col(|ui| {
+        let Some(ins_diff) = ins_diff else {
+            return;
+        };
+        let (marker, color, hover) = match ins_progress(current, ins_diff) {
+            Some(ObjInsProgress::Better) => {
+                ("▲ ", appearance.insert_color, "Matches the target now, but didn't in this build")
+            }
+            Some(ObjInsProgress::Worse) => {
+                ("▼ ", appearance.delete_color, "Matched the target in this build, but doesn't now")
+            }
+            None => ("  ", appearance.text_color, ""),
+        };
+        let response = Label::new(LayoutJob::single_section(
+            marker.to_string(),
+            appearance.code_text_format(color, false),
+        ))
+        .ui(ui);
+        if !hover.is_empty() {
+            response.on_hover_text(hover);
+        }
+        let response_cb = |response: Response| {
+            if let Some(ins) = &ins_diff.ins {
+                response.on_hover_ui_at_pointer(|ui| {
+                    ins_hover_ui(ui, prev.ctx.obj, section, ins, symbol, appearance)
+                })
+            } else {
+                response
+            }
+        };
+        if let Some(action) = asm_row_ui(
+            ui,
+            prev.ctx.obj.arch.as_ref(),
+            ins_diff,
+            symbol,
+            appearance,
+            ins_view_state,
+            column,
+            response_cb,
+        ) {
+            ret = Some(action);
+        }
+    });
+    ret
+}
+
+#[must_use]
+#[expect(clippy::too_many_arguments)]
 fn asm_table_ui(
     ui: &mut egui::Ui,
     available_width: f32,
     left_ctx: Option<FunctionDiffContext<'_>>,
     right_ctx: Option<FunctionDiffContext<'_>>,
+    prev: Option<PrevColumn<'_>>,
     appearance: &Appearance,
     ins_view_state: &FunctionViewState,
     symbol_state: &SymbolViewState,
@@ -451,7 +601,7 @@ fn asm_table_ui(
         render_table(
             ui,
             available_width,
-            2,
+            if prev.is_some() { 3 } else { 2 },
             appearance.code_font.size,
             instructions_len,
             |row, column| {
@@ -472,7 +622,15 @@ fn asm_table_ui(
                         }
                     }
                     if row.response().clicked() {
-                        ret = Some(DiffViewAction::ClearDiffHighlight);
+                        ret = Some(DiffViewAction::SelectInstruction(row.index()));
+                    }
+                } else if column == 2 {
+                    if let (Some(ctx), Some(prev)) = (right_ctx, prev) {
+                        if let Some(action) =
+                            prev_col_ui(row, ctx, prev, appearance, ins_view_state, column)
+                        {
+                            ret = Some(action);
+                        }
                     }
                 }
             },
@@ -497,7 +655,7 @@ fn asm_table_ui(
                                     ret = Some(action);
                                 }
                                 if row.response().clicked() {
-                                    ret = Some(DiffViewAction::ClearDiffHighlight);
+                                    ret = Some(DiffViewAction::SelectInstruction(row.index()));
                                 }
                             },
                         );
@@ -555,7 +713,7 @@ fn asm_table_ui(
                                     ret = Some(action);
                                 }
                                 if row.response().clicked() {
-                                    ret = Some(DiffViewAction::ClearDiffHighlight);
+                                    ret = Some(DiffViewAction::SelectInstruction(row.index()));
                                 }
                             },
                         );
@@ -602,6 +760,119 @@ fn asm_table_ui(
     ret
 }
 
+/// Shows the selected object's source file, highlighting the selected instruction's line and
+/// the lines with mismatched instructions.
+fn source_ui(
+    ui: &mut egui::Ui,
+    state: &DiffViewState,
+    left_ctx: Option<FunctionDiffContext<'_>>,
+    right_ctx: Option<FunctionDiffContext<'_>>,
+    appearance: &Appearance,
+) {
+    let Some(source_file) = &state.source_file else {
+        return;
+    };
+    let file_name = source_file.path.file_name().unwrap_or_default().to_string_lossy();
+    ui.label(RichText::new(file_name).font(appearance.code_font.clone()))
+        .on_hover_text(source_file.path.display().to_string());
+    let lines = match &source_file.lines {
+        Ok(lines) => lines,
+        Err(e) => {
+            ui.colored_label(appearance.delete_color, e);
+            return;
+        }
+    };
+
+    // Prefer the base object's line info
+    let symbol_diffs = [right_ctx, left_ctx]
+        .into_iter()
+        .flatten()
+        .filter_map(|ctx| ctx.symbol_ref.map(|symbol_ref| ctx.diff.symbol_diff(symbol_ref)))
+        .collect::<Vec<_>>();
+    let line_diffs = symbol_diffs
+        .iter()
+        .map(|symbol_diff| source_line_diffs(symbol_diff))
+        .find(|line_diffs| !line_diffs.is_empty())
+        .unwrap_or_default();
+    let selected_line = state.function_state.selected_row().and_then(|row| {
+        symbol_diffs
+            .iter()
+            .find_map(|symbol_diff| symbol_diff.instructions.get(row)?.ins.as_ref()?.line)
+    });
+
+    let row_height = appearance.code_font.size;
+    ui.spacing_mut().item_spacing.y = 0.0;
+    let mut scroll_area = ScrollArea::both().auto_shrink([false, false]);
+    if state.function_state.autoscroll_to_selected_line {
+        if let Some(line) = selected_line {
+            // Center the selected line
+            let offset = line.saturating_sub(1) as f32 * row_height - ui.available_height() / 2.0;
+            scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+        }
+    }
+    scroll_area.show_rows(ui, row_height, lines.len(), |ui, range| {
+        ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
+        for (idx, line) in lines.iter().enumerate().skip(range.start).take(range.len()) {
+            let line_number = idx as u32 + 1;
+            let mismatched = line_diffs.get(&line_number).copied().unwrap_or_default();
+            let selected = selected_line == Some(line_number);
+            let mut job = LayoutJob::default();
+            job.append(
+                &format!("{line_number:>5} "),
+                0.0,
+                appearance.code_text_format(appearance.deemphasized_text_color, false),
+            );
+            let color = if mismatched { appearance.replace_color } else { appearance.text_color };
+            let mut format = appearance.code_text_format(color, selected);
+            if mismatched && !selected {
+                format.background = ui.visuals().faint_bg_color;
+            }
+            job.append(line, 0.0, format);
+            Label::new(job).ui(ui);
+        }
+    });
+}
+
+fn format_build_time(build: &BaseBuild, appearance: &Appearance) -> String {
+    let format = format_description::parse("[hour]:[minute]:[second]").unwrap();
+    build.time.to_offset(appearance.utc_offset).format(&format).unwrap()
+}
+
+/// Selects an earlier base build to show in a third column.
+#[must_use]
+fn compare_base_ui(
+    ui: &mut egui::Ui,
+    state: &DiffViewState,
+    appearance: &Appearance,
+) -> Option<DiffViewAction> {
+    let mut ret = None;
+    let selected_text = match &state.compare_base {
+        Some(base) => format_build_time(base, appearance),
+        None => "None".to_string(),
+    };
+    ui.add_enabled_ui(!state.base_history.is_empty() || state.compare_base.is_some(), |ui| {
+        egui::ComboBox::new("compare_base", "Compare")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(state.compare_base.is_none(), "None").clicked() {
+                    ret = Some(DiffViewAction::SetCompareBase(None));
+                }
+                for (idx, build) in state.base_history.iter().enumerate() {
+                    let selected =
+                        state.compare_base.as_ref().is_some_and(|base| Arc::ptr_eq(base, build));
+                    if ui.selectable_label(selected, format_build_time(build, appearance)).clicked()
+                    {
+                        ret = Some(DiffViewAction::SetCompareBase(Some(idx)));
+                    }
+                }
+            })
+            .response
+            .on_hover_text("Compare against an earlier build of the base")
+            .on_disabled_hover_text("No earlier builds of the base yet");
+    });
+    ret
+}
+
 #[derive(Clone, Copy)]
 pub struct FunctionDiffContext<'a> {
     pub obj: &'a ObjInfo,
@@ -681,9 +952,32 @@ pub fn function_diff_ui(
         return Some(DiffViewAction::Navigate(DiffViewNavigation::symbol_diff()));
     }
 
+    // Earlier base build to compare against, once the build has diffed it
+    let compare = result.compare.as_ref().filter(|compare| {
+        state.compare_base.as_ref().is_some_and(|base| Arc::ptr_eq(base, &compare.base))
+    });
+    let prev = compare.zip(left_ctx).and_then(|(compare, left_ctx)| {
+        let left_symbol_ref = left_ctx.symbol_ref?;
+        let prev_target_diff = compare.target_diff.symbol_diff(left_symbol_ref);
+        let rows = align_prev_rows(left_ctx.diff.symbol_diff(left_symbol_ref), prev_target_diff);
+        let ctx = FunctionDiffContext {
+            obj: &compare.base.obj,
+            diff: &compare.base_diff,
+            symbol_ref: Some(prev_target_diff.target_symbol?),
+        };
+        Some((ctx, rows, &compare.base))
+    });
+    let prev_column = prev.as_ref().map(|(ctx, rows, _)| PrevColumn { ctx: *ctx, rows });
+
     // Header
-    let available_width = ui.available_width();
-    render_header(ui, available_width, 2, |ui, column| {
+    let show_source = state.show_source && state.source_path_available;
+    let available_width = if show_source {
+        ui.available_width() * (1.0 - SOURCE_VIEW_WIDTH)
+    } else {
+        ui.available_width()
+    };
+    let num_columns = if prev.is_some() { 3 } else { 2 };
+    render_header(ui, available_width, num_columns, |ui, column| {
         if column == 0 {
             // Left column
             ui.horizontal(|ui| {
@@ -708,6 +1002,27 @@ pub fn function_diff_ui(
                         ret = Some(DiffViewAction::CreateScratch(symbol.name.clone()));
                     }
                 }
+                ui.separator();
+                let mut show_function_graph = state.show_function_graph;
+                if ui
+                    .checkbox(&mut show_function_graph, "Graph")
+                    .on_hover_text("Show the basic block graph instead of the instruction listing")
+                    .changed()
+                {
+                    ret = Some(DiffViewAction::SetShowFunctionGraph(show_function_graph));
+                }
+                let mut show_source = state.show_source;
+                if ui
+                    .add_enabled(
+                        state.source_path_available,
+                        egui::Checkbox::new(&mut show_source, "Source"),
+                    )
+                    .on_hover_text("Show the source file next to the instructions")
+                    .on_disabled_hover_text("Source file metadata missing")
+                    .changed()
+                {
+                    ret = Some(DiffViewAction::SetShowSource(show_source));
+                }
             });
 
             if let Some((_section, symbol)) = left_ctx
@@ -769,6 +1084,10 @@ pub fn function_diff_ui(
                 {
                     ret = Some(DiffViewAction::OpenSourcePath);
                 }
+                ui.separator();
+                if let Some(action) = compare_base_ui(ui, state, appearance) {
+                    ret = Some(action);
+                }
             });
 
             if let Some(((_section, symbol), symbol_diff)) = right_ctx.and_then(|ctx| {
@@ -790,6 +1109,25 @@ pub fn function_diff_ui(
                                 .color(match_color_for_symbol(match_percent, appearance)),
                         );
                     }
+                    if let Some(score) = symbol_diff.score {
+                        ui.label(
+                            RichText::new(format!("score {score}"))
+                                .font(appearance.code_font.clone())
+                                .color(appearance.deemphasized_text_color),
+                        )
+                        .on_hover_text("Sum of the penalties of each differing instruction");
+                    }
+                    if symbol_diff.approximate {
+                        ui.label(
+                            RichText::new("approximate")
+                                .font(appearance.code_font.clone())
+                                .color(appearance.replace_color),
+                        )
+                        .on_hover_text(
+                            "The instruction alignment was cut short by the time limit or the \
+                            function's size, so it may not be the best alignment.",
+                        );
+                    }
                     if left_ctx.is_some_and(|m| m.has_symbol()) {
                         ui.separator();
                         if ui
@@ -818,27 +1156,74 @@ pub fn function_diff_ui(
                         .color(appearance.highlight_color),
                 );
             }
+        } else if column == 2 {
+            // Earlier build column
+            if let Some((ctx, _, base)) = &prev {
+                ui.scope(|ui| {
+                    ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
+                    ui.label(format!("Earlier build: {}", format_build_time(base, appearance)));
+                });
+                if let Some(symbol_ref) = ctx.symbol_ref {
+                    let (_section, symbol) = ctx.obj.section_symbol(symbol_ref);
+                    let name = symbol.demangled_name.as_deref().unwrap_or(&symbol.name);
+                    ui.label(
+                        RichText::new(name)
+                            .font(appearance.code_font.clone())
+                            .color(appearance.highlight_color),
+                    );
+                    if let Some(match_percent) = ctx.diff.symbol_diff(symbol_ref).match_percent {
+                        ui.label(
+                            RichText::new(format!("{:.0}%", match_percent.floor()))
+                                .font(appearance.code_font.clone())
+                                .color(match_color_for_symbol(match_percent, appearance)),
+                        );
+                    }
+                }
+            }
         }
     });
 
     // Table
     let id = Id::new(state.symbol_state.left_symbol.as_ref().map(|s| s.symbol_name.as_str()))
         .with(state.symbol_state.right_symbol.as_ref().map(|s| s.symbol_name.as_str()));
-    if let Some(action) = ui
-        .push_id(id, |ui| {
-            asm_table_ui(
-                ui,
-                available_width,
-                left_ctx,
-                right_ctx,
-                appearance,
-                &state.function_state,
-                &state.symbol_state,
-            )
-        })
-        .inner
-    {
-        ret = Some(action);
+    let mut table_ui = |ui: &mut egui::Ui| {
+        if state.show_function_graph {
+            ui.push_id(id, |ui| {
+                function_graph_ui(ui, available_width, left_ctx, right_ctx, appearance)
+            });
+        } else if let Some(action) = ui
+            .push_id(id, |ui| {
+                asm_table_ui(
+                    ui,
+                    available_width,
+                    left_ctx,
+                    right_ctx,
+                    prev_column,
+                    appearance,
+                    &state.function_state,
+                    &state.symbol_state,
+                )
+            })
+            .inner
+        {
+            ret = Some(action);
+        }
+    };
+    if show_source {
+        StripBuilder::new(ui).size(Size::remainder()).vertical(|mut strip| {
+            strip.strip(|builder| {
+                builder.size(Size::exact(available_width)).size(Size::remainder()).horizontal(
+                    |mut strip| {
+                        strip.cell(table_ui);
+                        strip.cell(|ui| {
+                            source_ui(ui, state, left_ctx, right_ctx, appearance);
+                        });
+                    },
+                );
+            });
+        });
+    } else {
+        table_ui(ui);
     }
     ret
 }
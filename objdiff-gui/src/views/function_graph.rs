@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use egui::{text::LayoutJob, Color32, Galley, Pos2, Rect, ScrollArea, Sense, Shape, Stroke, Vec2};
+use objdiff_core::{
+    diff::{
+        display::{display_diff, DiffText},
+        ObjBasicBlock, ObjBlockDiffKind, ObjInsDiff, ObjInsDiffKind,
+    },
+    obj::ObjSymbol,
+};
+
+use crate::views::{
+    appearance::Appearance, column_layout::render_strips, function_diff::FunctionDiffContext,
+    write_text,
+};
+
+const BLOCK_PADDING: f32 = 6.0;
+const BLOCK_SPACING: Vec2 = Vec2::new(24.0, 32.0);
+/// Space to the right of the graph for back edges (loops)
+const BACK_EDGE_MARGIN: f32 = 32.0;
+
+fn block_color(kind: ObjBlockDiffKind, appearance: &Appearance) -> Color32 {
+    match kind {
+        ObjBlockDiffKind::None => appearance.deemphasized_text_color,
+        ObjBlockDiffKind::Mismatch => appearance.replace_color,
+        ObjBlockDiffKind::Delete => appearance.delete_color,
+        ObjBlockDiffKind::Insert => appearance.insert_color,
+    }
+}
+
+fn block_layout_job(
+    instructions: &[ObjInsDiff],
+    symbol: &ObjSymbol,
+    appearance: &Appearance,
+) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    for ins_diff in instructions.iter().filter(|i| i.ins.is_some()) {
+        if !job.text.is_empty() {
+            write_text("\n", appearance.text_color, &mut job, appearance.code_font.clone());
+        }
+        let base_color = match ins_diff.kind {
+            ObjInsDiffKind::None
+            | ObjInsDiffKind::OpMismatch
+            | ObjInsDiffKind::ArgMismatch
+            | ObjInsDiffKind::RegisterRename => appearance.text_color,
+            ObjInsDiffKind::Replace => appearance.replace_color,
+            ObjInsDiffKind::Delete => appearance.delete_color,
+            ObjInsDiffKind::Insert => appearance.insert_color,
+        };
+        display_diff(ins_diff, symbol.address, |text| {
+            let diff_color =
+                |idx: usize| appearance.diff_colors[idx % appearance.diff_colors.len()];
+            let (text, color) = match text {
+                DiffText::Basic(s) => (s.to_string(), base_color),
+                DiffText::BasicColor(s, idx) => (s.to_string(), diff_color(idx)),
+                DiffText::Line(_) | DiffText::Eol => return Ok(()),
+                DiffText::Address(addr) => (format!("{:x}:", addr), base_color),
+                DiffText::Opcode(mnemonic, _op) => {
+                    let color = if ins_diff.kind == ObjInsDiffKind::OpMismatch {
+                        appearance.replace_color
+                    } else {
+                        base_color
+                    };
+                    (format!("{mnemonic:<8}"), color)
+                }
+                DiffText::Argument(arg, diff) => {
+                    (arg.to_string(), diff.map_or(base_color, |d| diff_color(d.idx)))
+                }
+                DiffText::BranchDest(addr, diff) => {
+                    (format!("{addr:x}"), diff.map_or(base_color, |d| diff_color(d.idx)))
+                }
+                DiffText::Symbol(sym, diff) => (
+                    sym.demangled_name.as_ref().unwrap_or(&sym.name).clone(),
+                    diff.map_or(appearance.emphasized_text_color, |d| diff_color(d.idx)),
+                ),
+                DiffText::Spacing(n) => (" ".repeat(n), base_color),
+            };
+            write_text(&text, color, &mut job, appearance.code_font.clone());
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+    }
+    job
+}
+
+/// Assigns each block a layer, so that forward edges always point downwards.
+fn block_layers(blocks: &[ObjBasicBlock]) -> Vec<usize> {
+    let mut layers = vec![0usize; blocks.len()];
+    let mut has_pred = vec![false; blocks.len()];
+    for (idx, block) in blocks.iter().enumerate() {
+        if idx > 0 && !has_pred[idx] {
+            // Unreachable from earlier blocks, place it below the previous one
+            layers[idx] = layers[idx - 1] + 1;
+        }
+        for &succ in block.successors.iter().filter(|&&s| s > idx) {
+            layers[succ] = layers[succ].max(layers[idx] + 1);
+            has_pred[succ] = true;
+        }
+    }
+    layers
+}
+
+/// Positions blocks of the given sizes, returning their rects and the total graph size.
+fn layout_blocks(blocks: &[ObjBasicBlock], sizes: &[Vec2]) -> (Vec<Rect>, Vec2) {
+    let layers = block_layers(blocks);
+    let num_layers = layers.iter().max().map_or(0, |&l| l + 1);
+    let mut rects = vec![Rect::NOTHING; blocks.len()];
+    let mut total = Vec2::ZERO;
+    let mut y = 0.0;
+    for layer in 0..num_layers {
+        let mut x = 0.0;
+        let mut height = 0.0f32;
+        for idx in (0..blocks.len()).filter(|&i| layers[i] == layer) {
+            rects[idx] = Rect::from_min_size(Pos2::new(x, y), sizes[idx]);
+            x += sizes[idx].x + BLOCK_SPACING.x;
+            height = height.max(sizes[idx].y);
+        }
+        total.x = total.x.max(x);
+        y += height + BLOCK_SPACING.y;
+    }
+    total.y = y;
+    total.x += BACK_EDGE_MARGIN;
+    (rects, total)
+}
+
+fn graph_ui(ui: &mut egui::Ui, ctx: FunctionDiffContext<'_>, appearance: &Appearance) {
+    let Some(symbol_ref) = ctx.symbol_ref else {
+        return;
+    };
+    let (_section, symbol) = ctx.obj.section_symbol(symbol_ref);
+    let symbol_diff = ctx.diff.symbol_diff(symbol_ref);
+    let blocks = &symbol_diff.blocks;
+    let galleys = blocks
+        .iter()
+        .map(|block| {
+            let job = block_layout_job(
+                &symbol_diff.instructions[block.start..block.end],
+                symbol,
+                appearance,
+            );
+            ui.fonts(|f| f.layout_job(job))
+        })
+        .collect::<Vec<Arc<Galley>>>();
+    let sizes =
+        galleys.iter().map(|g| g.size() + Vec2::splat(BLOCK_PADDING * 2.0)).collect::<Vec<_>>();
+    let (rects, size) = layout_blocks(blocks, &sizes);
+
+    ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+        let (response, painter) =
+            ui.allocate_painter(size + Vec2::splat(BLOCK_PADDING * 2.0), Sense::hover());
+        let offset = response.rect.min.to_vec2() + Vec2::splat(BLOCK_PADDING);
+        let rects = rects.iter().map(|r| r.translate(offset)).collect::<Vec<_>>();
+        let back_edge_x = offset.x + size.x - BACK_EDGE_MARGIN / 2.0;
+
+        // Edges
+        let edge_stroke = Stroke::new(1.0, appearance.deemphasized_text_color);
+        for (idx, block) in blocks.iter().enumerate() {
+            let from = rects[idx];
+            for &succ in &block.successors {
+                let to = rects[succ];
+                if succ > idx {
+                    let start = from.center_bottom();
+                    let end = to.center_top();
+                    painter.arrow(start, end - start, edge_stroke);
+                } else {
+                    // Loop back along the right side of the graph
+                    let points = vec![
+                        from.right_center(),
+                        Pos2::new(back_edge_x, from.center().y),
+                        Pos2::new(back_edge_x, to.center().y),
+                        to.right_center(),
+                    ];
+                    painter.add(Shape::line(points, edge_stroke));
+                }
+            }
+        }
+
+        // Blocks
+        for ((block, rect), galley) in blocks.iter().zip(&rects).zip(galleys) {
+            let color = block_color(block.kind, appearance);
+            let fill = if block.kind == ObjBlockDiffKind::None {
+                ui.visuals().extreme_bg_color
+            } else {
+                ui.visuals().faint_bg_color
+            };
+            painter.rect(*rect, 2.0, fill, Stroke::new(1.0, color));
+            painter.galley(rect.min + Vec2::splat(BLOCK_PADDING), galley, appearance.text_color);
+        }
+    });
+}
+
+/// Draws the basic block graphs of both functions side by side.
+pub fn function_graph_ui(
+    ui: &mut egui::Ui,
+    available_width: f32,
+    left_ctx: Option<FunctionDiffContext<'_>>,
+    right_ctx: Option<FunctionDiffContext<'_>>,
+    appearance: &Appearance,
+) {
+    render_strips(ui, available_width, 2, |ui, column| {
+        if column == 0 {
+            if let Some(ctx) = left_ctx {
+                graph_ui(ui, ctx, appearance);
+            }
+        } else if column == 1 {
+            if let Some(ctx) = right_ctx {
+                graph_ui(ui, ctx, appearance);
+            }
+        }
+    });
+}
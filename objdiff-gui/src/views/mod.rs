@@ -10,10 +10,12 @@ pub(crate) mod extab_diff;
 pub(crate) mod file;
 pub(crate) mod frame_history;
 pub(crate) mod function_diff;
+pub(crate) mod function_graph;
 pub(crate) mod graphics;
 pub(crate) mod jobs;
 pub(crate) mod rlwinm;
 pub(crate) mod symbol_diff;
+pub(crate) mod symbol_search;
 
 #[inline]
 fn write_text(str: &str, color: Color32, job: &mut LayoutJob, font_id: FontId) {
@@ -1,4 +1,4 @@
-use std::{collections::BTreeMap, mem::take, ops::Bound};
+use std::{collections::BTreeMap, mem::take, ops::Bound, sync::Arc};
 
 use egui::{
     style::ScrollAnimation, text::LayoutJob, CollapsingHeader, Color32, Id, OpenUrl, ScrollArea,
@@ -7,8 +7,15 @@ use egui::{
 use objdiff_core::{
     arch::ObjArch,
     build::BuildStatus,
-    diff::{display::HighlightKind, ObjDiff, ObjSymbolDiff},
-    jobs::{create_scratch::CreateScratchResult, objdiff::ObjDiffResult, Job, JobQueue, JobResult},
+    diff::{
+        display::{display_variable_location, HighlightKind},
+        ObjDiff, ObjSymbolDiff,
+    },
+    jobs::{
+        create_scratch::CreateScratchResult,
+        objdiff::{BaseBuild, BaseBuildHistory, ObjDiffResult},
+        Job, JobQueue, JobResult,
+    },
     obj::{
         ObjInfo, ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolFlags, SymbolRef, SECTION_COMMON,
     },
@@ -22,7 +29,7 @@ use crate::{
     views::{
         appearance::Appearance,
         column_layout::{render_header, render_strips},
-        function_diff::FunctionViewState,
+        function_diff::{FunctionViewState, SourceFile},
         write_text,
     },
 };
@@ -65,8 +72,8 @@ pub enum DiffViewAction {
     OpenSourcePath,
     /// Set the highlight for a diff column
     SetDiffHighlight(usize, HighlightKind),
-    /// Clear the highlight for all diff columns
-    ClearDiffHighlight,
+    /// Select an instruction row and clear the highlight for all diff columns
+    SelectInstruction(usize),
     /// Start selecting a left symbol for mapping.
     /// The symbol reference is the right symbol to map to.
     SelectingLeft(SymbolRefByName),
@@ -77,6 +84,16 @@ pub enum DiffViewAction {
     SetMapping(View, SymbolRefByName, SymbolRefByName),
     /// Set the show_mapped_symbols flag
     SetShowMappedSymbols(bool),
+    /// Set the show_data_bytes flag
+    SetShowDataBytes(bool),
+    /// Set the show_function_graph flag
+    SetShowFunctionGraph(bool),
+    /// Set the show_source flag
+    SetShowSource(bool),
+    /// Find functions similar to a symbol in the given column, in the symbol search
+    FindSimilar(usize, SymbolRef),
+    /// Compare the function diff against an earlier base build, by index in the history
+    SetCompareBase(Option<usize>),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -116,7 +133,7 @@ impl DiffViewNavigation {
 
 #[derive(Default)]
 pub struct DiffViewState {
-    pub build: Option<Box<ObjDiffResult>>,
+    pub build: Option<Arc<ObjDiffResult>>,
     pub scratch: Option<Box<CreateScratchResult>>,
     pub current_view: View,
     pub symbol_state: SymbolViewState,
@@ -129,6 +146,17 @@ pub struct DiffViewState {
     pub source_path_available: bool,
     pub post_build_nav: Option<DiffViewNavigation>,
     pub object_name: String,
+    /// Show raw bytes in the data diff view, even if the symbol has type information
+    pub show_data_bytes: bool,
+    /// Show the basic block graph in the function diff view instead of the listing
+    pub show_function_graph: bool,
+    /// Show the source file next to the function diff view
+    pub show_source: bool,
+    pub source_file: Option<SourceFile>,
+    /// Earlier base builds of the current object
+    pub base_history: BaseBuildHistory,
+    /// Earlier base build shown in the function diff view's third column
+    pub compare_base: Option<Arc<BaseBuild>>,
 }
 
 #[derive(Default)]
@@ -145,9 +173,27 @@ pub struct SymbolViewState {
 
 impl DiffViewState {
     pub fn pre_update(&mut self, jobs: &mut JobQueue, state: &AppStateRef) {
+        let mut reload_source = false;
         jobs.results.retain_mut(|result| match result {
             JobResult::ObjDiff(result) => {
-                self.build = take(result);
+                let replaced = self.build.take();
+                self.build = take(result).map(Arc::from);
+                if let (Some(replaced), Some(build)) = (replaced, &self.build) {
+                    match Arc::try_unwrap(replaced) {
+                        Ok(replaced) => self.base_history.push_replaced(replaced, build),
+                        Err(_) => {
+                            log::warn!("Previous build still in use, not adding it to history")
+                        }
+                    }
+                }
+                // Builds of another object can't be compared against
+                if let (Some(base), Some(build)) = (&self.compare_base, &self.build) {
+                    if build.second_obj.as_ref().is_some_and(|(obj, _)| obj.path != base.obj.path) {
+                        self.compare_base = None;
+                    }
+                }
+                // The source file may have changed since the last build
+                reload_source = true;
 
                 // TODO: where should this go?
                 if let Some(result) = self.post_build_nav.take() {
@@ -156,6 +202,7 @@ impl DiffViewState {
                     }
                     self.symbol_state.left_symbol = result.left_symbol;
                     self.symbol_state.right_symbol = result.right_symbol;
+                    self.function_state.clear_selection();
                 }
 
                 false
@@ -180,9 +227,29 @@ impl DiffViewState {
             } else {
                 self.source_path_available = false;
             }
+            let source_path = state
+                .config
+                .project_dir
+                .as_ref()
+                .zip(state.config.selected_obj.as_ref().and_then(|obj| obj.source_path.as_ref()))
+                .map(|(project_dir, source_path)| project_dir.join(source_path));
+            match source_path {
+                Some(path) if self.show_source => {
+                    if reload_source || self.source_file.as_ref().map_or(true, |f| f.path != path) {
+                        self.source_file = Some(SourceFile::load(path));
+                    }
+                }
+                _ => self.source_file = None,
+            }
             self.scratch_available = is_create_scratch_available(&state.config);
-            self.object_name =
+            let object_name =
                 state.config.selected_obj.as_ref().map(|o| o.name.clone()).unwrap_or_default();
+            if object_name != self.object_name {
+                // Earlier builds are only kept for the selected unit
+                self.base_history.clear();
+                self.compare_base = None;
+                self.object_name = object_name;
+            }
         }
     }
 
@@ -199,6 +266,7 @@ impl DiffViewState {
 
         // Clear the autoscroll flag so that it doesn't scroll continuously.
         self.symbol_state.autoscroll_to_highlighted_symbols = false;
+        self.function_state.autoscroll_to_selected_line = false;
 
         let Some(action) = action else {
             return;
@@ -233,6 +301,7 @@ impl DiffViewState {
                         }
                         self.symbol_state.left_symbol = nav.left_symbol;
                         self.symbol_state.right_symbol = nav.right_symbol;
+                        self.function_state.clear_selection();
                     }
                 } else {
                     // Enter selection mode
@@ -288,7 +357,8 @@ impl DiffViewState {
             DiffViewAction::SetDiffHighlight(column, kind) => {
                 self.function_state.set_highlight(column, kind);
             }
-            DiffViewAction::ClearDiffHighlight => {
+            DiffViewAction::SelectInstruction(row) => {
+                self.function_state.select_row(row);
                 self.function_state.clear_highlight();
             }
             DiffViewAction::SelectingLeft(right_ref) => {
@@ -346,6 +416,26 @@ impl DiffViewState {
             DiffViewAction::SetShowMappedSymbols(value) => {
                 self.symbol_state.show_mapped_symbols = value;
             }
+            DiffViewAction::SetShowDataBytes(value) => {
+                self.show_data_bytes = value;
+            }
+            DiffViewAction::SetShowFunctionGraph(value) => {
+                self.show_function_graph = value;
+            }
+            DiffViewAction::SetShowSource(value) => {
+                self.show_source = value;
+            }
+            DiffViewAction::FindSimilar(..) => {
+                // Handled by the symbol search
+            }
+            DiffViewAction::SetCompareBase(idx) => {
+                self.compare_base = idx.and_then(|idx| self.base_history.get(idx).cloned());
+                if self.compare_base.is_some() {
+                    if let Ok(mut state) = state.write() {
+                        state.queue_reload = true;
+                    }
+                }
+            }
         }
     }
 }
@@ -368,7 +458,7 @@ fn symbol_context_menu_ui(
     symbol_diff: &ObjSymbolDiff,
     section: Option<&ObjSection>,
     column: usize,
-) -> Option<DiffViewNavigation> {
+) -> Option<DiffViewAction> {
     let mut ret = None;
     ui.scope(|ui| {
         ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
@@ -394,32 +484,39 @@ fn symbol_context_menu_ui(
             let has_extab =
                 ctx.obj.arch.ppc().and_then(|ppc| ppc.extab_for_symbol(symbol)).is_some();
             if has_extab && ui.button("Decode exception table").clicked() {
-                ret = Some(DiffViewNavigation::with_symbols(
+                ret = Some(DiffViewAction::Navigate(DiffViewNavigation::with_symbols(
                     View::ExtabDiff,
                     other_ctx,
                     symbol,
                     section,
                     symbol_diff,
                     column,
-                ));
+                )));
+                ui.close_menu();
+            }
+
+            if section.kind == ObjSectionKind::Code && ui.button("Find similar functions").clicked()
+            {
+                ret = Some(DiffViewAction::FindSimilar(column, symbol_diff.symbol_ref));
                 ui.close_menu();
             }
 
             if ui.button("Map symbol").clicked() {
                 let symbol_ref = SymbolRefByName::new(symbol, Some(section));
-                if column == 0 {
-                    ret = Some(DiffViewNavigation {
+                let nav = if column == 0 {
+                    DiffViewNavigation {
                         view: Some(View::FunctionDiff),
                         left_symbol: Some(symbol_ref),
                         right_symbol: None,
-                    });
+                    }
                 } else {
-                    ret = Some(DiffViewNavigation {
+                    DiffViewNavigation {
                         view: Some(View::FunctionDiff),
                         left_symbol: None,
                         right_symbol: Some(symbol_ref),
-                    });
-                }
+                    }
+                };
+                ret = Some(DiffViewAction::Navigate(nav));
                 ui.close_menu();
             }
         }
@@ -455,6 +552,24 @@ fn symbol_hover_ui(ui: &mut Ui, arch: &dyn ObjArch, symbol: &ObjSymbol, appearan
                 format!("extabindex symbol: {}", &extab.eti_symbol.name),
             );
         }
+        if let Some(info) = &symbol.function_info {
+            ui.colored_label(
+                appearance.highlight_color,
+                format!("Returns: {}", info.return_type.as_deref().unwrap_or("void")),
+            );
+            for (kind, variables) in [("Param", &info.params), ("Local", &info.locals)] {
+                for variable in variables {
+                    let mut text = format!("{kind}: {}", variable.name);
+                    if let Some(ty) = &variable.ty {
+                        text.push_str(&format!(": {ty}"));
+                    }
+                    if let Some(location) = &variable.location {
+                        text.push_str(&format!(" @ {}", display_variable_location(arch, location)));
+                    }
+                    ui.colored_label(appearance.highlight_color, text);
+                }
+            }
+        }
     });
 }
 
@@ -527,7 +642,7 @@ fn symbol_ui(
         if let Some(result) =
             symbol_context_menu_ui(ui, ctx, other_ctx, symbol, symbol_diff, section, column)
         {
-            ret = Some(DiffViewAction::Navigate(result));
+            ret = Some(result);
         }
     });
     if selected && state.autoscroll_to_highlighted_symbols {
@@ -860,6 +975,58 @@ pub struct SymbolDiffContext<'a> {
     pub diff: &'a ObjDiff,
 }
 
+/// Lists the suggested pairings for symbols without a match, each of which can be accepted as a
+/// symbol mapping.
+#[must_use]
+fn pairing_suggestions_ui(
+    ui: &mut Ui,
+    result: &ObjDiffResult,
+    appearance: &Appearance,
+) -> Option<DiffViewAction> {
+    let (Some((left_obj, _)), Some((right_obj, _))) = (&result.first_obj, &result.second_obj)
+    else {
+        return None;
+    };
+    if result.pairings.is_empty() {
+        return None;
+    }
+    let mut ret = None;
+    CollapsingHeader::new(format!("Suggested pairings ({})", result.pairings.len()))
+        .default_open(false)
+        .show(ui, |ui| {
+            for pairing in &result.pairings {
+                let (left_section, left_symbol) = left_obj.section_symbol(pairing.left);
+                let (right_section, right_symbol) = right_obj.section_symbol(pairing.right);
+                ui.horizontal(|ui| {
+                    ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
+                    let percent = pairing.confidence * 100.0;
+                    ui.colored_label(
+                        match_color_for_symbol(percent, appearance),
+                        format!("{percent:.0}%"),
+                    );
+                    ui.label(left_symbol.demangled_name.as_deref().unwrap_or(&left_symbol.name));
+                    ui.label("→");
+                    ui.label(right_symbol.demangled_name.as_deref().unwrap_or(&right_symbol.name));
+                    if pairing.applied {
+                        ui.colored_label(appearance.deemphasized_text_color, "(applied)");
+                    }
+                    if ui
+                        .button("Accept")
+                        .on_hover_text("Add this pairing to the unit's symbol mappings")
+                        .clicked()
+                    {
+                        ret = Some(DiffViewAction::SetMapping(
+                            View::SymbolDiff,
+                            SymbolRefByName::new(left_symbol, left_section),
+                            SymbolRefByName::new(right_symbol, right_section),
+                        ));
+                    }
+                });
+            }
+        });
+    ret
+}
+
 #[must_use]
 pub fn symbol_diff_ui(
     ui: &mut Ui,
@@ -936,6 +1103,10 @@ pub fn symbol_diff_ui(
         }
     });
 
+    if let Some(action) = pairing_suggestions_ui(ui, result, appearance) {
+        ret = Some(action);
+    }
+
     // Table
     let filter = match &state.search_regex {
         Some(regex) => SymbolFilter::Search(regex),
@@ -0,0 +1,298 @@
+use std::mem::take;
+
+use egui::{Align, Layout, RichText, TextStyle};
+use egui_extras::{Column, TableBuilder};
+use objdiff_core::{
+    diff::fingerprint::{fingerprint_symbol, FunctionFingerprint, MIN_SIMILARITY},
+    index::{IndexedSymbol, ProjectIndex},
+    jobs::{Job, JobQueue, JobResult},
+    obj::{ObjSectionKind, SymbolRef},
+};
+
+use crate::{
+    app::{AppStateRef, ObjectConfig},
+    jobs::start_symbol_index,
+    views::{
+        appearance::Appearance,
+        symbol_diff::{
+            match_color_for_symbol, DiffViewNavigation, DiffViewState, SymbolRefByName, View,
+        },
+    },
+};
+
+/// Maximum number of search results to display.
+const MAX_RESULTS: usize = 500;
+
+/// A function to list similar functions for.
+pub struct SimilarQuery {
+    pub unit: String,
+    pub name: String,
+    pub fingerprint: FunctionFingerprint,
+}
+
+/// The displayed results of a search, at most [`MAX_RESULTS`].
+struct SearchResults {
+    symbols: Vec<(IndexedSymbol, Option<f32>)>,
+    /// Number of results before truncation
+    total: usize,
+}
+
+#[derive(Default)]
+pub struct SymbolSearchViewState {
+    pub query: String,
+    pub similar_to: Option<SimilarQuery>,
+    pub index: Option<Box<ProjectIndex>>,
+    pub index_running: bool,
+    /// Results of the current query, recomputed when the query or index changes
+    results: Option<SearchResults>,
+}
+
+impl SymbolSearchViewState {
+    pub fn pre_update(&mut self, jobs: &mut JobQueue) {
+        jobs.results.retain_mut(|result| {
+            if let JobResult::SymbolIndex(result) = result {
+                self.index = take(result);
+                self.results = None;
+                false
+            } else {
+                true
+            }
+        });
+        self.index_running = jobs.is_running(Job::SymbolIndex);
+    }
+
+    /// Lists the functions similar to a symbol of the current build, indexing the project first
+    /// if needed.
+    pub fn find_similar(
+        &mut self,
+        ctx: &egui::Context,
+        jobs: &mut JobQueue,
+        state: &AppStateRef,
+        diff_state: &DiffViewState,
+        column: usize,
+        symbol_ref: SymbolRef,
+    ) {
+        let Some(build) = &diff_state.build else {
+            return;
+        };
+        let obj = match column {
+            0 => &build.first_obj,
+            _ => &build.second_obj,
+        };
+        let Some((obj, _)) = obj else {
+            return;
+        };
+        let Ok(state) = state.read() else {
+            return;
+        };
+        let (_, symbol) = obj.section_symbol(symbol_ref);
+        match fingerprint_symbol(obj, symbol_ref, &state.config.diff_obj_config) {
+            Ok(fingerprint) => {
+                self.similar_to = Some(SimilarQuery {
+                    unit: diff_state.object_name.clone(),
+                    name: symbol.name.clone(),
+                    fingerprint,
+                });
+                self.results = None;
+            }
+            Err(e) => {
+                log::error!("Failed to fingerprint {}: {e:#}", symbol.name);
+                return;
+            }
+        }
+        if self.index.is_none() {
+            start_symbol_index(ctx, jobs, &state);
+        }
+    }
+
+    /// Returns the results of the current query, searching the index if they're out of date.
+    fn results(&mut self) -> Option<&SearchResults> {
+        let index = self.index.as_ref()?;
+        let similar_to = &self.similar_to;
+        let query = &self.query;
+        Some(self.results.get_or_insert_with(|| {
+            let results = match similar_to {
+                Some(query) => index
+                    .find_similar(&query.fingerprint, MIN_SIMILARITY)
+                    .into_iter()
+                    // Skip the function itself
+                    .filter(|s| !(s.symbol.unit == query.unit && s.symbol.name() == query.name))
+                    .map(|s| (s.symbol, Some(s.similarity)))
+                    .collect::<Vec<_>>(),
+                None => index.search(query).into_iter().map(|s| (s, None)).collect(),
+            };
+            SearchResults {
+                total: results.len(),
+                symbols: results
+                    .into_iter()
+                    .take(MAX_RESULTS)
+                    .map(|(symbol, similarity)| (symbol.clone(), similarity))
+                    .collect(),
+            }
+        }))
+    }
+}
+
+pub fn symbol_search_window(
+    ctx: &egui::Context,
+    show: &mut bool,
+    state: &AppStateRef,
+    search_state: &mut SymbolSearchViewState,
+    diff_state: &mut DiffViewState,
+    jobs: &mut JobQueue,
+    appearance: &Appearance,
+) {
+    let mut selected = None;
+    egui::Window::new("Symbol Search").open(show).default_width(600.0).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if let Some(similar_to) = &search_state.similar_to {
+                ui.label(format!("Functions similar to {}", similar_to.name));
+                if ui.small_button("✖").on_hover_text("Back to search").clicked() {
+                    search_state.similar_to = None;
+                    search_state.results = None;
+                }
+            } else {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut search_state.query)
+                        .hint_text("Symbol name or 0x address"),
+                );
+                if response.changed() {
+                    search_state.results = None;
+                }
+            }
+            let label =
+                if search_state.index.is_some() { "Rebuild index" } else { "Index project" };
+            if ui.add_enabled(!search_state.index_running, egui::Button::new(label)).clicked() {
+                if let Ok(state) = state.read() {
+                    start_symbol_index(ctx, jobs, &state);
+                }
+            }
+        });
+        ui.separator();
+
+        let show_similarity = search_state.similar_to.is_some();
+        let Some(results) = search_state.results() else {
+            if search_state.index_running {
+                ui.label("Indexing…");
+            } else {
+                ui.label("Index the project to search symbols across all units.");
+            }
+            return;
+        };
+        if results.total > results.symbols.len() {
+            ui.label(format!("Showing {} of {} results", results.symbols.len(), results.total));
+        } else {
+            ui.label(format!("{} results", results.total));
+        }
+        let row_height = ui.text_style_height(&TextStyle::Body) + 4.0;
+        let mut table = TableBuilder::new(ui)
+            .striped(true)
+            .cell_layout(Layout::left_to_right(Align::Center))
+            .column(Column::auto().resizable(true))
+            .column(Column::auto().resizable(true))
+            .column(Column::remainder().clip(true))
+            .column(Column::auto());
+        if show_similarity {
+            table = table.column(Column::auto());
+        }
+        table
+            .header(20.0, |mut header| {
+                for title in ["Unit", "Section", "Symbol", "Match", "Similarity"]
+                    .into_iter()
+                    .take(if show_similarity { 5 } else { 4 })
+                {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, results.symbols.len(), |mut row| {
+                    let (symbol, similarity) = &results.symbols[row.index()];
+                    row.col(|ui| {
+                        ui.label(&symbol.unit);
+                    });
+                    row.col(|ui| {
+                        ui.label(&symbol.section);
+                    });
+                    row.col(|ui| {
+                        let name = symbol.demangled_name.as_deref().unwrap_or(symbol.name());
+                        let mut text = RichText::new(name).monospace();
+                        if symbol.target_name.is_none() {
+                            // Symbol only exists in the base object
+                            text = text.color(appearance.deemphasized_text_color);
+                        }
+                        let response = ui.selectable_label(false, text);
+                        let response = match symbol.virtual_address {
+                            Some(address) => response.on_hover_text(format!(
+                                "{} @ {:#x}",
+                                symbol.name(),
+                                address
+                            )),
+                            None => response.on_hover_text(symbol.name()),
+                        };
+                        if response.clicked() {
+                            selected = Some(symbol.clone());
+                        }
+                    });
+                    row.col(|ui| {
+                        if let Some(match_percent) = symbol.match_percent {
+                            ui.colored_label(
+                                match_color_for_symbol(match_percent, appearance),
+                                format!("{:.0}%", match_percent.floor()),
+                            );
+                        }
+                    });
+                    if let Some(similarity) = similarity {
+                        row.col(|ui| {
+                            ui.label(format!("{:.0}%", similarity * 100.0));
+                        });
+                    }
+                });
+            });
+    });
+
+    if let Some(symbol) = selected {
+        navigate_to_symbol(state, diff_state, &symbol);
+    }
+}
+
+/// Selects the unit containing the symbol and opens its diff view.
+fn navigate_to_symbol(state: &AppStateRef, diff_state: &mut DiffViewState, symbol: &IndexedSymbol) {
+    let Ok(mut state) = state.write() else {
+        return;
+    };
+    let Some(object) = state.objects.iter().find(|o| o.name() == symbol.unit) else {
+        return;
+    };
+    let config = ObjectConfig::from(object);
+    let view = match symbol.section_kind {
+        ObjSectionKind::Code => View::FunctionDiff,
+        ObjSectionKind::Data | ObjSectionKind::Bss => View::DataDiff,
+    };
+    let symbol_ref = |name: &Option<String>| {
+        name.as_ref().map(|name| SymbolRefByName {
+            symbol_name: name.clone(),
+            section_name: Some(symbol.section.clone()),
+        })
+    };
+    let nav = DiffViewNavigation {
+        view: Some(view),
+        left_symbol: symbol_ref(&symbol.target_name),
+        right_symbol: symbol_ref(&symbol.base_name),
+    };
+    let same_unit = state.config.selected_obj.as_ref().is_some_and(|o| o.name == symbol.unit);
+    if same_unit && !state.is_selecting_symbol() {
+        // Navigate immediately
+        diff_state.current_view = view;
+        diff_state.symbol_state.left_symbol = nav.left_symbol;
+        diff_state.symbol_state.right_symbol = nav.right_symbol;
+    } else {
+        if same_unit {
+            state.clear_selection();
+        } else {
+            state.set_selected_obj(config);
+        }
+        diff_state.post_build_nav = Some(nav);
+    }
+}